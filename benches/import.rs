@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+use chrono::{Duration, SecondsFormat, TimeZone, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use criterion::{SamplingMode, Throughput};
+use surrealdb_test::models::gamelog_join_leave::GamelogJoinLeave;
+use surrealdb_test::rows::gamelog_join_leave::GamelogJoinLeaveRow;
+use surrealdb_test::zaphkiel::world_instance::WorldInstance;
+
+/// Fixture sizes, in rows.
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+
+/// Rows per batch for the import benchmarks.
+#[cfg(feature = "bench-engines")]
+const BATCH_SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+/// A small xorshift generator, so fixtures are the same on every run.
+struct Seeded(u64);
+
+impl Seeded {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// `n` location strings mixing public, private, friends and hidden instances.
+fn locations(n: usize) -> Vec<String> {
+    let mut rng = Seeded(0x5eed);
+    let regions = ["us", "use", "eu", "jp"];
+    (0..n)
+        .map(|_| {
+            let world = format!(
+                "wrld_{:08x}-0000-0000-0000-{:012x}",
+                rng.below(500),
+                rng.next()
+            );
+            let instance = rng.below(99_999);
+            let region = regions[rng.below(regions.len() as u64) as usize];
+            let owner = format!("usr_{:08x}", rng.below(10_000));
+            match rng.below(4) {
+                0 => format!("{}:{}~region({})", world, instance, region),
+                1 => format!(
+                    "{}:{}~private({})~region({})~nonce({:x})",
+                    world,
+                    instance,
+                    owner,
+                    region,
+                    rng.next()
+                ),
+                2 => format!(
+                    "{}:{}~friends({})~region({})",
+                    world, instance, owner, region
+                ),
+                _ => format!(
+                    "{}:{}~hidden({})~region({})",
+                    world, instance, owner, region
+                ),
+            }
+        })
+        .collect()
+}
+
+/// `n` join/leave rows, one second apart, over 10k players.
+fn join_leave_rows(n: usize) -> Vec<GamelogJoinLeaveRow> {
+    let mut rng = Seeded(0xfeed);
+    let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+    locations(n)
+        .into_iter()
+        .enumerate()
+        .map(|(id, location)| {
+            let player = rng.below(10_000);
+            GamelogJoinLeaveRow {
+                id: id as i64,
+                created_at: (start + Duration::seconds(id as i64))
+                    .to_rfc3339_opts(SecondsFormat::Millis, true),
+                event: if id % 2 == 0 {
+                    "OnPlayerJoined"
+                } else {
+                    "OnPlayerLeft"
+                }
+                .to_string(),
+                display_name: format!("player {}", player),
+                location,
+                user_id: format!("usr_{:08x}", player),
+                time: rng.below(3_600_000) as i64,
+            }
+        })
+        .collect()
+}
+
+fn parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.sampling_mode(SamplingMode::Flat).sample_size(10);
+
+    for size in SIZES {
+        group.throughput(Throughput::Elements(size as u64));
+
+        let locations = locations(size);
+        group.bench_with_input(
+            BenchmarkId::new("WorldInstance::from_str", size),
+            &locations,
+            |b, locations| {
+                b.iter(|| {
+                    for location in locations {
+                        black_box(WorldInstance::from_str(location).ok());
+                    }
+                })
+            },
+        );
+
+        let rows = join_leave_rows(size);
+        group.bench_with_input(
+            BenchmarkId::new("GamelogJoinLeave::try_from", size),
+            &rows,
+            |b, rows| {
+                b.iter_batched(
+                    || rows.clone(),
+                    |rows| {
+                        rows.into_iter()
+                            .map(GamelogJoinLeave::try_from)
+                            .collect::<Result<Vec<_>, _>>()
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+#[cfg(feature = "bench-engines")]
+fn import(c: &mut Criterion) {
+    use std::time::{Duration, Instant};
+
+    use surrealdb::engine::local::{Db, Mem, RocksDb};
+    use surrealdb::Surreal;
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+    use surrealdb_test::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+
+    async fn mem() -> Surreal<Db> {
+        let db = Surreal::new::<Mem>(()).await.unwrap();
+        db.use_ns("bench").use_db("bench").await.unwrap();
+        db
+    }
+
+    async fn rocksdb(dir: &std::path::Path, run: usize) -> Surreal<Db> {
+        let db = Surreal::new::<RocksDb>(dir.join(run.to_string()))
+            .await
+            .unwrap();
+        db.use_ns("bench").use_db("bench").await.unwrap();
+        db
+    }
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let dir = std::env::temp_dir().join(format!("surrealdb-test-bench-{}", std::process::id()));
+    let mut group = c.benchmark_group("import");
+    group.sampling_mode(SamplingMode::Flat).sample_size(10);
+    // Every RocksDB iteration gets a directory of its own, numbered across the whole group, so
+    // no iteration writes into rows an earlier one left behind.
+    let mut run = 0;
+
+    for size in SIZES {
+        group.throughput(Throughput::Elements(size as u64));
+        let rows = join_leave_rows(size)
+            .into_iter()
+            .map(GamelogJoinLeave::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        for batch_size in BATCH_SIZES {
+            let options = BatchOptions {
+                batch_size,
+                ..Default::default()
+            };
+
+            for engine in ["mem", "rocksdb"] {
+                let id = BenchmarkId::new(format!("{}/batch {}", engine, batch_size), size);
+                group.bench_with_input(id, &rows, |b, rows| {
+                    b.to_async(&runtime).iter_custom(|iters| {
+                        run += iters as usize;
+                        let first = run - iters as usize;
+                        let dir = dir.clone();
+                        async move {
+                            let mut total = Duration::ZERO;
+                            for i in 0..iters as usize {
+                                let db = match engine {
+                                    "mem" => mem().await,
+                                    _ => rocksdb(&dir, first + i).await,
+                                };
+                                let importer = Importer::new(db.clone(), options);
+                                let repo = GamelogJoinLeaveRepo::new(db);
+                                let start = Instant::now();
+                                black_box(importer.import_table(repo, rows).await);
+                                total += start.elapsed();
+                                drop(importer);
+                                let _ = std::fs::remove_dir_all(dir.join((first + i).to_string()));
+                            }
+                            total
+                        }
+                    })
+                });
+            }
+        }
+    }
+
+    group.finish();
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[cfg(not(feature = "bench-engines"))]
+fn import(_: &mut Criterion) {
+    eprintln!("import benchmarks need `--features bench-engines`, skipping");
+}
+
+criterion_group!(benches, parsing, import);
+criterion_main!(benches);
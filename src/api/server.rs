@@ -0,0 +1,190 @@
+use std::net::SocketAddr;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use surrealdb::{Connection, Surreal};
+
+use crate::models::online_heatmap::OnlineHeatmap;
+use crate::models::session::Session;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::models::world::EnrichedLocation;
+use crate::models::world_stats::WorldStats;
+use crate::repo::feed::FeedOnlineOfflineRepo;
+use crate::repo::friend::FriendRepo;
+use crate::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+use crate::repo::gamelog_location::GamelogLocationRepo;
+use crate::repo::world::WorldRepo;
+use crate::zaphkiel::timestamp::SourceTimezone;
+
+/// A player, as returned by `GET /players/{usr}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Player {
+    pub user_id: String,
+    pub friend: Option<UsrFriendLogCurrent>,
+    pub sessions: Vec<Session>,
+}
+
+/// The connection to the shared tenant, which holds the `world` cache every account shares.
+#[derive(Debug)]
+pub struct Shared<C: Connection>(pub Surreal<C>);
+
+impl<C: Connection> Clone for Shared<C> {
+    fn clone(&self) -> Self {
+        Shared(self.0.clone())
+    }
+}
+
+/// The `from` and `to` query parameters of `GET /sessions` and `GET /visits`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct TimeRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    /// Check if `at` falls inside the range.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| at >= from) && self.to.is_none_or(|to| at < to)
+    }
+}
+
+/// The `tz` query parameter of `GET /friends/{usr}/online`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct HeatmapQuery {
+    pub tz: Option<SourceTimezone>,
+}
+
+/// A database error, returned to the client as `500 {"error": "..."}`.
+#[derive(Debug)]
+pub struct ApiError(surrealdb::Error);
+
+impl From<surrealdb::Error> for ApiError {
+    fn from(error: surrealdb::Error) -> Self {
+        ApiError(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.0.to_string() }));
+        (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Build the read-only API over `db`, an account's tenant, and `shared`, the shared tenant.
+///
+/// Visits are joined with their cached world from `shared`, since the two live in different
+/// databases.
+///
+/// # Endpoints
+///
+/// - `GET /players/{usr}` - The friend record and sessions of a player.
+/// - `GET /worlds/{wrld}/visits` - Every visit to a world, as an `EnrichedLocation`.
+/// - `GET /visits?from=&to=` - Every visit in the range, as an `EnrichedLocation`.
+/// - `GET /sessions?from=&to=` - Every `Session` that started in the range. Both bounds are
+///   optional RFC 3339 timestamps.
+/// - `GET /stats/worlds` - `WorldStats` for every visited world, most visited first.
+/// - `GET /friends` - Every `UsrFriendLogCurrent`.
+/// - `GET /friends/{usr}/online?tz=` - The `OnlineHeatmap` of a friend. `tz` is a
+///   `SourceTimezone` and defaults to `local`.
+pub fn router<C: Connection>(db: Surreal<C>, shared: Surreal<C>) -> Router {
+    Router::new()
+        .route("/players/:usr", get(player::<C>))
+        .route("/worlds/:wrld/visits", get(world_visits::<C>))
+        .route("/visits", get(visits::<C>))
+        .route("/sessions", get(sessions::<C>))
+        .route("/stats/worlds", get(world_stats::<C>))
+        .route("/friends", get(friends::<C>))
+        .route("/friends/:usr/online", get(friend_online::<C>))
+        .layer(Extension(Shared(shared)))
+        .with_state(db)
+}
+
+/// Serve the API on `addr` until the process is stopped.
+///
+/// `db` and `shared` must be separate connections, since every clone of a `Surreal` shares the
+/// namespace and database it points at.
+pub async fn serve<C: Connection>(
+    db: Surreal<C>,
+    shared: Surreal<C>,
+    addr: SocketAddr,
+) -> Result<(), Box<dyn std::error::Error>> {
+    axum::Server::bind(&addr)
+        .serve(router(db, shared).into_make_service())
+        .await?;
+    Ok(())
+}
+
+async fn player<C: Connection>(
+    State(db): State<Surreal<C>>,
+    Path(usr): Path<String>,
+) -> ApiResult<Player> {
+    let friend = FriendRepo::new(db.clone()).get(&usr).await?;
+    let join_leave = GamelogJoinLeaveRepo::new(db).find_by_player(&usr).await?;
+
+    Ok(Json(Player {
+        user_id: usr,
+        friend,
+        sessions: Session::from_join_leave(&join_leave),
+    }))
+}
+
+async fn world_visits<C: Connection>(
+    State(db): State<Surreal<C>>,
+    Extension(Shared(shared)): Extension<Shared<C>>,
+    Path(wrld): Path<String>,
+) -> ApiResult<Vec<EnrichedLocation>> {
+    let visits = GamelogLocationRepo::new(db).find_by_world(&wrld).await?;
+    let world = WorldRepo::new(shared).get(&wrld).await?;
+    Ok(Json(EnrichedLocation::join(visits, world.as_slice())))
+}
+
+async fn visits<C: Connection>(
+    State(db): State<Surreal<C>>,
+    Extension(Shared(shared)): Extension<Shared<C>>,
+    Query(range): Query<TimeRange>,
+) -> ApiResult<Vec<EnrichedLocation>> {
+    let visits = GamelogLocationRepo::new(db)
+        .range(range.from, range.to)
+        .await?;
+    let worlds = WorldRepo::new(shared).all().await?;
+    Ok(Json(EnrichedLocation::join(visits, &worlds)))
+}
+
+async fn sessions<C: Connection>(
+    State(db): State<Surreal<C>>,
+    Query(range): Query<TimeRange>,
+) -> ApiResult<Vec<Session>> {
+    let sessions = GamelogJoinLeaveRepo::new(db)
+        .sessions(range.from, range.to)
+        .await?;
+    Ok(Json(sessions))
+}
+
+async fn world_stats<C: Connection>(State(db): State<Surreal<C>>) -> ApiResult<Vec<WorldStats>> {
+    let stats = GamelogLocationRepo::new(db).world_stats().await?;
+    Ok(Json(stats))
+}
+
+async fn friends<C: Connection>(
+    State(db): State<Surreal<C>>,
+) -> ApiResult<Vec<UsrFriendLogCurrent>> {
+    let friends = FriendRepo::new(db).all().await?;
+    Ok(Json(friends))
+}
+
+async fn friend_online<C: Connection>(
+    State(db): State<Surreal<C>>,
+    Path(usr): Path<String>,
+    Query(query): Query<HeatmapQuery>,
+) -> ApiResult<OnlineHeatmap> {
+    let events = FeedOnlineOfflineRepo::new(db).find_by_player(&usr).await?;
+    let timezone = query.tz.unwrap_or_default();
+    Ok(Json(OnlineHeatmap::from_events(&usr, &events, timezone)))
+}
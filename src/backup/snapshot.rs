@@ -0,0 +1,641 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use surrealdb::sql::Value;
+use surrealdb::{Connection, Surreal};
+
+use crate::repo::table::TableRepo;
+
+/// The file formats a `Snapshot` can be backed up as.
+///
+/// # Available Formats
+/// - Surql: a SurrealQL script that recreates every record and then the schema.
+/// - NdJsonGz: gzip compressed NDJSON, one tagged `BackupLine` per line.
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::backup::snapshot::BackupFormat;
+///
+/// assert_eq!(BackupFormat::from_path("history.ndjson.gz"), BackupFormat::NdJsonGz);
+/// assert_eq!(BackupFormat::from_path("history.surql"), BackupFormat::Surql);
+/// assert_eq!("ndjson".parse::<BackupFormat>().unwrap(), BackupFormat::NdJsonGz);
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum BackupFormat {
+    #[default]
+    Surql,
+    NdJsonGz,
+}
+
+impl BackupFormat {
+    /// The file extension used for this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BackupFormat::Surql => "surql",
+            BackupFormat::NdJsonGz => "ndjson.gz",
+        }
+    }
+
+    /// The format of the backup at `path`, from its extension: `.gz` is `NdJsonGz`, anything
+    /// else `Surql`.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension() {
+            Some(extension) if extension == "gz" => BackupFormat::NdJsonGz,
+            _ => BackupFormat::Surql,
+        }
+    }
+}
+
+impl FromStr for BackupFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "surql" | "surrealql" => Ok(BackupFormat::Surql),
+            "ndjson" | "ndjson.gz" | "jsonl" => Ok(BackupFormat::NdJsonGz),
+            _ => Err(format!("Unknown backup format: {}", s)),
+        }
+    }
+}
+
+/// The record count and checksum of one table.
+///
+/// `checksum` is the hex SHA-256 of the table's records as JSON, one per line, sorted, so it
+/// doesn't depend on the order SurrealDB returns them in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct TableChecksum {
+    pub table: String,
+    pub count: usize,
+    pub checksum: String,
+}
+
+impl fmt::Display for TableChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.table, self.count, self.checksum)
+    }
+}
+
+impl FromStr for TableChecksum {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(table), Some(count), Some(checksum), None) => Ok(TableChecksum {
+                table: table.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| format!("Invalid record count: {}", count))?,
+                checksum: checksum.to_string(),
+            }),
+            _ => Err(format!("Invalid table checksum: {}", s)),
+        }
+    }
+}
+
+/// A table whose count or checksum differs between a backup and a database.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct TableMismatch {
+    pub table: String,
+    pub expected: Option<TableChecksum>,
+    pub actual: Option<TableChecksum>,
+}
+
+/// The outcome of comparing a database against a backup's manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct IntegrityReport {
+    pub checked: usize,
+    pub mismatches: Vec<TableMismatch>,
+}
+
+impl IntegrityReport {
+    /// Compare the `actual` checksums of a database against the `expected` ones of a backup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use surrealdb_test::backup::snapshot::{IntegrityReport, TableChecksum};
+    ///
+    /// let table = |count: usize| TableChecksum {
+    ///     table: "friend_log_current".to_string(),
+    ///     count,
+    ///     checksum: "00".to_string(),
+    /// };
+    ///
+    /// assert!(IntegrityReport::compare(&[table(1)], &[table(1)]).is_ok());
+    /// assert!(!IntegrityReport::compare(&[table(1)], &[table(2)]).is_ok());
+    /// assert!(!IntegrityReport::compare(&[table(1)], &[]).is_ok());
+    /// ```
+    pub fn compare(expected: &[TableChecksum], actual: &[TableChecksum]) -> Self {
+        let mut tables = expected
+            .iter()
+            .chain(actual)
+            .map(|checksum| checksum.table.as_str())
+            .collect::<Vec<_>>();
+        tables.sort_unstable();
+        tables.dedup();
+
+        let find = |checksums: &[TableChecksum], table: &str| {
+            checksums
+                .iter()
+                .find(|checksum| checksum.table == table)
+                .cloned()
+        };
+        let mismatches = tables
+            .iter()
+            .map(|table| TableMismatch {
+                table: table.to_string(),
+                expected: find(expected, table),
+                actual: find(actual, table),
+            })
+            .filter(|mismatch| mismatch.expected != mismatch.actual)
+            .collect();
+
+        IntegrityReport {
+            checked: tables.len(),
+            mismatches,
+        }
+    }
+
+    /// Check if every table matched.
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for IntegrityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} tables checked, {} mismatched",
+            self.checked,
+            self.mismatches.len()
+        )?;
+        for mismatch in &self.mismatches {
+            let count = |checksum: &Option<TableChecksum>| {
+                checksum
+                    .as_ref()
+                    .map_or("missing".to_string(), |checksum| checksum.count.to_string())
+            };
+            writeln!(
+                f,
+                "  {}: expected {} records, found {}",
+                mismatch.table,
+                count(&mismatch.expected),
+                count(&mismatch.actual)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// One line of an NDJSON backup.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum BackupLine {
+    Header(BackupHeader),
+    Schema(String),
+    Record(BackupRecord),
+    Checksum(TableChecksum),
+}
+
+/// Where and when a backup was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct BackupHeader {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One record of a backup, as a SurrealQL literal such as
+/// `{ id: player:usr_1234, label: 'Tom' }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct BackupRecord {
+    pub table: String,
+    pub record: String,
+}
+
+/// The version of the backup files written by this build.
+pub const BACKUP_VERSION: u32 = 2;
+
+/// Records restored per query.
+const RESTORE_CHUNK: usize = 1000;
+
+/// Everything in one namespace/database: every table `INFO FOR DB` lists, which takes in the
+/// model tables, the social graph, the avatar timeline and `script_migration`, and the schema
+/// definitions.
+///
+/// Records are kept as SurrealQL literals, sorted within their table, so record links and
+/// datetimes survive the round trip.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub struct Snapshot {
+    pub schema: Vec<String>,
+    pub tables: BTreeMap<String, Vec<String>>,
+}
+
+impl Snapshot {
+    /// Read everything from the namespace/database `db` points at.
+    #[tracing::instrument(skip_all)]
+    pub async fn read<C: Connection>(db: &Surreal<C>) -> Result<Self, Box<dyn Error>> {
+        let repo = TableRepo::new(db.clone());
+        let mut tables = BTreeMap::new();
+        for table in repo.tables().await? {
+            let mut records = repo
+                .records(&table)
+                .await?
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>();
+            records.sort_unstable();
+            tables.insert(table, records);
+        }
+
+        Ok(Snapshot {
+            schema: repo.schema().await?,
+            tables,
+        })
+    }
+
+    /// Read a backup written by `write`, checking it against the checksums it carries.
+    ///
+    /// A SurrealQL backup can only be run, not read back, so only `NdJsonGz` is accepted.
+    pub fn open(path: impl AsRef<Path>) -> Result<(Self, Vec<TableChecksum>), Box<dyn Error>> {
+        if BackupFormat::from_path(&path) != BackupFormat::NdJsonGz {
+            return Err("only NDJSON backups can be read into a snapshot".into());
+        }
+        let reader = BufReader::new(GzDecoder::new(File::open(path)?));
+
+        let mut snapshot = Snapshot::default();
+        let mut manifest = Vec::new();
+        for line in reader.lines() {
+            match serde_json::from_str(&line?)? {
+                BackupLine::Header(header) if header.version != BACKUP_VERSION => {
+                    return Err(format!("unsupported backup version: {}", header.version).into());
+                }
+                BackupLine::Header(_) => {}
+                BackupLine::Schema(definition) => snapshot.schema.push(definition),
+                BackupLine::Record(record) => snapshot
+                    .tables
+                    .entry(record.table)
+                    .or_default()
+                    .push(record.record),
+                BackupLine::Checksum(checksum) => manifest.push(checksum),
+            }
+        }
+        // Empty tables have no records, only a checksum.
+        for checksum in &manifest {
+            snapshot.tables.entry(checksum.table.clone()).or_default();
+        }
+
+        let report = IntegrityReport::compare(&manifest, &snapshot.manifest());
+        if !report.is_ok() {
+            return Err(format!("backup is corrupt: {}", report).into());
+        }
+        Ok((snapshot, manifest))
+    }
+
+    /// The record count and checksum of every table.
+    pub fn manifest(&self) -> Vec<TableChecksum> {
+        self.tables
+            .iter()
+            .map(|(table, records)| checksum(table, records))
+            .collect()
+    }
+
+    /// How many records the snapshot holds, not counting the schema.
+    pub fn len(&self) -> usize {
+        self.tables.values().map(Vec::len).sum()
+    }
+
+    /// Check if the snapshot holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write the snapshot to `path` as `format`, returning its manifest.
+    pub fn write(
+        &self,
+        path: impl AsRef<Path>,
+        format: BackupFormat,
+    ) -> Result<Vec<TableChecksum>, Box<dyn Error>> {
+        let out = BufWriter::new(File::create(path)?);
+        match format {
+            BackupFormat::Surql => self.write_surql(out),
+            BackupFormat::NdJsonGz => {
+                let mut out = GzEncoder::new(out, Compression::default());
+                let manifest = self.write_ndjson(&mut out)?;
+                out.finish()?.flush()?;
+                Ok(manifest)
+            }
+        }
+    }
+
+    /// Write the snapshot as NDJSON lines, header first and checksums last.
+    pub fn write_ndjson<W: Write>(&self, mut out: W) -> Result<Vec<TableChecksum>, Box<dyn Error>> {
+        let manifest = self.manifest();
+        let header = BackupLine::Header(BackupHeader {
+            version: BACKUP_VERSION,
+            created_at: Utc::now(),
+        });
+        let records = self.tables.iter().flat_map(|(table, records)| {
+            records.iter().map(|record| {
+                BackupLine::Record(BackupRecord {
+                    table: table.clone(),
+                    record: record.clone(),
+                })
+            })
+        });
+        let lines = std::iter::once(header)
+            .chain(self.schema.iter().cloned().map(BackupLine::Schema))
+            .chain(records)
+            .chain(manifest.iter().cloned().map(BackupLine::Checksum));
+        for line in lines {
+            serde_json::to_writer(&mut out, &line)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(manifest)
+    }
+
+    /// Write the snapshot as a SurrealQL script.
+    ///
+    /// # What it does
+    ///
+    /// * The manifest is written first, as `-- checksum <table> <count> <sha256>` comments.
+    /// * Records come next, see `statements`.
+    /// * The schema comes last, so `script_migration.executed_at` keeps its original value
+    ///   instead of the `VALUE` clause's `time::now()`.
+    pub fn write_surql<W: Write>(&self, mut out: W) -> Result<Vec<TableChecksum>, Box<dyn Error>> {
+        let manifest = self.manifest();
+        writeln!(out, "-- surrealdb-test backup, version {}", BACKUP_VERSION)?;
+        writeln!(out, "-- created_at {}", Utc::now().to_rfc3339())?;
+        for checksum in &manifest {
+            writeln!(out, "-- checksum {}", checksum)?;
+        }
+
+        for statement in self.statements()? {
+            writeln!(out, "{}", statement)?;
+        }
+        for definition in self.definitions() {
+            writeln!(out, "{}", definition)?;
+        }
+        out.flush()?;
+        Ok(manifest)
+    }
+
+    /// The statements recreating every record: `UPDATE` for records, then `RELATE` for graph
+    /// edges, the records with an `in` and an `out` record link, once the records they link
+    /// exist.
+    pub fn statements(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut records = Vec::new();
+        let mut edges = Vec::new();
+        for record in self.tables.values().flatten() {
+            match statement(record)? {
+                (true, relate) => edges.push(relate),
+                (false, update) => records.push(update),
+            }
+        }
+        records.extend(edges);
+        Ok(records)
+    }
+
+    /// Write the snapshot into the database `db` points at, records first and schema last.
+    #[tracing::instrument(skip_all, fields(records = self.len()))]
+    pub async fn restore<C: Connection>(self, db: &Surreal<C>) -> Result<(), Box<dyn Error>> {
+        let repo = TableRepo::new(db.clone());
+        for statements in self.statements()?.chunks(RESTORE_CHUNK) {
+            repo.execute(statements).await?;
+        }
+        repo.execute(&self.definitions()).await?;
+        Ok(())
+    }
+
+    /// The schema definitions, each ending in `;`.
+    fn definitions(&self) -> Vec<String> {
+        self.schema
+            .iter()
+            .map(|definition| format!("{};", definition.trim_end_matches(';')))
+            .collect()
+    }
+}
+
+/// Restore the backup at `path` into the database `db` points at, which must be empty, and
+/// check the result against the backup's checksums.
+///
+/// SurrealQL backups are run as they are. NDJSON backups are read into a `Snapshot` first.
+#[tracing::instrument(skip_all, fields(path = %path.as_ref().display()))]
+pub async fn restore<C: Connection>(
+    db: &Surreal<C>,
+    path: impl AsRef<Path>,
+) -> Result<IntegrityReport, Box<dyn Error>> {
+    if !Snapshot::read(db).await?.is_empty() {
+        return Err("can't restore into a database that already holds records".into());
+    }
+
+    let manifest = match BackupFormat::from_path(&path) {
+        BackupFormat::Surql => {
+            let mut script = String::new();
+            File::open(&path)?.read_to_string(&mut script)?;
+            TableRepo::new(db.clone()).run(&script).await?;
+            surql_manifest(&script)?
+        }
+        BackupFormat::NdJsonGz => {
+            let (snapshot, manifest) = Snapshot::open(&path)?;
+            snapshot.restore(db).await?;
+            manifest
+        }
+    };
+
+    verify(db, &manifest).await
+}
+
+/// Compare the database `db` points at against a backup's `manifest`.
+pub async fn verify<C: Connection>(
+    db: &Surreal<C>,
+    manifest: &[TableChecksum],
+) -> Result<IntegrityReport, Box<dyn Error>> {
+    let actual = Snapshot::read(db).await?.manifest();
+    Ok(IntegrityReport::compare(manifest, &actual))
+}
+
+/// The checksums of the backup at `path`, in either format.
+pub fn manifest(path: impl AsRef<Path>) -> Result<Vec<TableChecksum>, Box<dyn Error>> {
+    match BackupFormat::from_path(&path) {
+        BackupFormat::Surql => {
+            let mut script = String::new();
+            File::open(&path)?.read_to_string(&mut script)?;
+            Ok(surql_manifest(&script)?)
+        }
+        BackupFormat::NdJsonGz => Ok(Snapshot::open(path)?.1),
+    }
+}
+
+/// The `-- checksum` comments at the top of a SurrealQL backup.
+fn surql_manifest(script: &str) -> Result<Vec<TableChecksum>, String> {
+    script
+        .lines()
+        .take_while(|line| line.starts_with("--"))
+        .filter_map(|line| line.strip_prefix("-- checksum "))
+        .map(str::parse)
+        .collect()
+}
+
+fn checksum(table: &str, records: &[String]) -> TableChecksum {
+    let mut lines = records.iter().collect::<Vec<_>>();
+    lines.sort_unstable();
+
+    let mut hasher = Sha256::new();
+    for line in &lines {
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+    TableChecksum {
+        table: table.to_string(),
+        count: records.len(),
+        checksum: hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    }
+}
+
+/// The statement recreating `record`, and whether it is a graph edge.
+///
+/// Edges are written as `RELATE <in>-><id>-><out>`, so SurrealDB links them into the graph
+/// again, and every other record as `UPDATE <id>`.
+fn statement(record: &str) -> Result<(bool, String), Box<dyn Error>> {
+    let Value::Object(mut object) = surrealdb::sql::value(record)? else {
+        return Err(format!("Invalid record: {}", record).into());
+    };
+    let id = object
+        .remove("id")
+        .ok_or_else(|| format!("Record without an id: {}", record))?;
+    match (object.get("in"), object.get("out")) {
+        (Some(from @ Value::Thing(_)), Some(to @ Value::Thing(_))) => {
+            let relate = format!("RELATE {}->{}->{}", from, id, to);
+            object.remove("in");
+            object.remove("out");
+            Ok((
+                true,
+                format!("{} CONTENT {} RETURN NONE;", relate, Value::Object(object)),
+            ))
+        }
+        _ => Ok((
+            false,
+            format!(
+                "UPDATE {} CONTENT {} RETURN NONE;",
+                id,
+                Value::Object(object)
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::backup::snapshot::{surql_manifest, BackupFormat, Snapshot, TableChecksum};
+
+    /// `literal` as `Snapshot::read` would have written it.
+    fn record(literal: &str) -> String {
+        surrealdb::sql::value(literal).unwrap().to_string()
+    }
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            schema: vec!["DEFINE TABLE friend_log_current SCHEMALESS".to_string()],
+            tables: BTreeMap::from([
+                (
+                    "friend_log_current".to_string(),
+                    vec![record(
+                        r#"{ id: friend_log_current:usr_a, display_name: 'a "quoted" name' }"#,
+                    )],
+                ),
+                (
+                    "met".to_string(),
+                    vec![record(
+                        "{ id: met:1, in: player:usr_a, out: player:usr_b, weight: 1, \
+                        first_seen: '2023-04-29T10:00:00Z' }",
+                    )],
+                ),
+                (
+                    "player".to_string(),
+                    vec![
+                        record("{ id: player:usr_a, label: 'a' }"),
+                        record("{ id: player:usr_b, label: 'b' }"),
+                    ],
+                ),
+                (
+                    "script_migration".to_string(),
+                    vec![record(
+                        "{ id: script_migration:1, script_name: '20230521_194846_MigrationsTestOne', \
+                        executed_at: '2023-04-29T10:00:00Z' }",
+                    )],
+                ),
+                ("wore".to_string(), Vec::new()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_ndjson_round_trips() {
+        let snapshot = snapshot();
+        let path = std::env::temp_dir().join(format!(
+            "surrealdb-test-backup-{}.{}",
+            std::process::id(),
+            BackupFormat::NdJsonGz.extension()
+        ));
+        let manifest = snapshot.write(&path, BackupFormat::NdJsonGz).unwrap();
+
+        let (read, read_manifest) = Snapshot::open(&path).unwrap();
+        assert_eq!(read, snapshot);
+        assert_eq!(read_manifest, manifest);
+        assert_eq!(manifest.len(), 5);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_surql_carries_its_manifest() {
+        let snapshot = snapshot();
+        let mut out = Vec::new();
+        let manifest = snapshot.write_surql(&mut out).unwrap();
+        let script = String::from_utf8(out).unwrap();
+
+        assert_eq!(surql_manifest(&script).unwrap(), manifest);
+        assert!(script.contains("UPDATE friend_log_current:usr_a CONTENT"));
+        assert!(script.contains(r#"a "quoted" name"#));
+        let relate = script
+            .find("RELATE player:usr_a->met:1->player:usr_b")
+            .unwrap();
+        assert!(relate > script.find("UPDATE player:usr_b").unwrap());
+        assert!(script
+            .trim_end()
+            .ends_with("DEFINE TABLE friend_log_current SCHEMALESS;"));
+    }
+
+    #[test]
+    fn test_manifest_ignores_record_order() {
+        let mut snapshot = snapshot();
+        let manifest = snapshot.manifest();
+        let players = snapshot.tables.get_mut("player").unwrap();
+        players.reverse();
+        assert_eq!(snapshot.manifest(), manifest);
+
+        snapshot.tables.get_mut("player").unwrap().pop();
+        assert_ne!(snapshot.manifest(), manifest);
+        assert_eq!("player 1 00".parse::<TableChecksum>().unwrap().count, 1);
+    }
+}
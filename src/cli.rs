@@ -0,0 +1,156 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use surrealdb_test::export::anonymise::Granularity;
+use surrealdb_test::export::exporter::{ExportFormat, ExportTable};
+use surrealdb_test::graph::writers::GraphFormat;
+use surrealdb_test::logging::subscriber::LogFormat;
+use surrealdb_test::repo::insert_strategy::InsertStrategy;
+use surrealdb_test::repo::purge::TableRetention;
+use surrealdb_test::zaphkiel::timestamp::SourceTimezone;
+
+/// Import VRCX data into SurrealDB and query it.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// The log filter, e.g. `debug` or `surrealdb_test=trace`. Overrides `RUST_LOG` and
+    /// `verbose` in the settings.
+    #[arg(long, global = true)]
+    pub log_level: Option<String>,
+    /// How logs are written: `pretty` or `json`.
+    #[arg(long, global = true, default_value = "pretty")]
+    pub log_format: LogFormat,
+    /// The VRChat account to work on, as a user id such as `usr_1234-abcd` or as VRCX prefixes
+    /// its tables. Without it, `import` writes every account to its own database and the other
+    /// commands use the configured `ns` and `tb`.
+    #[arg(long, global = true)]
+    pub account: Option<String>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Import one or more VRCX databases into SurrealDB.
+    ///
+    /// The world and avatar caches go to the shared database, and visited worlds missing from
+    /// the cache are listed. Join/leave rows without a user id get one from the names seen in
+    /// the friend list and the feeds, and rows whose name several players used at the time are
+    /// listed. The social graph, avatar timeline, GPS moves and invites of every account are
+    /// rebuilt from what it holds after the import.
+    Import {
+        /// The paths to `vrcx.sqlite`. Several files are merged into one history.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Merge duplicate rows by their natural key even when importing a single file.
+        #[arg(long)]
+        merge: bool,
+        /// Rows written per batch.
+        #[arg(long, default_value_t = 1000)]
+        batch_size: usize,
+        /// Batches written at once.
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// How batches are written: `insert`, `transaction` or `upsert`.
+        #[arg(long, default_value = "insert")]
+        strategy: InsertStrategy,
+        /// Times a failed batch is retried before it is split to find the bad rows.
+        #[arg(long, default_value_t = 2)]
+        retries: usize,
+        /// The timezone of timestamps written without an offset: `local`, `utc`, an IANA name
+        /// such as `Europe/Berlin`, or an offset such as `+09:00`.
+        #[arg(long, default_value = "local")]
+        timezone: SourceTimezone,
+    },
+    /// Copy the database into another one in the same namespace, anonymised for sharing.
+    Anonymise {
+        /// The database to write the anonymised copy to.
+        to: String,
+        /// The secret key pseudonyms are derived from. Reuse it to anonymise later data the
+        /// same way.
+        #[arg(long)]
+        key: String,
+        /// How far timestamps are rounded down: `second`, `minute`, `hour` or `day`.
+        #[arg(long, default_value = "hour")]
+        granularity: Granularity,
+    },
+    /// Write tables to CSV, NDJSON or Parquet files, one `<table>.<extension>` file each.
+    Export {
+        /// The directory to write the files to. It is created if needed.
+        dir: PathBuf,
+        /// `csv`, `ndjson` or `parquet`.
+        #[arg(long, default_value = "csv")]
+        format: ExportFormat,
+        /// Only export this table, e.g. `sessions`. Repeat it for several. Defaults to every
+        /// table.
+        #[arg(long = "table")]
+        tables: Vec<ExportTable>,
+        /// Only rows at or after this time, e.g. `2023-04-01T00:00:00Z`.
+        #[arg(long)]
+        from: Option<DateTime<Utc>>,
+        /// Only rows before this time.
+        #[arg(long)]
+        to: Option<DateTime<Utc>>,
+        /// Anonymise the rows with this secret key, as `anonymise` does.
+        #[arg(long)]
+        key: Option<String>,
+        /// How far timestamps are rounded down when anonymising.
+        #[arg(long, default_value = "hour")]
+        granularity: Granularity,
+    },
+    /// Delete old records, or everything known about one player.
+    Purge {
+        /// Delete records older than this many days from every table with a timestamp.
+        #[arg(long)]
+        older_than: Option<u32>,
+        /// Override the retention of one table, as `<table>=<days>`, or `<table>` to keep it.
+        #[arg(long = "retain")]
+        retain: Vec<TableRetention>,
+        /// Delete every record of this user id, for "forget me" requests.
+        #[arg(long)]
+        forget: Option<String>,
+        /// Only list what would be deleted.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Back the database up to a file: SurrealQL, or gzip NDJSON when the path ends in `.gz`.
+    Backup {
+        /// The file to write, e.g. `history.surql` or `history.ndjson.gz`.
+        path: PathBuf,
+    },
+    /// Restore a backup into the database, which must be empty, and check the result.
+    Restore {
+        /// The backup to read.
+        path: PathBuf,
+    },
+    /// Compare the record counts and checksums of the database against a backup.
+    Verify {
+        /// The backup to compare against.
+        path: PathBuf,
+    },
+    /// Write the social graph to stdout, to open in Gephi, yEd or Graphviz.
+    Graph {
+        /// `graphml` or `dot`.
+        #[arg(long, default_value = "graphml")]
+        format: GraphFormat,
+        /// Only edges seen at or after this time, e.g. `2023-04-01T00:00:00Z`.
+        #[arg(long)]
+        from: Option<DateTime<Utc>>,
+        /// Only edges seen before this time.
+        #[arg(long)]
+        to: Option<DateTime<Utc>>,
+        /// Include edge weights.
+        #[arg(long)]
+        weights: bool,
+    },
+    /// Start a local read-only HTTP/JSON API over the imported data.
+    Serve {
+        /// The address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: SocketAddr,
+    },
+    /// Print live join/leave and friend events as they arrive.
+    Tail,
+}
@@ -0,0 +1,789 @@
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use surrealdb::{Connection, Surreal};
+
+use crate::graph::avatar_timeline::{AvatarTimeline, Wear};
+use crate::graph::social_graph::{Edge, EdgeKind, GraphOptions, Node, NodeKind, SocialGraph};
+use crate::import::batch::BatchOptions;
+use crate::import::importer::{Importer, TableReport, VrcxTables};
+use crate::models::avatar_history::AvatarHistory;
+use crate::models::feed_avatar::FeedAvatar;
+use crate::models::feed_bio::FeedBio;
+use crate::models::feed_gps::FeedGps;
+use crate::models::feed_online_offline::FeedOnlineOffline;
+use crate::models::feed_status::FeedStatus;
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::gamelog_location::GamelogLocation;
+use crate::models::moderation::{Moderation, ModerationEvent};
+use crate::models::notification::Notification;
+use crate::models::session::Session;
+use crate::models::source::Source;
+use crate::models::tenant::{account_prefix, Tenant};
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::repo::avatar_history::AvatarHistoryRepo;
+use crate::repo::feed::{
+    FeedAvatarRepo, FeedBioRepo, FeedGpsRepo, FeedOnlineOfflineRepo, FeedStatusRepo,
+};
+use crate::repo::friend::FriendRepo;
+use crate::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+use crate::repo::gamelog_location::GamelogLocationRepo;
+use crate::repo::moderation::ModerationRepo;
+use crate::repo::moderation_history::ModerationHistoryRepo;
+use crate::repo::notification::NotificationRepo;
+use crate::repo::table::TableRepo;
+use crate::zaphkiel::location::Location;
+use crate::zaphkiel::world_instance::WorldInstance;
+
+/// How far timestamps are rounded down when anonymising.
+///
+/// # Available Variants
+/// - Second
+/// - Minute
+/// - Hour
+/// - Day, in UTC
+///
+/// # Examples
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use surrealdb_test::export::anonymise::Granularity;
+///
+/// let at = Utc.with_ymd_and_hms(2023, 4, 29, 10, 42, 7).unwrap();
+/// assert_eq!(
+///     Granularity::Hour.coarsen(at),
+///     Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap()
+/// );
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum Granularity {
+    Second,
+    Minute,
+    #[default]
+    Hour,
+    Day,
+}
+
+impl Granularity {
+    /// The length of one step, in seconds.
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Granularity::Second => 1,
+            Granularity::Minute => 60,
+            Granularity::Hour => 60 * 60,
+            Granularity::Day => 24 * 60 * 60,
+        }
+    }
+
+    /// Round `at` down to the start of its step.
+    pub fn coarsen(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let seconds = at.timestamp();
+        Utc.timestamp_opt(seconds - seconds.rem_euclid(self.seconds()), 0)
+            .unwrap()
+    }
+}
+
+impl FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "second" => Ok(Granularity::Second),
+            "minute" => Ok(Granularity::Minute),
+            "hour" => Ok(Granularity::Hour),
+            "day" => Ok(Granularity::Day),
+            _ => Err(format!("Unknown granularity: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Granularity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Granularity::Second => write!(f, "second"),
+            Granularity::Minute => write!(f, "minute"),
+            Granularity::Hour => write!(f, "hour"),
+            Granularity::Day => write!(f, "day"),
+        }
+    }
+}
+
+/// Pseudonymises records for sharing.
+///
+/// # What it does
+///
+/// * `user_id`s become `usr_` followed by a UUID-shaped HMAC-SHA256 of the id under `key`.
+///   Ids are hashed as VRCX prefixes its tables, so `usr_1234-abcd` and the account
+///   `usr1234abcd` get the same pseudonym.
+/// * `display_name`s become `player_` followed by a keyed hash of the name.
+/// * The owner ids in `hidden(…)`, `friends(…)` and `private(…)` are emptied, which keeps the
+///   access type. The `nonce` is dropped.
+/// * Timestamps are rounded down to `granularity`, and the raw `created_at` text is dropped.
+/// * Numeric record ids become a keyed hash of the original id, which is itself a hash of the
+///   original natural key. Rows that only differ by less than `granularity` keep apart.
+/// * Free text a player wrote, such as bios, status descriptions and notification messages, is
+///   dropped, since it can name anyone.
+/// * `Source` files are cut down to their file name.
+///
+/// The same key always gives the same pseudonyms, so records stay linked across tables and
+/// exports. Without the key they can't be reversed.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::export::anonymise::{Anonymise, Anonymiser};
+/// use surrealdb_test::zaphkiel::world_instance::WorldInstance;
+///
+/// let anonymiser = Anonymiser::new("secret");
+/// assert_eq!(
+///     anonymiser.pseudonym("usr_1234-abcd"),
+///     anonymiser.pseudonym("usr1234abcd")
+/// );
+/// assert_ne!(anonymiser.pseudonym("usr_1234"), Anonymiser::new("other").pseudonym("usr_1234"));
+///
+/// let instance = WorldInstance::try_from("wrld_1234:1234~private(usr_1234)~region(eu)").unwrap();
+/// assert_eq!(
+///     instance.anonymise(&anonymiser).to_string(),
+///     "wrld_1234:1234~private()~region(eu)"
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct Anonymiser {
+    key: Vec<u8>,
+    pub granularity: Granularity,
+}
+
+impl fmt::Debug for Anonymiser {
+    /// Leave the key out, so it never ends up in logs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Anonymiser")
+            .field("granularity", &self.granularity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Anonymiser {
+    /// Create a new `Anonymiser` hashing with `key`, coarsening timestamps to the hour.
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        Anonymiser {
+            key: key.as_ref().to_vec(),
+            granularity: Granularity::default(),
+        }
+    }
+
+    /// Set how far timestamps are rounded down.
+    pub fn granularity(mut self, granularity: Granularity) -> Self {
+        self.granularity = granularity;
+        self
+    }
+
+    /// The pseudonym of a user id, e.g. `usr_9f86d081-884c-7d65-9a2f-eaa0c55ad015`.
+    pub fn pseudonym(&self, user_id: &str) -> String {
+        let hex = self.hash("user_id", &account_prefix(user_id));
+        format!(
+            "usr_{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+
+    /// The alias of a display name, e.g. `player_9f86d08188`.
+    pub fn alias(&self, display_name: &str) -> String {
+        format!("player_{}", &self.hash("display_name", display_name)[..10])
+    }
+
+    /// The record id of a row whose original record id was `id`.
+    pub fn record_id(&self, id: i64) -> i64 {
+        let hex = self.hash("record_id", &id.to_string());
+        (u64::from_str_radix(&hex[..16], 16).expect("hex digits") & i64::MAX as u64) as i64
+    }
+
+    /// Round `at` down to the granularity.
+    pub fn coarsen(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        self.granularity.coarsen(at)
+    }
+
+    /// The hex HMAC-SHA256 of `value`, with `domain` keeping ids and names apart.
+    fn hash(&self, domain: &str, value: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC takes any key");
+        mac.update(domain.as_bytes());
+        mac.update(&[0]);
+        mac.update(value.as_bytes());
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Copy every table of `from` into `to`, anonymised.
+    ///
+    /// # What it does
+    ///
+    /// * Fails before writing anything if `from` holds records in a table not listed in
+    ///   `ANONYMISED_TABLES`, the social graph or `script_migration`, so nothing is copied
+    ///   half anonymised.
+    /// * Reads the game log, the friend list, the feeds, the avatar history, the moderation
+    ///   list and its history, the notifications, the social graph and the avatar timeline
+    ///   from `from`.
+    /// * Anonymises every record. Records keep apart even when coarsening gives them the same
+    ///   natural key, see `Anonymiser::record_id`.
+    /// * Writes the records to `to` with `options`, and stores the graph and the timeline there.
+    ///
+    /// The `world` and `avatar` caches live in the shared database and are not copied.
+    ///
+    /// `db` is left pointing at `to`.
+    #[tracing::instrument(skip_all, fields(from = %from, to = %to))]
+    pub async fn copy_database<C: Connection>(
+        &self,
+        db: &Surreal<C>,
+        from: &Tenant,
+        to: &Tenant,
+        options: BatchOptions,
+    ) -> Result<Vec<TableReport>, Box<dyn Error>> {
+        if from == to {
+            return Err(format!("can't anonymise {} in place", from).into());
+        }
+
+        from.select(db).await?;
+        let repo = TableRepo::new(db.clone());
+        for table in repo.tables().await? {
+            let known = ANONYMISED_TABLES.contains(&table.as_str())
+                || NodeKind::ALL.iter().any(|kind| kind.table() == table)
+                || EdgeKind::ALL.iter().any(|kind| kind.table() == table)
+                || table == "script_migration";
+            if !known && repo.count(&table).await? > 0 {
+                return Err(format!("can't anonymise {}: unknown table {}", from, table).into());
+            }
+        }
+
+        let tables = VrcxTables {
+            locations: GamelogLocationRepo::new(db.clone())
+                .all()
+                .await?
+                .anonymise(self),
+            join_leave: GamelogJoinLeaveRepo::new(db.clone())
+                .all()
+                .await?
+                .anonymise(self),
+            friends: FriendRepo::new(db.clone()).all().await?.anonymise(self),
+            feed_avatar: FeedAvatarRepo::new(db.clone()).all().await?.anonymise(self),
+            avatar_history: AvatarHistoryRepo::new(db.clone())
+                .all()
+                .await?
+                .anonymise(self),
+            feed_status: FeedStatusRepo::new(db.clone()).all().await?.anonymise(self),
+            feed_bio: FeedBioRepo::new(db.clone()).all().await?.anonymise(self),
+            feed_online_offline: FeedOnlineOfflineRepo::new(db.clone())
+                .all()
+                .await?
+                .anonymise(self),
+            feed_gps: FeedGpsRepo::new(db.clone()).all().await?.anonymise(self),
+            notifications: NotificationRepo::new(db.clone())
+                .all()
+                .await?
+                .anonymise(self),
+            ..Default::default()
+        };
+        let moderation = ModerationRepo::new(db.clone()).all().await?.anonymise(self);
+        let moderation_history = ModerationHistoryRepo::new(db.clone())
+            .all()
+            .await?
+            .anonymise(self);
+        let mut graph = SocialGraph::fetch(db, &GraphOptions::default()).await?;
+        // The timeline carries the whole `Wear`, an edge only its start and end.
+        graph.edges.retain(|edge| edge.kind != EdgeKind::Wore);
+        let timeline = AvatarTimeline::fetch(db, None).await?;
+
+        to.select(db).await?;
+        let importer = Importer::new(db.clone(), options);
+        let mut reports = importer.write(tables).await;
+        reports.push(
+            importer
+                .import_table(ModerationRepo::new(db.clone()), &moderation)
+                .await,
+        );
+        reports.push(
+            importer
+                .import_table(ModerationHistoryRepo::new(db.clone()), &moderation_history)
+                .await,
+        );
+        graph.anonymise(self).store(db).await?;
+        AvatarTimeline {
+            wears: timeline.wears.anonymise(self),
+        }
+        .store(db)
+        .await?;
+
+        Ok(reports)
+    }
+}
+
+/// The record tables `Anonymiser::copy_database` anonymises and copies, besides the social
+/// graph.
+pub const ANONYMISED_TABLES: [&str; 12] = [
+    "gamelog_locations",
+    "gamelog_join_leave",
+    "friend_log_current",
+    "feed_avatar",
+    "avatar_history",
+    "feed_status",
+    "feed_bio",
+    "feed_online_offline",
+    "feed_gps",
+    "moderation",
+    "moderation_history",
+    "notification",
+];
+
+/// A value that can be anonymised by an `Anonymiser`.
+pub trait Anonymise: Sized {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self;
+}
+
+impl<T: Anonymise> Anonymise for Vec<T> {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        self.into_iter()
+            .map(|value| value.anonymise(anonymiser))
+            .collect()
+    }
+}
+
+impl<T: Anonymise> Anonymise for Option<T> {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        self.map(|value| value.anonymise(anonymiser))
+    }
+}
+
+impl Anonymise for WorldInstance {
+    fn anonymise(self, _: &Anonymiser) -> Self {
+        let strip = |owner: Option<String>| owner.map(|_| String::new());
+        WorldInstance {
+            hidden: strip(self.hidden),
+            friends: strip(self.friends),
+            private: strip(self.private),
+            nonce: None,
+            ..self
+        }
+    }
+}
+
+impl Anonymise for Location {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        match self {
+            Location::Instance(instance) => Location::Instance(instance.anonymise(anonymiser)),
+            location => location,
+        }
+    }
+}
+
+impl Anonymise for Source {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        Source {
+            file: Path::new(&self.file)
+                .file_name()
+                .map_or(String::new(), |name| name.to_string_lossy().into_owned()),
+            account: self
+                .account
+                .map(|account| account_prefix(&anonymiser.pseudonym(&account))),
+        }
+    }
+}
+
+impl Anonymise for GamelogLocation {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        GamelogLocation {
+            id: anonymiser.record_id(self.id),
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            world_instance: self.world_instance.anonymise(anonymiser),
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for GamelogJoinLeave {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        GamelogJoinLeave {
+            id: anonymiser.record_id(self.id),
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            display_name: anonymiser.alias(&self.display_name),
+            location: self.location.anonymise(anonymiser),
+            user_id: self.user_id.map(|user_id| anonymiser.pseudonym(&user_id)),
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for UsrFriendLogCurrent {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        UsrFriendLogCurrent {
+            user_id: anonymiser.pseudonym(&self.user_id),
+            display_name: anonymiser.alias(&self.display_name),
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for FeedAvatar {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        FeedAvatar {
+            id: anonymiser.record_id(self.id),
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            user_id: anonymiser.pseudonym(&self.user_id),
+            display_name: anonymiser.alias(&self.display_name),
+            owner_id: self
+                .owner_id
+                .map(|owner_id| anonymiser.pseudonym(&owner_id)),
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for AvatarHistory {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        AvatarHistory {
+            id: anonymiser.record_id(self.id),
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for FeedStatus {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        FeedStatus {
+            id: anonymiser.record_id(self.id),
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            user_id: anonymiser.pseudonym(&self.user_id),
+            display_name: anonymiser.alias(&self.display_name),
+            status_description: None,
+            previous_status_description: None,
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for FeedBio {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        FeedBio {
+            id: anonymiser.record_id(self.id),
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            user_id: anonymiser.pseudonym(&self.user_id),
+            display_name: anonymiser.alias(&self.display_name),
+            bio: String::new(),
+            previous_bio: None,
+            sources: self.sources.anonymise(anonymiser),
+        }
+    }
+}
+
+impl Anonymise for FeedOnlineOffline {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        FeedOnlineOffline {
+            id: anonymiser.record_id(self.id),
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            user_id: anonymiser.pseudonym(&self.user_id),
+            display_name: anonymiser.alias(&self.display_name),
+            location: self.location.anonymise(anonymiser),
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for FeedGps {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        FeedGps {
+            id: anonymiser.record_id(self.id),
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            user_id: anonymiser.pseudonym(&self.user_id),
+            display_name: anonymiser.alias(&self.display_name),
+            location: self.location.anonymise(anonymiser),
+            previous_location: self.previous_location.anonymise(anonymiser),
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for Moderation {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        Moderation {
+            user_id: anonymiser.pseudonym(&self.user_id),
+            updated_at: self.updated_at.map(|at| anonymiser.coarsen(at)),
+            updated_at_raw: String::new(),
+            display_name: anonymiser.alias(&self.display_name),
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for ModerationEvent {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        ModerationEvent {
+            id: anonymiser.record_id(self.id),
+            user_id: anonymiser.pseudonym(&self.user_id),
+            display_name: anonymiser.alias(&self.display_name),
+            at: anonymiser.coarsen(self.at),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for Notification {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        // Notifications from the system have no sender.
+        let pseudonym = |user_id: String| match user_id.is_empty() {
+            true => user_id,
+            false => anonymiser.pseudonym(&user_id),
+        };
+        Notification {
+            created_at: anonymiser.coarsen(self.created_at),
+            created_at_raw: String::new(),
+            sender_user_id: pseudonym(self.sender_user_id),
+            sender_username: anonymiser.alias(&self.sender_username),
+            receiver_user_id: pseudonym(self.receiver_user_id),
+            message: None,
+            location: self.location.anonymise(anonymiser),
+            image_url: None,
+            invite_message: None,
+            request_message: None,
+            response_message: None,
+            sources: self.sources.anonymise(anonymiser),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for Wear {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        Wear {
+            user_id: anonymiser.pseudonym(&self.user_id),
+            display_name: anonymiser.alias(&self.display_name),
+            owner_id: self
+                .owner_id
+                .map(|owner_id| anonymiser.pseudonym(&owner_id)),
+            worn_from: anonymiser.coarsen(self.worn_from),
+            worn_to: self.worn_to.map(|at| anonymiser.coarsen(at)),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for Session {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        Session {
+            display_name: anonymiser.alias(&self.display_name),
+            user_id: self.user_id.map(|user_id| anonymiser.pseudonym(&user_id)),
+            location: self.location.anonymise(anonymiser),
+            joined_at: self.joined_at.map(|at| anonymiser.coarsen(at)),
+            left_at: self.left_at.map(|at| anonymiser.coarsen(at)),
+            ..self
+        }
+    }
+}
+
+/// The anonymised key of a player node: its user id, or its display name when it had none.
+fn player_key(anonymiser: &Anonymiser, key: &str) -> String {
+    if key.starts_with("usr_") {
+        anonymiser.pseudonym(key)
+    } else {
+        anonymiser.alias(key)
+    }
+}
+
+/// The anonymised id of a node, e.g. `player:usr_1234`.
+fn node_id(anonymiser: &Anonymiser, id: String) -> String {
+    match id.split_once(':') {
+        Some((table, key)) if table == NodeKind::Player.table() => {
+            format!("{}:{}", table, player_key(anonymiser, key))
+        }
+        _ => id,
+    }
+}
+
+impl Anonymise for Node {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        match self.kind {
+            NodeKind::Player => Node {
+                key: player_key(anonymiser, &self.key),
+                label: anonymiser.alias(&self.label),
+                ..self
+            },
+            _ => self,
+        }
+    }
+}
+
+impl Anonymise for Edge {
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        Edge {
+            from: node_id(anonymiser, self.from),
+            to: node_id(anonymiser, self.to),
+            first_seen: self.first_seen.map(|at| anonymiser.coarsen(at)),
+            last_seen: self.last_seen.map(|at| anonymiser.coarsen(at)),
+            ..self
+        }
+    }
+}
+
+impl Anonymise for SocialGraph {
+    /// Anonymise every node and edge. Player ids are pseudonymised the same way in both, so
+    /// every edge still connects the same nodes.
+    fn anonymise(self, anonymiser: &Anonymiser) -> Self {
+        SocialGraph {
+            nodes: self
+                .nodes
+                .into_iter()
+                .map(|node| node.anonymise(anonymiser))
+                .collect(),
+            edges: self
+                .edges
+                .into_iter()
+                .map(|edge| edge.anonymise(anonymiser))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::export::anonymise::{Anonymise, Anonymiser, Granularity};
+    use crate::graph::social_graph::{GraphOptions, SocialGraph};
+    use crate::models::moderation::{ModerationAction, ModerationEvent};
+    use crate::models::notification::Notification;
+    use crate::models::session::Session;
+    use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+
+    fn session(user_id: &str) -> Session {
+        let joined_at = Utc.with_ymd_and_hms(2023, 4, 29, 10, 12, 0).unwrap();
+        Session {
+            display_name: format!("name of {}", user_id),
+            user_id: Some(user_id.to_string()),
+            location: "wrld_1234:1234~friends(usr_owner)~nonce(secret)".into(),
+            joined_at: Some(joined_at),
+            left_at: Some(joined_at + Duration::minutes(30)),
+            duration: None,
+        }
+    }
+
+    #[test]
+    fn test_session_is_anonymised() {
+        let anonymiser = Anonymiser::new("key").granularity(Granularity::Day);
+        let session = session("usr_a").anonymise(&anonymiser);
+
+        assert_eq!(session.user_id, Some(anonymiser.pseudonym("usr_a")));
+        assert_eq!(session.display_name, anonymiser.alias("name of usr_a"));
+        assert_eq!(session.location.to_string(), "wrld_1234:1234~friends()");
+        assert_eq!(
+            session.joined_at,
+            Some(Utc.with_ymd_and_hms(2023, 4, 29, 0, 0, 0).unwrap())
+        );
+        assert!(!format!("{:?}", anonymiser).contains("key"));
+    }
+
+    #[test]
+    fn test_graph_structure_is_kept() {
+        let anonymiser = Anonymiser::new("key");
+        let friends = [UsrFriendLogCurrent {
+            user_id: "usr_a".to_string(),
+            display_name: "a".to_string(),
+            ..Default::default()
+        }];
+        let graph = SocialGraph::build(
+            &[session("usr_a"), session("usr_b")],
+            &friends,
+            "usr_me",
+            &GraphOptions::default(),
+        );
+        let anonymised = graph.clone().anonymise(&anonymiser);
+
+        assert_eq!(anonymised.nodes.len(), graph.nodes.len());
+        assert_eq!(anonymised.edges.len(), graph.edges.len());
+        let ids = anonymised
+            .nodes
+            .iter()
+            .map(|node| node.id())
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), graph.nodes.len());
+        for edge in &anonymised.edges {
+            assert!(ids.contains(&edge.from), "{}", edge.from);
+            assert!(ids.contains(&edge.to), "{}", edge.to);
+        }
+        assert!(!ids
+            .iter()
+            .any(|id| id.contains("usr_a") || id.contains("usr_me")));
+    }
+
+    #[test]
+    fn test_notification_text_is_dropped() {
+        let anonymiser = Anonymiser::new("key");
+        let notification = Notification {
+            sender_username: "a".to_string(),
+            sender_user_id: "usr_a".to_string(),
+            message: Some("meet me at home".to_string()),
+            invite_message: Some("come over".to_string()),
+            ..Default::default()
+        }
+        .anonymise(&anonymiser);
+
+        assert_eq!(notification.sender_user_id, anonymiser.pseudonym("usr_a"));
+        assert_eq!(notification.sender_username, anonymiser.alias("a"));
+        assert_eq!(notification.receiver_user_id, "");
+        assert_eq!(notification.message, None);
+        assert_eq!(notification.invite_message, None);
+    }
+
+    #[test]
+    fn test_moderation_events_keep_apart() {
+        let anonymiser = Anonymiser::new("key").granularity(Granularity::Day);
+        let at = Utc.with_ymd_and_hms(2023, 4, 29, 10, 12, 0).unwrap();
+        let block = ModerationEvent::new("usr_a", "a", ModerationAction::Block, at);
+        let again = ModerationEvent::new(
+            "usr_a",
+            "a",
+            ModerationAction::Block,
+            at + Duration::hours(2),
+        );
+        let (block, again) = (
+            block.clone().anonymise(&anonymiser),
+            again.anonymise(&anonymiser),
+        );
+
+        assert_eq!(block.user_id, anonymiser.pseudonym("usr_a"));
+        assert_eq!(
+            block.at,
+            Utc.with_ymd_and_hms(2023, 4, 29, 0, 0, 0).unwrap()
+        );
+        assert_eq!(block.at, again.at);
+        assert_ne!(block.id, again.id);
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::gamelog_location::GamelogLocation;
+use crate::models::session::Session;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::zaphkiel::location::Location;
+use crate::zaphkiel::world_instance::WorldInstance;
+
+/// A model that can be written out by the `Exporter`.
+///
+/// Nested structs, such as `WorldInstance`, are flattened into one column per field, with the
+/// field names joined by `_`, e.g. `world_instance_world_id`.
+pub trait Exportable: serde::Serialize {
+    /// The table name, used for file names and the Parquet schema.
+    const TABLE: &'static str;
+
+    /// The timestamp the date-range filter is applied to, if the model has one.
+    fn timestamp(&self) -> Option<DateTime<Utc>>;
+
+    /// An instance with every nested `Option` struct, timestamp and number set to `Some`, so
+    /// that `columns` sees every field and `column_types` its type.
+    fn template() -> Self;
+
+    /// The flattened column names, in field order.
+    fn columns() -> Vec<String>
+    where
+        Self: Sized,
+    {
+        flatten(&Self::template())
+            .into_iter()
+            .map(|(column, _)| column)
+            .collect()
+    }
+
+    /// The type of every column, in the order of `columns`.
+    fn column_types() -> Vec<ColumnType>
+    where
+        Self: Sized,
+    {
+        flatten(&Self::template())
+            .iter()
+            .map(|(_, value)| ColumnType::of(value))
+            .collect()
+    }
+}
+
+/// The type of an exported column, used for the Parquet schema.
+///
+/// # Available Types
+/// - Text, also used for enums and lists
+/// - Integer
+/// - Float
+/// - Boolean
+/// - Timestamp, a UTC time
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::export::exportable::{ColumnType, Exportable};
+/// use surrealdb_test::models::gamelog_location::GamelogLocation;
+///
+/// let columns = GamelogLocation::columns();
+/// let types = GamelogLocation::column_types();
+/// let type_of = |name: &str| types[columns.iter().position(|column| column == name).unwrap()];
+///
+/// assert_eq!(type_of("id"), ColumnType::Integer);
+/// assert_eq!(type_of("created_at"), ColumnType::Timestamp);
+/// assert_eq!(type_of("world_name"), ColumnType::Text);
+/// assert_eq!(type_of("world_instance_strict"), ColumnType::Boolean);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum ColumnType {
+    Text,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp,
+}
+
+impl ColumnType {
+    /// The type of a flattened template value. Strings that parse as a time are timestamps,
+    /// and `null` is text.
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::Bool(_) => ColumnType::Boolean,
+            Value::Number(number) if number.is_f64() => ColumnType::Float,
+            Value::Number(_) => ColumnType::Integer,
+            Value::String(text) if text.parse::<DateTime<Utc>>().is_ok() => ColumnType::Timestamp,
+            _ => ColumnType::Text,
+        }
+    }
+}
+
+/// Flatten a value into `(column, value)` pairs.
+///
+/// # What it does
+///
+/// * Objects are walked recursively, joining the keys with `_`.
+/// * Strings, numbers, booleans and `null` are kept as they are.
+/// * Arrays are kept as a JSON string.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::export::exportable::flatten;
+/// use surrealdb_test::models::gamelog_location::GamelogLocation;
+///
+/// let location = GamelogLocation::new();
+/// let columns = flatten(&location);
+///
+/// assert_eq!(columns[0], ("id".to_string(), 0.into()));
+/// assert!(columns.iter().any(|(column, _)| column == "world_instance_world_id"));
+/// ```
+pub fn flatten<T: serde::Serialize>(value: &T) -> Vec<(String, Value)> {
+    let mut columns = Vec::new();
+    flatten_into(
+        String::new(),
+        serde_json::to_value(value).unwrap_or(Value::Null),
+        &mut columns,
+    );
+    columns
+}
+
+fn flatten_into(prefix: String, value: Value, columns: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let column = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}_{}", prefix, key)
+                };
+                flatten_into(column, value, columns);
+            }
+        }
+        Value::Array(_) => columns.push((prefix, Value::String(value.to_string()))),
+        value => columns.push((prefix, value)),
+    }
+}
+
+impl Exportable for GamelogLocation {
+    const TABLE: &'static str = "gamelog_locations";
+
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        Some(self.created_at)
+    }
+
+    fn template() -> Self {
+        Self {
+            time: Some(0),
+            ..Self::default()
+        }
+    }
+}
+
+impl Exportable for GamelogJoinLeave {
+    const TABLE: &'static str = "gamelog_join_leave";
+
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        Some(self.created_at)
+    }
+
+    fn template() -> Self {
+        Self {
+            location: Location::Instance(WorldInstance::default()),
+            time: Some(0),
+            ..Self::default()
+        }
+    }
+}
+
+impl Exportable for UsrFriendLogCurrent {
+    const TABLE: &'static str = "friend_log_current";
+
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn template() -> Self {
+        Self::default()
+    }
+}
+
+impl Exportable for Session {
+    const TABLE: &'static str = "sessions";
+
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.started_at()
+    }
+
+    fn template() -> Self {
+        Self {
+            location: Location::Instance(WorldInstance::default()),
+            joined_at: Some(DateTime::default()),
+            left_at: Some(DateTime::default()),
+            duration: Some(0),
+            ..Self::default()
+        }
+    }
+}
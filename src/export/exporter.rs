@@ -0,0 +1,666 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::future::Future;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use surrealdb::{Connection, Surreal};
+
+use crate::export::anonymise::{Anonymise, Anonymiser};
+use crate::export::exportable::{flatten, ColumnType, Exportable};
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::gamelog_location::GamelogLocation;
+use crate::models::session::Session;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::repo::friend::FriendRepo;
+use crate::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+use crate::repo::gamelog_location::GamelogLocationRepo;
+
+/// The file formats the `Exporter` can write.
+///
+/// # Available Formats
+/// - Csv
+/// - NdJson (JSON Lines)
+/// - Parquet, only when built with the `parquet` feature
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::export::exporter::ExportFormat;
+///
+/// let format: ExportFormat = "jsonl".parse().unwrap();
+/// assert_eq!(format, ExportFormat::NdJson);
+/// assert_eq!(format.extension(), "ndjson");
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    NdJson,
+    Parquet,
+}
+
+impl ExportFormat {
+    /// The file extension used for this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::NdJson => "ndjson",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" | "jsonl" | "json-lines" => Ok(ExportFormat::NdJson),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+/// The tables the `Exporter` knows how to export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum ExportTable {
+    GamelogLocation,
+    GamelogJoinLeave,
+    FriendLogCurrent,
+    Sessions,
+}
+
+impl ExportTable {
+    /// Every exportable table.
+    pub const ALL: [ExportTable; 4] = [
+        ExportTable::GamelogLocation,
+        ExportTable::GamelogJoinLeave,
+        ExportTable::FriendLogCurrent,
+        ExportTable::Sessions,
+    ];
+
+    /// The table name, as used by `Exportable::TABLE`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportTable::GamelogLocation => GamelogLocation::TABLE,
+            ExportTable::GamelogJoinLeave => GamelogJoinLeave::TABLE,
+            ExportTable::FriendLogCurrent => UsrFriendLogCurrent::TABLE,
+            ExportTable::Sessions => Session::TABLE,
+        }
+    }
+}
+
+impl FromStr for ExportTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExportTable::ALL
+            .into_iter()
+            .find(|table| table.name() == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown export table: {}", s))
+    }
+}
+
+/// Which rows the `Exporter` writes.
+///
+/// # Values
+///
+/// - `from` - Only rows at or after this time.
+/// - `to` - Only rows before this time.
+/// - `tables` - Only these tables. Empty means every table.
+///
+/// Rows without a timestamp, such as friends, are never filtered out by date.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct ExportFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub tables: Vec<ExportTable>,
+}
+
+impl ExportFilter {
+    /// Check if `table` should be exported.
+    pub fn includes(&self, table: ExportTable) -> bool {
+        self.tables.is_empty() || self.tables.contains(&table)
+    }
+
+    /// Check if `item` falls inside the date range.
+    pub fn matches<T: Exportable>(&self, item: &T) -> bool {
+        let Some(timestamp) = item.timestamp() else {
+            return true;
+        };
+        self.from.is_none_or(|from| timestamp >= from) && self.to.is_none_or(|to| timestamp < to)
+    }
+}
+
+/// Streams models out as CSV, NDJSON or Parquet.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::export::exporter::{ExportFormat, Exporter};
+/// use surrealdb_test::models::usr_friend_log_current::UsrFriendLogCurrent;
+///
+/// let friend = UsrFriendLogCurrent {
+///     user_id: "usr_1234".to_string(),
+///     display_name: "test".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let mut out = Vec::new();
+/// let written = Exporter::new(ExportFormat::Csv).write([friend], &mut out).unwrap();
+///
+/// assert_eq!(written, 1);
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "user_id,display_name,trust_level,sources\nusr_1234,test,Unknown,[]\n"
+/// );
+/// ```
+///
+/// # Values
+///
+/// - `format` - The file format.
+/// - `filter` - Which rows are written.
+/// - `row_group_size` - Rows per Parquet row group.
+/// - `page_size` - Rows read from the database per query by `export_table`.
+/// - `anonymiser` - Anonymises every row before it is written, if set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Exporter {
+    pub format: ExportFormat,
+    pub filter: ExportFilter,
+    pub row_group_size: usize,
+    pub page_size: usize,
+    #[serde(skip)]
+    pub anonymiser: Option<Anonymiser>,
+}
+
+impl Exporter {
+    /// Create a new `Exporter` for `format`, with no filter.
+    pub fn new(format: ExportFormat) -> Self {
+        Exporter {
+            format,
+            filter: ExportFilter::default(),
+            row_group_size: 8192,
+            page_size: 1000,
+            anonymiser: None,
+        }
+    }
+
+    /// Set the filter.
+    pub fn filter(mut self, filter: ExportFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Anonymise every item with `anonymiser` before it is written.
+    ///
+    /// The date-range filter still sees the original timestamps.
+    pub fn anonymise(mut self, anonymiser: Anonymiser) -> Self {
+        self.anonymiser = Some(anonymiser);
+        self
+    }
+
+    /// Write every item that matches the date range to `out`, returning how many were written.
+    pub fn write<T, W>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        out: W,
+    ) -> Result<usize, Box<dyn Error>>
+    where
+        T: Exportable + Anonymise,
+        W: Write + Send,
+    {
+        let mut writer = TableWriter::<T, W>::new(self, out)?;
+        writer.write(items)?;
+        writer.finish()
+    }
+
+    /// Export one table from the database to `out`.
+    ///
+    /// # What it does
+    ///
+    /// * The date range is applied by the query, and the rows are read `page_size` at a time,
+    ///   so only one page is held in memory.
+    /// * Sessions are paired from the joins and leaves in the range, see
+    ///   `GamelogJoinLeaveRepo::sessions`, so they are read in one go.
+    pub async fn export_table<C: Connection, W: Write + Send>(
+        &self,
+        db: &Surreal<C>,
+        table: ExportTable,
+        out: W,
+    ) -> Result<usize, Box<dyn Error>> {
+        let ExportFilter { from, to, .. } = self.filter;
+        let page_size = self.page_size.max(1);
+        match table {
+            ExportTable::GamelogLocation => {
+                let repo = GamelogLocationRepo::new(db.clone());
+                self.write_pages(out, |start| repo.page(from, to, start, page_size))
+                    .await
+            }
+            ExportTable::GamelogJoinLeave => {
+                let repo = GamelogJoinLeaveRepo::new(db.clone());
+                self.write_pages(out, |start| repo.page(from, to, start, page_size))
+                    .await
+            }
+            ExportTable::FriendLogCurrent => {
+                let repo = FriendRepo::new(db.clone());
+                self.write_pages(out, |start| repo.page(start, page_size))
+                    .await
+            }
+            ExportTable::Sessions => {
+                let sessions = GamelogJoinLeaveRepo::new(db.clone())
+                    .sessions(from, to)
+                    .await?;
+                self.write(sessions, out)
+            }
+        }
+    }
+
+    /// Write the pages `page` returns for each offset to `out`, until one comes back short.
+    async fn write_pages<T, W, F, Fut>(&self, out: W, mut page: F) -> Result<usize, Box<dyn Error>>
+    where
+        T: Exportable + Anonymise,
+        W: Write + Send,
+        F: FnMut(usize) -> Fut,
+        Fut: Future<Output = surrealdb::Result<Vec<T>>>,
+    {
+        let page_size = self.page_size.max(1);
+        let mut writer = TableWriter::<T, W>::new(self, out)?;
+        let mut start = 0;
+        loop {
+            let rows = page(start).await?;
+            let done = rows.len() < page_size;
+            start += rows.len();
+            writer.write(rows)?;
+            if done {
+                break;
+            }
+        }
+        writer.finish()
+    }
+
+    /// Export every table allowed by the filter into `dir`, one `<table>.<extension>` file each.
+    pub async fn export_all<C: Connection>(
+        &self,
+        db: &Surreal<C>,
+        dir: &Path,
+    ) -> Result<Vec<(ExportTable, PathBuf, usize)>, Box<dyn Error>> {
+        std::fs::create_dir_all(dir)?;
+
+        let mut written = Vec::new();
+        for table in ExportTable::ALL
+            .into_iter()
+            .filter(|table| self.filter.includes(*table))
+        {
+            let path = dir.join(format!("{}.{}", table.name(), self.format.extension()));
+            let out = BufWriter::new(File::create(&path)?);
+            let count = self.export_table(db, table, out).await?;
+            written.push((table, path, count));
+        }
+
+        Ok(written)
+    }
+}
+
+/// Writes the items of one `Exportable` table, a page at a time.
+struct TableWriter<'a, T, W: Write + Send> {
+    exporter: &'a Exporter,
+    columns: Vec<String>,
+    rows: RowWriter<W>,
+    count: usize,
+    items: std::marker::PhantomData<T>,
+}
+
+impl<'a, T: Exportable + Anonymise, W: Write + Send> TableWriter<'a, T, W> {
+    fn new(exporter: &'a Exporter, out: W) -> Result<Self, Box<dyn Error>> {
+        let columns = T::columns();
+        let rows = match exporter.format {
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(out);
+                writer.write_record(&columns)?;
+                RowWriter::Csv(Box::new(writer))
+            }
+            ExportFormat::NdJson => RowWriter::NdJson(out),
+            ExportFormat::Parquet => RowWriter::Parquet(Box::new(ParquetWriter::new(
+                T::TABLE,
+                &columns,
+                T::column_types(),
+                out,
+                exporter.row_group_size,
+            )?)),
+        };
+        Ok(TableWriter {
+            exporter,
+            columns,
+            rows,
+            count: 0,
+            items: std::marker::PhantomData,
+        })
+    }
+
+    /// Write every item that matches the date range.
+    fn write(&mut self, items: impl IntoIterator<Item = T>) -> Result<(), Box<dyn Error>> {
+        for item in items
+            .into_iter()
+            .filter(|item| self.exporter.filter.matches(item))
+        {
+            let item = match &self.exporter.anonymiser {
+                Some(anonymiser) => item.anonymise(anonymiser),
+                None => item,
+            };
+            self.rows
+                .write(&self.columns, to_row(&self.columns, &item))?;
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    /// Flush what is left, returning how many items were written.
+    fn finish(self) -> Result<usize, Box<dyn Error>> {
+        self.rows.finish()?;
+        Ok(self.count)
+    }
+}
+
+enum RowWriter<W: Write + Send> {
+    Csv(Box<csv::Writer<W>>),
+    NdJson(W),
+    Parquet(Box<ParquetWriter<W>>),
+}
+
+impl<W: Write + Send> RowWriter<W> {
+    fn write(&mut self, columns: &[String], row: Vec<Value>) -> Result<(), Box<dyn Error>> {
+        match self {
+            RowWriter::Csv(writer) => {
+                writer.write_record(row.iter().map(|value| to_text(value).unwrap_or_default()))?
+            }
+            RowWriter::NdJson(out) => {
+                let object = columns
+                    .iter()
+                    .cloned()
+                    .zip(row)
+                    .collect::<serde_json::Map<_, _>>();
+                serde_json::to_writer(&mut *out, &object)?;
+                out.write_all(b"\n")?;
+            }
+            RowWriter::Parquet(writer) => writer.write(row)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            RowWriter::Csv(mut writer) => writer.flush()?,
+            RowWriter::NdJson(mut out) => out.flush()?,
+            RowWriter::Parquet(writer) => writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+fn to_row<T: Exportable>(columns: &[String], item: &T) -> Vec<Value> {
+    let mut values = flatten(item).into_iter().collect::<HashMap<_, _>>();
+    columns
+        .iter()
+        .map(|column| values.remove(column).unwrap_or(Value::Null))
+        .collect()
+}
+
+/// Format a flattened value as text, with `null` as `None`.
+fn to_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(value) => Some(value.clone()),
+        value => Some(value.to_string()),
+    }
+}
+
+/// Writes rows as a Parquet file with one optional column per flattened field, typed by
+/// `ColumnType`. Timestamps are UTC milliseconds. Rows are buffered into row groups of
+/// `row_group_size`.
+#[cfg(feature = "parquet")]
+struct ParquetWriter<W: Write + Send> {
+    writer: parquet::file::writer::SerializedFileWriter<W>,
+    types: Vec<ColumnType>,
+    buffer: Vec<Vec<Value>>,
+    row_group_size: usize,
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write + Send> ParquetWriter<W> {
+    fn new(
+        table: &str,
+        columns: &[String],
+        types: Vec<ColumnType>,
+        out: W,
+        row_group_size: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        use std::sync::Arc;
+
+        use itertools::Itertools;
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+
+        let schema = format!(
+            "message {} {{ {} }}",
+            table,
+            columns
+                .iter()
+                .zip(&types)
+                .map(|(column, kind)| match kind {
+                    ColumnType::Text => format!("OPTIONAL BYTE_ARRAY {} (UTF8);", column),
+                    ColumnType::Integer => format!("OPTIONAL INT64 {};", column),
+                    ColumnType::Float => format!("OPTIONAL DOUBLE {};", column),
+                    ColumnType::Boolean => format!("OPTIONAL BOOLEAN {};", column),
+                    ColumnType::Timestamp => {
+                        format!("OPTIONAL INT64 {} (TIMESTAMP(MILLIS, true));", column)
+                    }
+                })
+                .join(" ")
+        );
+        let schema = Arc::new(parse_message_type(&schema)?);
+        let properties = Arc::new(WriterProperties::builder().build());
+        Ok(ParquetWriter {
+            writer: SerializedFileWriter::new(out, schema, properties)?,
+            types,
+            buffer: Vec::new(),
+            row_group_size: row_group_size.max(1),
+        })
+    }
+
+    fn write(&mut self, row: Vec<Value>) -> Result<(), Box<dyn Error>> {
+        self.buffer.push(row);
+        if self.buffer.len() >= self.row_group_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), Box<dyn Error>> {
+        self.flush()?;
+        self.writer.close()?;
+        Ok(())
+    }
+
+    /// Write the buffered rows as one row group.
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int64Type};
+
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let rows = std::mem::take(&mut self.buffer);
+        let mut row_group = self.writer.next_row_group()?;
+        for (index, kind) in self.types.iter().enumerate() {
+            let mut column = row_group
+                .next_column()?
+                .ok_or("Parquet schema has fewer columns than the export")?;
+            let values = rows.iter().map(|row| &row[index]);
+            match kind {
+                ColumnType::Text => write_column::<ByteArrayType>(&mut column, values, |value| {
+                    to_text(value).map(|text| ByteArray::from(text.into_bytes()))
+                })?,
+                ColumnType::Integer => write_column::<Int64Type>(&mut column, values, |value| {
+                    value
+                        .as_i64()
+                        .or_else(|| value.as_u64().map(|value| value as i64))
+                })?,
+                ColumnType::Float => {
+                    write_column::<DoubleType>(&mut column, values, Value::as_f64)?
+                }
+                ColumnType::Boolean => {
+                    write_column::<BoolType>(&mut column, values, Value::as_bool)?
+                }
+                ColumnType::Timestamp => write_column::<Int64Type>(&mut column, values, |value| {
+                    value
+                        .as_str()
+                        .and_then(|text| text.parse::<DateTime<Utc>>().ok())
+                        .map(|at| at.timestamp_millis())
+                })?,
+            }
+            column.close()?;
+        }
+        row_group.close()?;
+        Ok(())
+    }
+}
+
+/// Write one optional column, with a value that `convert` can't read written as null.
+#[cfg(feature = "parquet")]
+fn write_column<'a, T: parquet::data_type::DataType>(
+    column: &mut parquet::file::writer::SerializedColumnWriter<'_>,
+    values: impl Iterator<Item = &'a Value>,
+    convert: impl Fn(&Value) -> Option<T::T>,
+) -> Result<(), Box<dyn Error>> {
+    let values = values.map(convert).collect::<Vec<_>>();
+    let definition_levels = values
+        .iter()
+        .map(|value| value.is_some() as i16)
+        .collect::<Vec<_>>();
+    let values = values.into_iter().flatten().collect::<Vec<_>>();
+    column
+        .typed::<T>()
+        .write_batch(&values, Some(&definition_levels), None)?;
+    Ok(())
+}
+
+/// Without the `parquet` feature, opening a Parquet export always fails.
+#[cfg(not(feature = "parquet"))]
+struct ParquetWriter<W>(std::marker::PhantomData<W>);
+
+#[cfg(not(feature = "parquet"))]
+impl<W: Write + Send> ParquetWriter<W> {
+    fn new(
+        _table: &str,
+        _columns: &[String],
+        _types: Vec<ColumnType>,
+        _out: W,
+        _row_group_size: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        Err("Parquet export requires the `parquet` feature".into())
+    }
+
+    fn write(&mut self, _row: Vec<Value>) -> Result<(), Box<dyn Error>> {
+        Err("Parquet export requires the `parquet` feature".into())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn Error>> {
+        Err("Parquet export requires the `parquet` feature".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::export::exporter::{ExportFormat, Exporter, TableWriter};
+    use crate::models::gamelog_location::GamelogLocation;
+
+    fn locations() -> Vec<GamelogLocation> {
+        (1..=3)
+            .map(|id| GamelogLocation {
+                id,
+                created_at: Utc.with_ymd_and_hms(2023, 4, 29, 10, id as u32, 0).unwrap(),
+                world_name: format!("world {}", id),
+                time: Some(1000),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pages_write_the_same_file() {
+        let exporter = Exporter::new(ExportFormat::Csv);
+        let mut whole = Vec::new();
+        exporter.write(locations(), &mut whole).unwrap();
+
+        let mut paged = Vec::new();
+        let mut writer = TableWriter::<GamelogLocation, _>::new(&exporter, &mut paged).unwrap();
+        let mut rows = locations();
+        let rest = rows.split_off(2);
+        writer.write(rows).unwrap();
+        writer.write(rest).unwrap();
+        assert_eq!(writer.finish().unwrap(), 3);
+
+        assert_eq!(String::from_utf8(paged), String::from_utf8(whole));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_columns_are_typed() {
+        use parquet::basic::Type;
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::Field;
+
+        let path = std::env::temp_dir().join(format!(
+            "surrealdb-test-export-{}.parquet",
+            std::process::id()
+        ));
+        let exporter = Exporter {
+            row_group_size: 2,
+            ..Exporter::new(ExportFormat::Parquet)
+        };
+        let written = exporter
+            .write(locations(), std::fs::File::create(&path).unwrap())
+            .unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(written, 3);
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let schema = reader.metadata().file_metadata().schema_descr();
+        let type_of = |name: &str| {
+            (0..schema.num_columns())
+                .map(|index| schema.column(index))
+                .find(|column| column.name() == name)
+                .map(|column| column.physical_type())
+        };
+        assert_eq!(type_of("id"), Some(Type::INT64));
+        assert_eq!(type_of("created_at"), Some(Type::INT64));
+        assert_eq!(type_of("time"), Some(Type::INT64));
+        assert_eq!(type_of("world_instance_strict"), Some(Type::BOOLEAN));
+        assert_eq!(type_of("world_name"), Some(Type::BYTE_ARRAY));
+
+        let row = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        let created_at = row
+            .get_column_iter()
+            .find(|(name, _)| name.as_str() == "created_at")
+            .map(|(_, field)| field.clone());
+        assert_eq!(
+            created_at,
+            Some(Field::TimestampMillis(
+                Utc.with_ymd_and_hms(2023, 4, 29, 10, 1, 0)
+                    .unwrap()
+                    .timestamp_millis()
+            ))
+        );
+    }
+}
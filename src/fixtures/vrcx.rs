@@ -0,0 +1,1083 @@
+use std::path::Path;
+
+use chrono::{DateTime, Duration, SecondsFormat, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rusqlite::{params, Connection};
+
+use crate::models::tenant::account_prefix;
+use crate::rows::avatar_history::AvatarHistoryRow;
+use crate::rows::cache_avatar::CacheAvatarRow;
+use crate::rows::cache_world::CacheWorldRow;
+use crate::rows::feed_avatar::FeedAvatarRow;
+use crate::rows::feed_bio::FeedBioRow;
+use crate::rows::feed_gps::FeedGpsRow;
+use crate::rows::feed_online_offline::FeedOnlineOfflineRow;
+use crate::rows::feed_status::FeedStatusRow;
+use crate::rows::gamelog_join_leave::GamelogJoinLeaveRow;
+use crate::rows::gamelog_location::GamelogLocationRow;
+use crate::rows::moderation::ModerationRow;
+use crate::rows::notification::NotificationRow;
+use crate::rows::usr_friend_log_current::UsrFriendLogCurrentRow;
+
+/// The shared tables of `vrcx.sqlite`, from `sql_schema/`.
+const SHARED_SCHEMA: [&str; 10] = [
+    include_str!("../../sql_schema/20230429103651_gamelog_location.sql"),
+    include_str!("../../sql_schema/20230429105833_cache_avatar.sql"),
+    include_str!("../../sql_schema/20230429105840_cache_world.sql"),
+    include_str!("../../sql_schema/20230429105852_configs.sql"),
+    include_str!("../../sql_schema/20230429105916_favorite_world.sql"),
+    include_str!("../../sql_schema/20230429105924_gamelog_event.sql"),
+    include_str!("../../sql_schema/20230429105929_gamelog_join_leave.sql"),
+    include_str!("../../sql_schema/20230429105935_gamelog_portal_spawn.sql"),
+    include_str!("../../sql_schema/20230429105944_gamelog_video_play.sql"),
+    include_str!("../../sql_schema/20230429105949_memos.sql"),
+];
+
+/// The per-account tables of `vrcx.sqlite`, from `sql_schema/user_specific/`. Their names start
+/// with `_` and get the account prefix in front.
+const ACCOUNT_SCHEMA: [&str; 10] = [
+    include_str!("../../sql_schema/user_specific/20230429110010_avatar_history.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110018_feed_avatar.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110021_feed_bio.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110024_feed_gps.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110038_feed_online_offline.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110049_feed_status.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110057_friend_log_current.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110101_friend_log_history.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110107_moderation.sql"),
+    include_str!("../../sql_schema/user_specific/20230429110111_notifications.sql"),
+];
+
+const STATUSES: [&str; 4] = ["join me", "active", "ask me", "busy"];
+
+const TRUST_LEVELS: [&str; 5] = ["Visitor", "New User", "User", "Known User", "Trusted User"];
+
+const NAME_PARTS: [&str; 12] = [
+    "Neko", "Pixel", "Shadow", "Mochi", "Nova", "Kitsune", "Echo", "Sakura", "Glitch", "Luna",
+    "Byte", "Ember",
+];
+
+const WORLD_PARTS: [&str; 10] = [
+    "Cozy", "Midnight", "Rooftop", "Forest", "Pool", "Cafe", "Arcade", "Cabin", "Beach", "Club",
+];
+
+/// The instance access types, as they appear in a location string.
+///
+/// # Available Variants
+/// - Public: `wrld_..:1234`
+/// - FriendsPlus: `~hidden(usr_..)`
+/// - Friends: `~friends(usr_..)`
+/// - InvitePlus: `~private(usr_..)~canRequestInvite`
+/// - Invite: `~private(usr_..)`
+/// - Group: `~group(grp_..)`
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum AccessType {
+    #[default]
+    Public,
+    FriendsPlus,
+    Friends,
+    InvitePlus,
+    Invite,
+    Group,
+}
+
+impl AccessType {
+    pub const ALL: [AccessType; 6] = [
+        AccessType::Public,
+        AccessType::FriendsPlus,
+        AccessType::Friends,
+        AccessType::InvitePlus,
+        AccessType::Invite,
+        AccessType::Group,
+    ];
+}
+
+/// What `Fixture::generate` makes.
+///
+/// # Values
+///
+/// - `seed` - The same seed and options always give the same fixture.
+/// - `players` - Other players that can show up in instances.
+/// - `friends` - How many of the players are friends of the account.
+/// - `worlds` - Distinct worlds, at least 1.
+/// - `visits` - `gamelog_location` rows, one per instance visited.
+/// - `max_company` - At most this many players join each instance.
+/// - `renames` - Display name changes, spread over the players.
+/// - `start` - When the first visit starts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct FixtureOptions {
+    pub seed: u64,
+    pub players: usize,
+    pub friends: usize,
+    pub worlds: usize,
+    pub visits: usize,
+    pub max_company: usize,
+    pub renames: usize,
+    pub start: DateTime<Utc>,
+}
+
+impl Default for FixtureOptions {
+    fn default() -> Self {
+        FixtureOptions {
+            seed: 0x5eed,
+            players: 50,
+            friends: 15,
+            worlds: 10,
+            visits: 40,
+            max_company: 8,
+            renames: 10,
+            start: Utc.with_ymd_and_hms(2023, 4, 1, 18, 0, 0).unwrap(),
+        }
+    }
+}
+
+/// A generated player, with every display name it has had.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct FixturePlayer {
+    pub user_id: String,
+    /// `(since, display_name)`, oldest first. The first name is held from the start.
+    pub names: Vec<(DateTime<Utc>, String)>,
+    pub trust_level: String,
+}
+
+impl FixturePlayer {
+    /// The display name the player had at `at`.
+    pub fn name_at(&self, at: DateTime<Utc>) -> &str {
+        self.names
+            .iter()
+            .rev()
+            .find(|(since, _)| *since <= at)
+            .unwrap_or(&self.names[0])
+            .1
+            .as_str()
+    }
+
+    /// The current display name.
+    pub fn name(&self) -> &str {
+        &self.names.last().unwrap().1
+    }
+}
+
+/// One visited instance and who was there.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct FixtureVisit {
+    pub location: String,
+    pub access: AccessType,
+    pub joined_at: DateTime<Utc>,
+    pub left_at: DateTime<Utc>,
+    /// Indexes into `Fixture::players`.
+    pub company: Vec<usize>,
+}
+
+/// A synthetic VRCX database.
+///
+/// Visits follow each other without overlapping. On every visit the account joins first and
+/// leaves last, and everyone else joins and leaves in between, with `time` on leave rows set to
+/// how long they stayed. Join/leave rows use the display name the player had when they joined.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::fixtures::vrcx::{Fixture, FixtureOptions};
+///
+/// let fixture = Fixture::generate(&FixtureOptions::default());
+/// assert_eq!(fixture.locations.len(), 40);
+/// assert_eq!(fixture, Fixture::generate(&FixtureOptions::default()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Fixture {
+    pub account: FixturePlayer,
+    pub players: Vec<FixturePlayer>,
+    /// `(world_id, world_name)`.
+    pub worlds: Vec<(String, String)>,
+    pub visits: Vec<FixtureVisit>,
+    pub locations: Vec<GamelogLocationRow>,
+    pub join_leave: Vec<GamelogJoinLeaveRow>,
+    pub friends: Vec<UsrFriendLogCurrentRow>,
+    /// Every world but the last, so one visited world is always missing from the cache.
+    pub cache_worlds: Vec<CacheWorldRow>,
+    /// One avatar per friend, uploaded by them.
+    pub cache_avatars: Vec<CacheAvatarRow>,
+    /// Every friend switches between cached avatars a few times.
+    pub feed_avatar: Vec<FeedAvatarRow>,
+    /// A few cached avatars the account wore.
+    pub avatar_history: Vec<AvatarHistoryRow>,
+    /// Every friend changes their status twice.
+    pub feed_status: Vec<FeedStatusRow>,
+    /// Every friend edits their bio once.
+    pub feed_bio: Vec<FeedBioRow>,
+    /// Every friend comes online and goes offline twice, without the periods overlapping.
+    /// `Offline` rows carry the `time` they were online for.
+    pub feed_online_offline: Vec<FeedOnlineOfflineRow>,
+    /// Every friend moves into each visit they were part of, then to `private` after the last.
+    pub feed_gps: Vec<FeedGpsRow>,
+    /// The first two players who aren't friends are blocked halfway through, the next one is
+    /// muted.
+    pub moderation: Vec<ModerationRow>,
+    /// Every friend invites the account to each visit they were part of, five minutes before
+    /// it. The first player who isn't a friend sends a friend request halfway through.
+    pub notifications: Vec<NotificationRow>,
+}
+
+impl Fixture {
+    /// Generate a fixture from `options`.
+    pub fn generate(options: &FixtureOptions) -> Fixture {
+        let mut rng = StdRng::seed_from_u64(options.seed);
+
+        let account = FixturePlayer {
+            user_id: user_id(&mut rng),
+            names: vec![(options.start, "Fixture Account".to_string())],
+            trust_level: "Trusted User".to_string(),
+        };
+        let mut players = (0..options.players)
+            .map(|index| FixturePlayer {
+                user_id: user_id(&mut rng),
+                names: vec![(options.start, display_name(&mut rng, index, 0))],
+                trust_level: TRUST_LEVELS.choose(&mut rng).unwrap().to_string(),
+            })
+            .collect::<Vec<_>>();
+        let worlds = (0..options.worlds.max(1))
+            .map(|index| {
+                let name = format!(
+                    "{} {} {}",
+                    WORLD_PARTS.choose(&mut rng).unwrap(),
+                    WORLD_PARTS.choose(&mut rng).unwrap(),
+                    index
+                );
+                (format!("wrld_{}", uuid(&mut rng)), name)
+            })
+            .collect::<Vec<_>>();
+
+        let mut visits = Vec::new();
+        let mut at = options.start;
+        for index in 0..options.visits {
+            let access = match AccessType::ALL.get(index) {
+                Some(access) => *access,
+                None => *AccessType::ALL.choose(&mut rng).unwrap(),
+            };
+            let (world_id, _) = &worlds[rng.gen_range(0..worlds.len())];
+            let owner = match access {
+                AccessType::Public => account.user_id.clone(),
+                _ => players
+                    .choose(&mut rng)
+                    .map_or(account.user_id.clone(), |player| player.user_id.clone()),
+            };
+            let stay = Duration::seconds(rng.gen_range(5 * 60..3 * 60 * 60));
+            let company_size = rng.gen_range(0..=options.max_company.min(players.len()));
+            let company =
+                rand::seq::index::sample(&mut rng, players.len(), company_size).into_vec();
+
+            visits.push(FixtureVisit {
+                location: location(&mut rng, world_id, access, &owner),
+                access,
+                joined_at: at,
+                left_at: at + stay,
+                company,
+            });
+            at = at + stay + Duration::seconds(rng.gen_range(10..2 * 60 * 60));
+        }
+        let end = at;
+
+        for rename in 0..options.renames.min(players.len() * 4) {
+            let player = rng.gen_range(0..players.len());
+            let since = options.start
+                + Duration::seconds(rng.gen_range(1..(end - options.start).num_seconds().max(2)));
+            let name = display_name(&mut rng, player, rename + 1);
+            let names = &mut players[player].names;
+            names.push((since, name));
+            names.sort();
+        }
+
+        let locations = visits
+            .iter()
+            .enumerate()
+            .map(|(index, visit)| {
+                let (world_id, world_name) = worlds
+                    .iter()
+                    .find(|(world_id, _)| visit.location.starts_with(world_id.as_str()))
+                    .unwrap();
+                GamelogLocationRow {
+                    id: index as i64 + 1,
+                    created_at: timestamp(visit.joined_at),
+                    location: visit.location.clone(),
+                    world_id: world_id.clone(),
+                    world_name: world_name.clone(),
+                    time: (visit.left_at - visit.joined_at).num_milliseconds(),
+                    group_name: match visit.access {
+                        AccessType::Group => format!("{} Group", world_name),
+                        _ => String::new(),
+                    },
+                }
+            })
+            .collect();
+
+        let mut join_leave = Vec::new();
+        for visit in &visits {
+            let stay = (visit.left_at - visit.joined_at).num_seconds();
+            join_leave.push(event(&account, visit, visit.joined_at, None));
+            for player in &visit.company {
+                let player = &players[*player];
+                let joined_at = visit.joined_at + Duration::seconds(rng.gen_range(1..stay / 2));
+                let left_at = joined_at
+                    + Duration::seconds(
+                        rng.gen_range(1..(visit.left_at - joined_at).num_seconds()),
+                    );
+                join_leave.push(event(player, visit, joined_at, None));
+                join_leave.push(event(player, visit, left_at, Some(joined_at)));
+            }
+            join_leave.push(event(&account, visit, visit.left_at, Some(visit.joined_at)));
+        }
+        join_leave.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        for (index, row) in join_leave.iter_mut().enumerate() {
+            row.id = index as i64 + 1;
+        }
+
+        let friends = players
+            .iter()
+            .take(options.friends)
+            .map(|player| UsrFriendLogCurrentRow {
+                user_id: player.user_id.clone(),
+                display_name: player.name().to_string(),
+                trust_level: player.trust_level.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let cache_worlds = worlds
+            .iter()
+            .take(worlds.len() - 1)
+            .map(|(world_id, world_name)| {
+                let author = players.choose(&mut rng).unwrap_or(&account);
+                let version = rng.gen_range(1..20);
+                let created_at = options.start - Duration::days(rng.gen_range(30..1000));
+                CacheWorldRow {
+                    id: world_id.clone(),
+                    added_at: timestamp(options.start),
+                    author_id: author.user_id.clone(),
+                    author_name: author.name_at(created_at).to_string(),
+                    created_at: timestamp(created_at),
+                    description: format!("Welcome to {}", world_name),
+                    image_url: format!("https://example.com/{}/image", world_id),
+                    name: world_name.clone(),
+                    release_status: "public".to_string(),
+                    thumbnail_image_url: format!("https://example.com/{}/thumbnail", world_id),
+                    updated_at: timestamp(created_at + Duration::days(version)),
+                    version,
+                }
+            })
+            .collect();
+        let cache_avatars: Vec<CacheAvatarRow> = friends
+            .iter()
+            .map(|friend| {
+                let avatar_id = format!("avtr_{}", uuid(&mut rng));
+                let version = rng.gen_range(1..20);
+                let created_at = options.start - Duration::days(rng.gen_range(30..1000));
+                CacheAvatarRow {
+                    id: avatar_id.clone(),
+                    added_at: timestamp(options.start),
+                    author_id: friend.user_id.clone(),
+                    author_name: friend.display_name.clone(),
+                    created_at: timestamp(created_at),
+                    description: String::new(),
+                    image_url: format!("https://example.com/{}/image", avatar_id),
+                    name: format!("{} Avatar", friend.display_name),
+                    release_status: ["public", "private"].choose(&mut rng).unwrap().to_string(),
+                    thumbnail_image_url: format!("https://example.com/{}/thumbnail", avatar_id),
+                    updated_at: timestamp(created_at + Duration::days(version)),
+                    version,
+                }
+            })
+            .collect();
+
+        let mut feed_avatar = Vec::new();
+        if !cache_avatars.is_empty() {
+            for friend in &friends {
+                let mut previous: Option<&CacheAvatarRow> = None;
+                let mut switches = (0..3)
+                    .map(|_| {
+                        options.start
+                            + Duration::seconds(
+                                rng.gen_range(0..(end - options.start).num_seconds().max(1)),
+                            )
+                    })
+                    .collect::<Vec<_>>();
+                switches.sort();
+                for at in switches {
+                    let avatar = cache_avatars.choose(&mut rng).unwrap();
+                    feed_avatar.push(FeedAvatarRow {
+                        id: 0,
+                        created_at: timestamp(at),
+                        user_id: friend.user_id.clone(),
+                        display_name: friend.display_name.clone(),
+                        owner_id: avatar.author_id.clone(),
+                        avatar_name: avatar.name.clone(),
+                        current_avatar_image_url: avatar.image_url.clone(),
+                        current_avatar_thumbnail_image_url: avatar.thumbnail_image_url.clone(),
+                        previous_current_avatar_image_url: previous
+                            .map_or(String::new(), |previous| previous.image_url.clone()),
+                        previous_current_avatar_thumbnail_image_url: previous
+                            .map_or(String::new(), |previous| {
+                                previous.thumbnail_image_url.clone()
+                            }),
+                    });
+                    previous = Some(avatar);
+                }
+            }
+        }
+        feed_avatar.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        for (index, row) in feed_avatar.iter_mut().enumerate() {
+            row.id = index as i64 + 1;
+        }
+        let avatar_history = cache_avatars
+            .choose_multiple(&mut rng, 3)
+            .map(|avatar| AvatarHistoryRow {
+                avatar_id: avatar.id.clone(),
+                created_at: timestamp(
+                    options.start
+                        + Duration::seconds(
+                            rng.gen_range(0..(end - options.start).num_seconds().max(1)),
+                        ),
+                ),
+            })
+            .collect();
+
+        let random_at = |rng: &mut StdRng| {
+            options.start
+                + Duration::seconds(rng.gen_range(0..(end - options.start).num_seconds().max(1)))
+        };
+        let mut feed_status = Vec::new();
+        let mut feed_bio = Vec::new();
+        let mut feed_online_offline = Vec::new();
+        for friend in &friends {
+            let mut changes = (0..2).map(|_| random_at(&mut rng)).collect::<Vec<_>>();
+            changes.sort();
+            let mut previous = ("active".to_string(), String::new());
+            for at in changes {
+                let status = STATUSES.choose(&mut rng).unwrap().to_string();
+                let description = format!("{} for now", status);
+                feed_status.push(FeedStatusRow {
+                    id: 0,
+                    created_at: timestamp(at),
+                    user_id: friend.user_id.clone(),
+                    display_name: friend.display_name.clone(),
+                    status: status.clone(),
+                    status_description: description.clone(),
+                    previous_status: previous.0,
+                    previous_status_description: previous.1,
+                });
+                previous = (status, description);
+            }
+
+            feed_bio.push(FeedBioRow {
+                id: 0,
+                created_at: timestamp(random_at(&mut rng)),
+                user_id: friend.user_id.clone(),
+                display_name: friend.display_name.clone(),
+                bio: format!("Hi, I'm {}", friend.display_name),
+                previous_bio: String::new(),
+            });
+
+            let mut at = random_at(&mut rng);
+            for _ in 0..2 {
+                let minutes = rng.gen_range(10..180);
+                let event = |event: &str, at, location: &str, time| FeedOnlineOfflineRow {
+                    id: 0,
+                    created_at: timestamp(at),
+                    event: event.to_string(),
+                    user_id: friend.user_id.clone(),
+                    display_name: friend.display_name.clone(),
+                    location: location.to_string(),
+                    world_name: String::new(),
+                    time,
+                    group_name: String::new(),
+                };
+                feed_online_offline.push(event("Online", at, "private", 0));
+                feed_online_offline.push(event(
+                    "Offline",
+                    at + Duration::minutes(minutes),
+                    "offline",
+                    minutes * 60 * 1000,
+                ));
+                at += Duration::minutes(minutes + rng.gen_range(30..600));
+            }
+        }
+        feed_status.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        for (index, row) in feed_status.iter_mut().enumerate() {
+            row.id = index as i64 + 1;
+        }
+        feed_bio.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        for (index, row) in feed_bio.iter_mut().enumerate() {
+            row.id = index as i64 + 1;
+        }
+        feed_online_offline.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        for (index, row) in feed_online_offline.iter_mut().enumerate() {
+            row.id = index as i64 + 1;
+        }
+
+        let mut feed_gps = Vec::new();
+        for friend in &friends {
+            let index = players
+                .iter()
+                .position(|player| player.user_id == friend.user_id)
+                .unwrap();
+            let mut previous: Option<(&str, DateTime<Utc>)> = None;
+            let hop =
+                |at: DateTime<Utc>, location: &str, previous: Option<(&str, DateTime<Utc>)>| {
+                    FeedGpsRow {
+                        id: 0,
+                        created_at: timestamp(at),
+                        user_id: friend.user_id.clone(),
+                        display_name: friend.display_name.clone(),
+                        location: location.to_string(),
+                        world_name: worlds
+                            .iter()
+                            .find(|(world_id, _)| location.starts_with(world_id.as_str()))
+                            .map_or(String::new(), |(_, name)| name.clone()),
+                        previous_location: previous
+                            .map_or(String::new(), |(location, _)| location.to_string()),
+                        time: previous.map_or(0, |(_, since)| (at - since).num_milliseconds()),
+                        group_name: String::new(),
+                    }
+                };
+            for visit in visits.iter().filter(|visit| visit.company.contains(&index)) {
+                feed_gps.push(hop(visit.joined_at, &visit.location, previous));
+                previous = Some((&visit.location, visit.joined_at));
+            }
+            if let Some((_, since)) = previous {
+                feed_gps.push(hop(since + Duration::hours(1), "private", previous));
+            }
+        }
+        feed_gps.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        for (index, row) in feed_gps.iter_mut().enumerate() {
+            row.id = index as i64 + 1;
+        }
+
+        let halfway = options.start + (end - options.start) / 2;
+        let mut notifications = Vec::new();
+        for friend in &friends {
+            let index = players
+                .iter()
+                .position(|player| player.user_id == friend.user_id)
+                .unwrap();
+            for visit in visits.iter().filter(|visit| visit.company.contains(&index)) {
+                notifications.push(NotificationRow {
+                    created_at: timestamp(visit.joined_at - Duration::minutes(5)),
+                    kind: "invite".to_string(),
+                    sender_user_id: friend.user_id.clone(),
+                    sender_username: friend.display_name.clone(),
+                    receiver_user_id: account.user_id.clone(),
+                    world_id: visit.location.clone(),
+                    world_name: worlds
+                        .iter()
+                        .find(|(world_id, _)| visit.location.starts_with(world_id.as_str()))
+                        .map_or(String::new(), |(_, name)| name.clone()),
+                    ..Default::default()
+                });
+            }
+        }
+        if let Some(stranger) = players.iter().find(|player| {
+            !friends
+                .iter()
+                .any(|friend| friend.user_id == player.user_id)
+        }) {
+            notifications.push(NotificationRow {
+                created_at: timestamp(halfway),
+                kind: "friendRequest".to_string(),
+                sender_user_id: stranger.user_id.clone(),
+                sender_username: stranger.names.last().unwrap().1.clone(),
+                receiver_user_id: account.user_id.clone(),
+                ..Default::default()
+            });
+        }
+        notifications.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        for (index, row) in notifications.iter_mut().enumerate() {
+            row.id = format!("not_{:08}", index + 1);
+        }
+
+        let moderation = players
+            .iter()
+            .filter(|player| {
+                !friends
+                    .iter()
+                    .any(|friend| friend.user_id == player.user_id)
+            })
+            .take(3)
+            .enumerate()
+            .map(|(index, player)| ModerationRow {
+                user_id: player.user_id.clone(),
+                updated_at: timestamp(halfway),
+                display_name: player.names.last().unwrap().1.clone(),
+                block: (index < 2) as i64,
+                mute: (index == 2) as i64,
+            })
+            .collect();
+
+        Fixture {
+            account,
+            players,
+            worlds,
+            visits,
+            locations,
+            join_leave,
+            friends,
+            cache_worlds,
+            cache_avatars,
+            feed_avatar,
+            avatar_history,
+            feed_status,
+            feed_bio,
+            feed_online_offline,
+            feed_gps,
+            moderation,
+            notifications,
+        }
+    }
+
+    /// The prefix VRCX puts on the account's tables: its user id without `_` or `-`.
+    pub fn account_prefix(&self) -> String {
+        account_prefix(&self.account.user_id)
+    }
+
+    /// Write the fixture to a new `vrcx.sqlite` at `path`, creating every table in `sql_schema/`.
+    ///
+    /// Only `gamelog_location`, `gamelog_join_leave`, `cache_world`, `cache_avatar` and the
+    /// account's `_friend_log_current`, `_friend_log_history`, `_feed_avatar`, `_avatar_history`,
+    /// `_feed_status`, `_feed_bio`, `_feed_online_offline`, `_feed_gps`, `_moderation` and
+    /// `_notifications` get rows.
+    pub fn write_sqlite(&self, path: impl AsRef<Path>) -> rusqlite::Result<()> {
+        let mut conn = Connection::open(path)?;
+        let prefix = self.account_prefix();
+
+        for ddl in SHARED_SCHEMA {
+            conn.execute_batch(ddl)?;
+        }
+        for ddl in ACCOUNT_SCHEMA {
+            conn.execute_batch(
+                &ddl.replace("if not exists _", &format!("if not exists {}_", prefix)),
+            )?;
+        }
+
+        let tx = conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO gamelog_location \
+                (id, created_at, location, world_id, world_name, time, group_name) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for row in &self.locations {
+                insert.execute(params![
+                    row.id,
+                    row.created_at,
+                    row.location,
+                    row.world_id,
+                    row.world_name,
+                    row.time,
+                    row.group_name,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(
+                "INSERT INTO gamelog_join_leave \
+                (id, created_at, type, display_name, location, user_id, time) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for row in &self.join_leave {
+                insert.execute(params![
+                    row.id,
+                    row.created_at,
+                    row.event,
+                    row.display_name,
+                    row.location,
+                    row.user_id,
+                    row.time,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(
+                "INSERT INTO cache_world \
+                (id, added_at, author_id, author_name, created_at, description, image_url, name, \
+                release_status, thumbnail_image_url, updated_at, version) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+            for row in &self.cache_worlds {
+                insert.execute(params![
+                    row.id,
+                    row.added_at,
+                    row.author_id,
+                    row.author_name,
+                    row.created_at,
+                    row.description,
+                    row.image_url,
+                    row.name,
+                    row.release_status,
+                    row.thumbnail_image_url,
+                    row.updated_at,
+                    row.version,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(
+                "INSERT INTO cache_avatar \
+                (id, added_at, author_id, author_name, created_at, description, image_url, name, \
+                release_status, thumbnail_image_url, updated_at, version) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+            for row in &self.cache_avatars {
+                insert.execute(params![
+                    row.id,
+                    row.added_at,
+                    row.author_id,
+                    row.author_name,
+                    row.created_at,
+                    row.description,
+                    row.image_url,
+                    row.name,
+                    row.release_status,
+                    row.thumbnail_image_url,
+                    row.updated_at,
+                    row.version,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_friend_log_current (user_id, display_name, trust_level) \
+                VALUES (?1, ?2, ?3)",
+                prefix
+            ))?;
+            for friend in &self.friends {
+                insert.execute(params![
+                    friend.user_id,
+                    friend.display_name,
+                    friend.trust_level
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_feed_avatar \
+                (id, created_at, user_id, display_name, owner_id, avatar_name, \
+                current_avatar_image_url, current_avatar_thumbnail_image_url, \
+                previous_current_avatar_image_url, previous_current_avatar_thumbnail_image_url) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                prefix
+            ))?;
+            for row in &self.feed_avatar {
+                insert.execute(params![
+                    row.id,
+                    row.created_at,
+                    row.user_id,
+                    row.display_name,
+                    row.owner_id,
+                    row.avatar_name,
+                    row.current_avatar_image_url,
+                    row.current_avatar_thumbnail_image_url,
+                    row.previous_current_avatar_image_url,
+                    row.previous_current_avatar_thumbnail_image_url,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_avatar_history (avatar_id, created_at) VALUES (?1, ?2)",
+                prefix
+            ))?;
+            for row in &self.avatar_history {
+                insert.execute(params![row.avatar_id, row.created_at])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_feed_status \
+                (id, created_at, user_id, display_name, status, status_description, \
+                previous_status, previous_status_description) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                prefix
+            ))?;
+            for row in &self.feed_status {
+                insert.execute(params![
+                    row.id,
+                    row.created_at,
+                    row.user_id,
+                    row.display_name,
+                    row.status,
+                    row.status_description,
+                    row.previous_status,
+                    row.previous_status_description,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_feed_bio \
+                (id, created_at, user_id, display_name, bio, previous_bio) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                prefix
+            ))?;
+            for row in &self.feed_bio {
+                insert.execute(params![
+                    row.id,
+                    row.created_at,
+                    row.user_id,
+                    row.display_name,
+                    row.bio,
+                    row.previous_bio,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_feed_online_offline \
+                (id, created_at, user_id, display_name, type, location, world_name, time, \
+                group_name) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                prefix
+            ))?;
+            for row in &self.feed_online_offline {
+                insert.execute(params![
+                    row.id,
+                    row.created_at,
+                    row.user_id,
+                    row.display_name,
+                    row.event,
+                    row.location,
+                    row.world_name,
+                    row.time,
+                    row.group_name,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_feed_gps \
+                (id, created_at, user_id, display_name, location, world_name, previous_location, \
+                time, group_name) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                prefix
+            ))?;
+            for row in &self.feed_gps {
+                insert.execute(params![
+                    row.id,
+                    row.created_at,
+                    row.user_id,
+                    row.display_name,
+                    row.location,
+                    row.world_name,
+                    row.previous_location,
+                    row.time,
+                    row.group_name,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_moderation (user_id, updated_at, display_name, block, mute) \
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                prefix
+            ))?;
+            for row in &self.moderation {
+                insert.execute(params![
+                    row.user_id,
+                    row.updated_at,
+                    row.display_name,
+                    row.block,
+                    row.mute,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_notifications \
+                (id, created_at, type, sender_user_id, sender_username, receiver_user_id, \
+                message, world_id, world_name, image_url, invite_message, request_message, \
+                response_message, expired) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                prefix
+            ))?;
+            for row in &self.notifications {
+                insert.execute(params![
+                    row.id,
+                    row.created_at,
+                    row.kind,
+                    row.sender_user_id,
+                    row.sender_username,
+                    row.receiver_user_id,
+                    row.message,
+                    row.world_id,
+                    row.world_name,
+                    row.image_url,
+                    row.invite_message,
+                    row.request_message,
+                    row.response_message,
+                    row.expired,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {}_friend_log_history \
+                (created_at, type, user_id, display_name, previous_display_name, trust_level, \
+                previous_trust_level) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                prefix
+            ))?;
+            for friend in &self.friends {
+                let player = self
+                    .players
+                    .iter()
+                    .find(|player| player.user_id == friend.user_id)
+                    .unwrap();
+                let (since, first) = &player.names[0];
+                insert.execute(params![
+                    timestamp(*since),
+                    "Friend",
+                    player.user_id,
+                    first,
+                    "",
+                    player.trust_level,
+                    "",
+                ])?;
+                for pair in player.names.windows(2) {
+                    let ((_, previous), (since, name)) = (&pair[0], &pair[1]);
+                    insert.execute(params![
+                        timestamp(*since),
+                        "DisplayName",
+                        player.user_id,
+                        name,
+                        previous,
+                        player.trust_level,
+                        player.trust_level,
+                    ])?;
+                }
+            }
+        }
+        tx.commit()
+    }
+}
+
+/// A join row, or a leave row if `joined_at` is set. Leave rows keep the name the player joined
+/// with, like VRCX does within one instance.
+fn event(
+    player: &FixturePlayer,
+    visit: &FixtureVisit,
+    at: DateTime<Utc>,
+    joined_at: Option<DateTime<Utc>>,
+) -> GamelogJoinLeaveRow {
+    GamelogJoinLeaveRow {
+        id: 0,
+        created_at: timestamp(at),
+        event: match joined_at {
+            Some(_) => "OnPlayerLeft",
+            None => "OnPlayerJoined",
+        }
+        .to_string(),
+        display_name: player.name_at(joined_at.unwrap_or(at)).to_string(),
+        location: visit.location.clone(),
+        user_id: player.user_id.clone(),
+        time: joined_at.map_or(0, |joined_at| (at - joined_at).num_milliseconds()),
+    }
+}
+
+fn location(rng: &mut StdRng, world_id: &str, access: AccessType, owner: &str) -> String {
+    let instance = rng.gen_range(10_000..100_000);
+    let region = ["us", "use", "eu", "jp"].choose(rng).unwrap();
+    let nonce = uuid(rng);
+    match access {
+        AccessType::Public => format!("{}:{}~region({})", world_id, instance, region),
+        AccessType::FriendsPlus => format!(
+            "{}:{}~hidden({})~region({})~nonce({})",
+            world_id, instance, owner, region, nonce
+        ),
+        AccessType::Friends => format!(
+            "{}:{}~friends({})~region({})~nonce({})",
+            world_id, instance, owner, region, nonce
+        ),
+        AccessType::InvitePlus => format!(
+            "{}:{}~private({})~canRequestInvite~region({})~nonce({})",
+            world_id, instance, owner, region, nonce
+        ),
+        AccessType::Invite => format!(
+            "{}:{}~private({})~region({})~nonce({})",
+            world_id, instance, owner, region, nonce
+        ),
+        AccessType::Group => format!(
+            "{}:{}~group(grp_{})~region({})",
+            world_id,
+            instance,
+            uuid(rng),
+            region
+        ),
+    }
+}
+
+/// A display name that no other player has had: `index` and `generation` are unique.
+fn display_name(rng: &mut StdRng, index: usize, generation: usize) -> String {
+    let name = format!(
+        "{}{}{}",
+        NAME_PARTS.choose(rng).unwrap(),
+        NAME_PARTS.choose(rng).unwrap(),
+        index
+    );
+    match generation {
+        0 => name,
+        _ => format!("{} v{}", name, generation),
+    }
+}
+
+fn user_id(rng: &mut StdRng) -> String {
+    format!("usr_{}", uuid(rng))
+}
+
+fn uuid(rng: &mut StdRng) -> String {
+    let bytes: [u8; 16] = rng.gen();
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::fixtures::vrcx::{AccessType, Fixture, FixtureOptions};
+    use crate::models::gamelog_location::GamelogLocation;
+
+    #[test]
+    fn test_fixture_is_seeded() {
+        let other = Fixture::generate(&FixtureOptions {
+            seed: 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            Fixture::generate(&FixtureOptions::default()),
+            Fixture::generate(&FixtureOptions::default())
+        );
+        assert_ne!(Fixture::generate(&FixtureOptions::default()), other);
+    }
+
+    #[test]
+    fn test_fixture_covers_every_access_type() {
+        let fixture = Fixture::generate(&FixtureOptions::default());
+        let access = fixture
+            .visits
+            .iter()
+            .map(|visit| visit.access)
+            .collect::<HashSet<_>>();
+        assert_eq!(access, AccessType::ALL.into_iter().collect());
+
+        let locations = fixture
+            .locations
+            .into_iter()
+            .map(GamelogLocation::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let instances = locations.iter().map(|location| &location.world_instance);
+        assert!(instances.clone().any(|instance| instance.hidden.is_some()));
+        assert!(instances.clone().any(|instance| instance.friends.is_some()));
+        assert!(instances.clone().any(|instance| instance.private.is_some()));
+        assert!(instances.clone().any(|instance| instance.group.is_some()));
+    }
+}
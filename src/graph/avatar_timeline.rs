@@ -0,0 +1,406 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+use surrealdb::{Connection, Surreal};
+
+use crate::models::avatar::Avatar;
+use crate::models::avatar_history::AvatarHistory;
+use crate::models::feed_avatar::FeedAvatar;
+use crate::models::session::Session;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::repo::wear::WearRepo;
+
+/// A stretch of time a player wore one avatar, stored as a `player->wore->avatar` relation.
+///
+/// # Values
+///
+/// - `user_id` - The player, keying the `player` record.
+/// - `avatar` - The key of the `avatar` record, see `AvatarTimeline::build`.
+/// - `avatar_name` - The avatar's name, as the feed or the avatar cache had it.
+/// - `owner_id` - The uploader of the avatar, if known.
+/// - `worn_from` - When the player switched into the avatar.
+/// - `worn_to` - When they switched out of it. `None` for the last avatar seen.
+/// - `image_url`, `thumbnail_image_url` - The avatar's images.
+/// - `previous_image_url`, `previous_thumbnail_image_url` - The images of the avatar worn
+///   before, as the feed reported them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Wear {
+    pub user_id: String,
+    pub display_name: String,
+    pub avatar: String,
+    pub avatar_name: String,
+    pub owner_id: Option<String>,
+    pub worn_from: DateTime<Utc>,
+    pub worn_to: Option<DateTime<Utc>>,
+    pub image_url: Option<String>,
+    pub thumbnail_image_url: Option<String>,
+    pub previous_image_url: Option<String>,
+    pub previous_thumbnail_image_url: Option<String>,
+}
+
+impl Wear {
+    /// Check if the avatar was worn at `at`.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.worn_from <= at && self.worn_to.is_none_or(|to| at < to)
+    }
+
+    /// How long the avatar was worn in milliseconds, counting up to `now` if it still is.
+    pub fn duration(&self, now: DateTime<Utc>) -> u64 {
+        let to = self.worn_to.unwrap_or(now);
+        (to - self.worn_from).num_milliseconds().max(0) as u64
+    }
+}
+
+/// A session of a player, with the avatar they were wearing when it started.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Meeting {
+    pub session: Session,
+    pub wear: Option<Wear>,
+}
+
+/// How much an avatar was worn, see `AvatarTimeline::most_worn`.
+///
+/// # Values
+///
+/// - `avatar` - The key of the `avatar` record.
+/// - `avatar_name` - The name it was last worn under.
+/// - `wearers` - How many different players wore it.
+/// - `wears` - How many times it was switched into.
+/// - `total_time` - How long it was worn in total, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct AvatarPopularity {
+    pub avatar: String,
+    pub avatar_name: String,
+    pub wearers: u64,
+    pub wears: u64,
+    pub total_time: u64,
+}
+
+/// Every avatar every player was seen wearing, built from `_feed_avatar` and `_avatar_history`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{Duration, TimeZone, Utc};
+/// use surrealdb_test::graph::avatar_timeline::AvatarTimeline;
+/// use surrealdb_test::models::feed_avatar::FeedAvatar;
+///
+/// let at = Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap();
+/// let switch = |hours: i64, avatar_name: &str| FeedAvatar {
+///     created_at: at + Duration::hours(hours),
+///     user_id: "usr_1234".to_string(),
+///     avatar_name: avatar_name.to_string(),
+///     image_url: Some(format!("https://example.com/file/file_{}/1/file", avatar_name)),
+///     ..Default::default()
+/// };
+///
+/// let timeline = AvatarTimeline::build(&[switch(0, "a"), switch(2, "b")], &[], "usr_me", &[]);
+/// assert_eq!(timeline.wears.len(), 2);
+/// assert_eq!(timeline.wears[0].avatar, "file_a");
+/// assert_eq!(timeline.wears[0].worn_to, Some(at + Duration::hours(2)));
+///
+/// let wear = timeline.worn_at("usr_1234", at + Duration::hours(1)).unwrap();
+/// assert_eq!(wear.avatar_name, "a");
+/// assert!(timeline.worn_at("usr_1234", at - Duration::hours(1)).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub struct AvatarTimeline {
+    /// Ordered by `user_id`, then `worn_from`.
+    pub wears: Vec<Wear>,
+}
+
+impl AvatarTimeline {
+    /// Create a new, empty `AvatarTimeline`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the timeline from the friends' avatar feed and the account's own avatar history.
+    ///
+    /// # What it does
+    ///
+    /// * Every feed row starts a `Wear` for its player, and every history row one for `owner`.
+    /// * A wear ends when the next one of the same player starts. Switching into the avatar
+    ///   already worn doesn't start a new wear.
+    /// * History rows name their avatar by id, which is used as the `avatar` key.
+    /// * Feed rows only have the avatar's images, so the key is the id of the avatar in `avatars`
+    ///   with the same `image_url`. Failing that it is the file id in the image url, e.g.
+    ///   `file_1234` for `https://api.vrchat.cloud/api/1/file/file_1234/1/file`, and failing
+    ///   that the uploader and name.
+    /// * Names, uploaders and images missing from history rows are taken from `avatars`.
+    pub fn build(
+        feed: &[FeedAvatar],
+        history: &[AvatarHistory],
+        owner: &str,
+        avatars: &[Avatar],
+    ) -> Self {
+        let by_id: HashMap<&str, &Avatar> = avatars
+            .iter()
+            .map(|avatar| (avatar.id.as_str(), avatar))
+            .collect();
+        let by_image: HashMap<&str, &Avatar> = avatars
+            .iter()
+            .filter_map(|avatar| Some((avatar.image_url.as_deref()?, avatar)))
+            .collect();
+
+        let mut players: BTreeMap<&str, Vec<Wear>> = BTreeMap::new();
+        for row in feed {
+            let cached = row
+                .image_url
+                .as_deref()
+                .and_then(|image_url| by_image.get(image_url));
+            players.entry(&row.user_id).or_default().push(Wear {
+                user_id: row.user_id.clone(),
+                display_name: row.display_name.clone(),
+                avatar: match cached {
+                    Some(avatar) => avatar.id.clone(),
+                    None => avatar_key(row),
+                },
+                avatar_name: row.avatar_name.clone(),
+                owner_id: row.owner_id.clone(),
+                worn_from: row.created_at,
+                worn_to: None,
+                image_url: row.image_url.clone(),
+                thumbnail_image_url: row.thumbnail_image_url.clone(),
+                previous_image_url: row.previous_image_url.clone(),
+                previous_thumbnail_image_url: row.previous_thumbnail_image_url.clone(),
+            });
+        }
+        for row in history {
+            let cached = by_id.get(row.avatar_id.as_str());
+            players.entry(owner).or_default().push(Wear {
+                user_id: owner.to_string(),
+                display_name: owner.to_string(),
+                avatar: row.avatar_id.clone(),
+                avatar_name: cached.map_or(row.avatar_id.clone(), |avatar| avatar.name.clone()),
+                owner_id: cached.and_then(|avatar| avatar.author_id.clone()),
+                worn_from: row.created_at,
+                worn_to: None,
+                image_url: cached.and_then(|avatar| avatar.image_url.clone()),
+                thumbnail_image_url: cached.and_then(|avatar| avatar.thumbnail_image_url.clone()),
+                previous_image_url: None,
+                previous_thumbnail_image_url: None,
+            });
+        }
+
+        let mut wears = Vec::new();
+        for (_, mut switches) in players {
+            switches.sort_by_key(|wear| wear.worn_from);
+            let mut player: Vec<Wear> = Vec::with_capacity(switches.len());
+            for wear in switches {
+                match player.last_mut() {
+                    Some(last) if last.avatar == wear.avatar => continue,
+                    Some(last) => last.worn_to = Some(wear.worn_from),
+                    None => {}
+                }
+                player.push(wear);
+            }
+            wears.extend(player);
+        }
+        AvatarTimeline { wears }
+    }
+
+    /// Every wear of `user_id`, oldest first.
+    pub fn of_player<'a>(&'a self, user_id: &'a str) -> impl Iterator<Item = &'a Wear> {
+        self.wears
+            .iter()
+            .filter(move |wear| wear.user_id == user_id)
+    }
+
+    /// The avatar `user_id` was wearing at `at`, if it was seen.
+    pub fn worn_at(&self, user_id: &str, at: DateTime<Utc>) -> Option<&Wear> {
+        self.wears
+            .iter()
+            .find(|wear| wear.user_id == user_id && wear.contains(at))
+    }
+
+    /// The avatar `user_id` was wearing at the start of each of their `sessions`, i.e. when
+    /// they were met. Sessions of other players are skipped.
+    pub fn when_met(&self, user_id: &str, sessions: &[Session]) -> Vec<Meeting> {
+        sessions
+            .iter()
+            .filter(|session| session.user_id.as_deref() == Some(user_id))
+            .map(|session| Meeting {
+                session: session.clone(),
+                wear: session
+                    .started_at()
+                    .and_then(|at| self.worn_at(user_id, at))
+                    .cloned(),
+            })
+            .collect()
+    }
+
+    /// The avatars worn by `friends`, worn by the most friends first, then the longest worn.
+    ///
+    /// Wears still going on are counted up to `now`.
+    pub fn most_worn(
+        &self,
+        friends: &[UsrFriendLogCurrent],
+        now: DateTime<Utc>,
+    ) -> Vec<AvatarPopularity> {
+        let friends: HashSet<&str> = friends
+            .iter()
+            .map(|friend| friend.user_id.as_str())
+            .collect();
+        let mut avatars: HashMap<&str, (AvatarPopularity, HashSet<&str>)> = HashMap::new();
+        for wear in &self.wears {
+            if !friends.contains(wear.user_id.as_str()) {
+                continue;
+            }
+            let (popularity, wearers) = avatars.entry(&wear.avatar).or_default();
+            popularity.avatar = wear.avatar.clone();
+            popularity.avatar_name = wear.avatar_name.clone();
+            popularity.wears += 1;
+            popularity.total_time += wear.duration(now);
+            wearers.insert(&wear.user_id);
+        }
+
+        let mut popular = avatars
+            .into_values()
+            .map(|(popularity, wearers)| AvatarPopularity {
+                wearers: wearers.len() as u64,
+                ..popularity
+            })
+            .collect::<Vec<_>>();
+        popular.sort_by(|a, b| {
+            b.wearers
+                .cmp(&a.wearers)
+                .then_with(|| b.total_time.cmp(&a.total_time))
+                .then_with(|| a.avatar.cmp(&b.avatar))
+        });
+        popular
+    }
+
+    /// Replace every `wore` relation with this timeline, adding the `player` and `avatar`
+    /// records it links.
+    pub async fn store<C: Connection>(&self, db: &Surreal<C>) -> Result<(), Box<dyn Error>> {
+        WearRepo::new(db.clone()).replace_all(&self.wears).await?;
+        Ok(())
+    }
+
+    /// Read the timeline back from SurrealDB, only of `user_id` if it is given.
+    pub async fn fetch<C: Connection>(
+        db: &Surreal<C>,
+        user_id: Option<&str>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let wears = WearRepo::new(db.clone()).find(user_id).await?;
+        Ok(AvatarTimeline { wears })
+    }
+}
+
+fn avatar_key(row: &FeedAvatar) -> String {
+    let file_id = row
+        .image_url
+        .as_deref()
+        .and_then(|url| url.split('/').find(|part| part.starts_with("file_")));
+    match file_id {
+        Some(file_id) => file_id.to_string(),
+        None => format!(
+            "{}/{}",
+            row.owner_id.as_deref().unwrap_or_default(),
+            row.avatar_name
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::graph::avatar_timeline::AvatarTimeline;
+    use crate::models::avatar::Avatar;
+    use crate::models::avatar_history::AvatarHistory;
+    use crate::models::feed_avatar::FeedAvatar;
+    use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+
+    fn switch(user_id: &str, hours: i64, image: &str) -> FeedAvatar {
+        FeedAvatar {
+            created_at: Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap()
+                + Duration::hours(hours),
+            user_id: user_id.to_string(),
+            avatar_name: image.to_string(),
+            owner_id: Some("usr_author".to_string()),
+            image_url: Some(format!("https://example.com/{}", image)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cached_avatars_are_keyed_by_id() {
+        let avatars = [Avatar {
+            id: "avtr_1234".to_string(),
+            name: "Cached".to_string(),
+            image_url: Some("https://example.com/a".to_string()),
+            ..Default::default()
+        }];
+        let history = [AvatarHistory {
+            avatar_id: "avtr_1234".to_string(),
+            created_at: Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap(),
+            ..Default::default()
+        }];
+        let timeline = AvatarTimeline::build(
+            &[switch("usr_a", 0, "a"), switch("usr_a", 1, "b")],
+            &history,
+            "usr_me",
+            &avatars,
+        );
+        let keys = timeline
+            .wears
+            .iter()
+            .map(|wear| (wear.user_id.as_str(), wear.avatar.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                ("usr_a", "avtr_1234"),
+                ("usr_a", "usr_author/b"),
+                ("usr_me", "avtr_1234")
+            ]
+        );
+        assert_eq!(timeline.wears[2].avatar_name, "Cached");
+    }
+
+    #[test]
+    fn test_repeated_switches_extend_the_wear() {
+        let timeline = AvatarTimeline::build(
+            &[
+                switch("usr_a", 0, "a"),
+                switch("usr_a", 1, "a"),
+                switch("usr_a", 2, "b"),
+            ],
+            &[],
+            "usr_me",
+            &[],
+        );
+        assert_eq!(timeline.wears.len(), 2);
+        assert_eq!(timeline.wears[0].worn_to, Some(timeline.wears[1].worn_from));
+        assert_eq!(timeline.wears[0].duration(Utc::now()), 2 * 60 * 60 * 1000);
+    }
+
+    #[test]
+    fn test_most_worn_counts_friends_only() {
+        let timeline = AvatarTimeline::build(
+            &[
+                switch("usr_a", 0, "a"),
+                switch("usr_b", 0, "a"),
+                switch("usr_b", 1, "b"),
+                switch("usr_c", 0, "b"),
+            ],
+            &[],
+            "usr_me",
+            &[],
+        );
+        let friend = |user_id: &str| UsrFriendLogCurrent {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        };
+        let now = Utc.with_ymd_and_hms(2023, 4, 29, 12, 0, 0).unwrap();
+        let popular = timeline.most_worn(&[friend("usr_a"), friend("usr_b")], now);
+        assert_eq!(popular.len(), 2);
+        assert_eq!(popular[0].avatar, "usr_author/a");
+        assert_eq!(popular[0].wearers, 2);
+        assert_eq!(popular[0].total_time, 3 * 60 * 60 * 1000);
+        assert_eq!(popular[1].wearers, 1);
+    }
+}
@@ -0,0 +1,698 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+use chrono::{DateTime, Duration, Utc};
+use surrealdb::{Connection, Surreal};
+
+use crate::import::merge::stable_id;
+use crate::models::feed_gps::FeedGps;
+use crate::models::notification::Notification;
+use crate::models::session::Session;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::repo::graph::GraphRepo;
+use crate::zaphkiel::notification_kind::NotificationKind;
+use crate::zaphkiel::world_instance::WorldInstance;
+
+/// The kinds of nodes in the social graph, one SurrealDB table each.
+///
+/// # Available Kinds
+/// - Player
+/// - World
+/// - Instance
+/// - Avatar: an avatar a player wore, see `AvatarTimeline`
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub enum NodeKind {
+    Player,
+    World,
+    Instance,
+    Avatar,
+}
+
+impl NodeKind {
+    /// Every node kind.
+    pub const ALL: [NodeKind; 4] = [
+        NodeKind::Player,
+        NodeKind::World,
+        NodeKind::Instance,
+        NodeKind::Avatar,
+    ];
+
+    /// The SurrealDB table holding nodes of this kind.
+    pub fn table(&self) -> &'static str {
+        match self {
+            NodeKind::Player => "player",
+            NodeKind::World => "world",
+            NodeKind::Instance => "instance",
+            NodeKind::Avatar => "avatar",
+        }
+    }
+}
+
+/// The kinds of edges in the social graph, one SurrealDB relation table each.
+///
+/// # Available Kinds
+/// - Joined: `player->joined->instance`
+/// - Met: `player->met->player`, when two players were in the same instance at the same time
+/// - Friend: `player->friend->player`, from the owner to each friend
+/// - InstanceOf: `instance->instance_of->world`
+/// - Visited: `player->visited->instance`, a friend moving there, from `_feed_gps`
+/// - Invited: `player->invited->player`, from the sender of an invite to its receiver, from
+///   `_notifications`
+/// - Wore: `player->wore->avatar`, a stretch of time a player wore an avatar, see
+///   `AvatarTimeline`
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub enum EdgeKind {
+    Joined,
+    Met,
+    Friend,
+    InstanceOf,
+    Visited,
+    Invited,
+    Wore,
+}
+
+impl EdgeKind {
+    /// Every edge kind.
+    pub const ALL: [EdgeKind; 7] = [
+        EdgeKind::Joined,
+        EdgeKind::Met,
+        EdgeKind::Friend,
+        EdgeKind::InstanceOf,
+        EdgeKind::Visited,
+        EdgeKind::Invited,
+        EdgeKind::Wore,
+    ];
+
+    /// The SurrealDB relation table holding edges of this kind.
+    pub fn table(&self) -> &'static str {
+        match self {
+            EdgeKind::Joined => "joined",
+            EdgeKind::Met => "met",
+            EdgeKind::Friend => "friend",
+            EdgeKind::InstanceOf => "instance_of",
+            EdgeKind::Visited => "visited",
+            EdgeKind::Invited => "invited",
+            EdgeKind::Wore => "wore",
+        }
+    }
+
+    /// The fields holding when the edge was first and last seen.
+    ///
+    /// `wore` edges are `Wear`s, which call them `worn_from` and `worn_to`.
+    pub fn seen_fields(&self) -> (&'static str, &'static str) {
+        match self {
+            EdgeKind::Wore => ("worn_from", "worn_to"),
+            _ => ("first_seen", "last_seen"),
+        }
+    }
+
+    /// Check if the edge has no direction.
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, EdgeKind::Met)
+    }
+}
+
+/// A node in the social graph.
+///
+/// `key` is the SurrealDB record key, e.g. `usr_1234` for `player:usr_1234`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub key: String,
+    pub label: String,
+}
+
+impl Node {
+    /// The record id of the node, e.g. `player:usr_1234`.
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.kind.table(), self.key)
+    }
+}
+
+/// An edge in the social graph, between two node ids.
+///
+/// `weight` counts the sessions (`Joined`), overlapping session pairs (`Met`) or invites
+/// (`Invited`) behind the edge.
+/// `Visited` edges are one move each, so their weight is always `1`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Edge {
+    pub kind: EdgeKind,
+    pub from: String,
+    pub to: String,
+    pub weight: Option<u64>,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl Edge {
+    /// Check if the edge was seen at any point in `[from, to)`. Edges without timestamps always
+    /// match.
+    pub fn overlaps(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> bool {
+        from.is_none_or(|from| self.last_seen.is_none_or(|last| last >= from))
+            && to.is_none_or(|to| self.first_seen.is_none_or(|first| first < to))
+    }
+
+    /// The record key of the edge, so storing it again updates it instead of adding another.
+    ///
+    /// Edges are keyed by their endpoints. `Visited` and `Wore` edges are one event each, so
+    /// they are also keyed by when it happened.
+    pub fn key(&self) -> i64 {
+        match self.kind {
+            EdgeKind::Visited | EdgeKind::Wore => {
+                let at = self
+                    .first_seen
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_default();
+                stable_id(&[&self.from, &self.to, &at])
+            }
+            _ => stable_id(&[&self.from, &self.to]),
+        }
+    }
+
+    fn record(&mut self, weight: u64, at: Option<DateTime<Utc>>) {
+        self.weight = Some(self.weight.unwrap_or_default() + weight);
+        if let Some(at) = at {
+            self.first_seen = Some(self.first_seen.map_or(at, |first| first.min(at)));
+            self.last_seen = Some(self.last_seen.map_or(at, |last| last.max(at)));
+        }
+    }
+}
+
+/// Options for building, fetching and writing a `SocialGraph`.
+///
+/// # Values
+///
+/// - `weights` - Include edge weights in the output.
+/// - `from` - Only edges seen at or after this time.
+/// - `to` - Only edges seen before this time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct GraphOptions {
+    pub weights: bool,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// The player/world/instance graph described in `docs/Database-ERDiagram.puml`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{Duration, TimeZone, Utc};
+/// use surrealdb_test::graph::social_graph::{EdgeKind, GraphOptions, SocialGraph};
+/// use surrealdb_test::models::session::Session;
+///
+/// let joined_at = Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap();
+/// let session = |user_id: &str| Session {
+///     display_name: user_id.to_string(),
+///     user_id: Some(user_id.to_string()),
+///     location: "wrld_1234:1234".into(),
+///     joined_at: Some(joined_at),
+///     left_at: Some(joined_at + Duration::hours(1)),
+///     duration: None,
+/// };
+///
+/// let graph = SocialGraph::build(
+///     &[session("usr_a"), session("usr_b")],
+///     &[],
+///     "usr_me",
+///     &GraphOptions::default(),
+/// );
+///
+/// assert_eq!(graph.nodes.len(), 4);
+/// assert_eq!(graph.edges_of(EdgeKind::Met).count(), 1);
+/// assert_eq!(graph.edges_of(EdgeKind::Joined).count(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub struct SocialGraph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl SocialGraph {
+    /// Create a new, empty `SocialGraph`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every edge of `kind`.
+    pub fn edges_of(&self, kind: EdgeKind) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.kind == kind)
+    }
+
+    /// Build the graph from sessions and the current friend list.
+    ///
+    /// # What it does
+    ///
+    /// * Sessions outside `options.from`/`options.to` are skipped.
+    /// * Every session adds a `player`, `instance` and `world` node, a `joined` edge from the
+    ///   player to the instance and an `instance_of` edge from the instance to the world.
+    /// * Players are keyed by `user_id`, or by `display_name` when the id is unknown.
+    /// * Two sessions of different players in the same instance that overlap in time add a `met`
+    ///   edge between the players.
+    /// * Every friend adds a `player` node and a `friend` edge from `owner`.
+    pub fn build(
+        sessions: &[Session],
+        friends: &[UsrFriendLogCurrent],
+        owner: &str,
+        options: &GraphOptions,
+    ) -> Self {
+        let mut nodes = BTreeMap::new();
+        let mut edges = BTreeMap::new();
+        let mut by_instance: HashMap<&WorldInstance, Vec<(String, &Session)>> = HashMap::new();
+
+        let mut add_node = |kind: NodeKind, key: &str, label: &str| {
+            let node = Node {
+                kind,
+                key: key.to_string(),
+                label: label.to_string(),
+            };
+            let id = node.id();
+            nodes.insert(id.clone(), node);
+            id
+        };
+
+        let mut add_edge = |kind: EdgeKind, from: String, to: String, at: Option<DateTime<Utc>>| {
+            let (from, to) = if kind.is_symmetric() && to < from {
+                (to, from)
+            } else {
+                (from, to)
+            };
+            edges
+                .entry((kind, from.clone(), to.clone()))
+                .or_insert(Edge {
+                    kind,
+                    from,
+                    to,
+                    weight: None,
+                    first_seen: None,
+                    last_seen: None,
+                })
+                .record(1, at);
+        };
+
+        for session in sessions {
+            let started_at = session.started_at();
+            if let Some(started_at) = started_at {
+                if options.from.is_some_and(|from| started_at < from)
+                    || options.to.is_some_and(|to| started_at >= to)
+                {
+                    continue;
+                }
+            }
+
+            let key = player_key(session);
+            let player = add_node(NodeKind::Player, &key, &session.display_name);
+            let Some(location) = session.location.world_instance() else {
+                continue;
+            };
+
+            let instance_key = format!("{}:{}", location.world_id, location.instance_id);
+            let instance = add_node(NodeKind::Instance, &instance_key, &instance_key);
+            let world = add_node(NodeKind::World, &location.world_id, &location.world_id);
+
+            add_edge(
+                EdgeKind::Joined,
+                player.clone(),
+                instance.clone(),
+                started_at,
+            );
+            add_edge(EdgeKind::InstanceOf, instance, world, started_at);
+            by_instance
+                .entry(location)
+                .or_default()
+                .push((player, session));
+        }
+
+        for sessions in by_instance.values() {
+            for (index, (a, a_session)) in sessions.iter().enumerate() {
+                for (b, b_session) in &sessions[index + 1..] {
+                    if a == b {
+                        continue;
+                    }
+                    if let Some(met_at) = overlap(a_session, b_session) {
+                        add_edge(EdgeKind::Met, a.clone(), b.clone(), Some(met_at));
+                    }
+                }
+            }
+        }
+
+        if !friends.is_empty() {
+            let owner = add_node(NodeKind::Player, owner, owner);
+            for friend in friends {
+                let friend = add_node(NodeKind::Player, &friend.user_id, &friend.display_name);
+                add_edge(EdgeKind::Friend, owner.clone(), friend, None);
+            }
+        }
+
+        SocialGraph {
+            nodes: nodes.into_values().collect(),
+            edges: edges.into_values().collect(),
+        }
+    }
+
+    /// Build a `visited` edge for every move in the friends' GPS feed.
+    ///
+    /// # What it does
+    ///
+    /// * Moves outside `options.from`/`options.to` are skipped.
+    /// * Every move adds a `player` node. Moves into a world instance also add an `instance`
+    ///   node, keyed as in `build` so both kinds of edges meet at the same instances, and a
+    ///   `visited` edge from the player to it.
+    /// * Moves are not merged: two visits to the same instance are two edges.
+    /// * `first_seen` is when the player arrived, and `last_seen` when they moved on, or the
+    ///   arrival again if that is their last known move.
+    pub fn from_gps(gps: &[FeedGps], options: &GraphOptions) -> Self {
+        let mut nodes = BTreeMap::new();
+        let mut edges = Vec::new();
+
+        let mut by_player: BTreeMap<String, Vec<&FeedGps>> = BTreeMap::new();
+        for hop in gps {
+            let key = if hop.user_id.is_empty() {
+                hop.display_name.clone()
+            } else {
+                hop.user_id.clone()
+            };
+            by_player.entry(key).or_default().push(hop);
+        }
+
+        for (key, mut hops) in by_player {
+            hops.sort_by_key(|hop| hop.created_at);
+            for (index, hop) in hops.iter().enumerate() {
+                if options.from.is_some_and(|from| hop.created_at < from)
+                    || options.to.is_some_and(|to| hop.created_at >= to)
+                {
+                    continue;
+                }
+
+                let player = Node {
+                    kind: NodeKind::Player,
+                    key: key.clone(),
+                    label: hop.display_name.clone(),
+                };
+                let from = player.id();
+                nodes.insert(from.clone(), player);
+                let Some(location) = hop.location.world_instance() else {
+                    continue;
+                };
+
+                let instance_key = format!("{}:{}", location.world_id, location.instance_id);
+                let instance = Node {
+                    kind: NodeKind::Instance,
+                    key: instance_key.clone(),
+                    label: instance_key,
+                };
+                let to = instance.id();
+                nodes.insert(to.clone(), instance);
+
+                let left_at = hops.get(index + 1).map(|next| next.created_at);
+                edges.push(Edge {
+                    kind: EdgeKind::Visited,
+                    from,
+                    to,
+                    weight: Some(1),
+                    first_seen: Some(hop.created_at),
+                    last_seen: Some(left_at.unwrap_or(hop.created_at)),
+                });
+            }
+        }
+
+        SocialGraph {
+            nodes: nodes.into_values().collect(),
+            edges,
+        }
+    }
+
+    /// Build an `invited` edge from the sender of every invite to its receiver.
+    ///
+    /// # What it does
+    ///
+    /// * Only `Invite` notifications are used, and those outside `options.from`/`options.to`
+    ///   or without a sender or receiver are skipped.
+    /// * The sender and receiver add a `player` node each, labelled with the sender's
+    ///   username and the receiver's user id.
+    /// * Invites between the same two players are one edge, weighted by how many there were.
+    /// * Where each invite was to is kept on its `notification` record, see `NotificationRepo`.
+    pub fn from_notifications(notifications: &[Notification], options: &GraphOptions) -> Self {
+        let mut nodes = BTreeMap::new();
+        let mut edges = BTreeMap::new();
+
+        for notification in notifications {
+            if notification.kind != NotificationKind::Invite
+                || notification.sender_user_id.is_empty()
+                || notification.receiver_user_id.is_empty()
+                || options
+                    .from
+                    .is_some_and(|from| notification.created_at < from)
+                || options.to.is_some_and(|to| notification.created_at >= to)
+            {
+                continue;
+            }
+
+            let sender = Node {
+                kind: NodeKind::Player,
+                key: notification.sender_user_id.clone(),
+                label: notification.sender_username.clone(),
+            };
+            let receiver = Node {
+                kind: NodeKind::Player,
+                key: notification.receiver_user_id.clone(),
+                label: notification.receiver_user_id.clone(),
+            };
+            let (from, to) = (sender.id(), receiver.id());
+            nodes.insert(from.clone(), sender);
+            nodes.entry(to.clone()).or_insert(receiver);
+
+            edges
+                .entry((from.clone(), to.clone()))
+                .or_insert(Edge {
+                    kind: EdgeKind::Invited,
+                    from,
+                    to,
+                    weight: None,
+                    first_seen: None,
+                    last_seen: None,
+                })
+                .record(1, Some(notification.created_at));
+        }
+
+        SocialGraph {
+            nodes: nodes.into_values().collect(),
+            edges: edges.into_values().collect(),
+        }
+    }
+
+    /// Delete every edge of `kind`, so a graph that rebuilds them can be stored without
+    /// keeping the edges that are gone.
+    pub async fn clear<C: Connection>(
+        db: &Surreal<C>,
+        kind: EdgeKind,
+    ) -> Result<(), Box<dyn Error>> {
+        GraphRepo::new(db.clone()).clear(kind).await?;
+        Ok(())
+    }
+
+    /// Write the graph into SurrealDB, as `player`, `world`, `instance` and `avatar` records and
+    /// `joined`, `met`, `friend`, `instance_of`, `visited`, `invited` and `wore` relations.
+    ///
+    /// Edges are written under `Edge::key`, so storing a graph again updates its edges instead
+    /// of duplicating them.
+    pub async fn store<C: Connection>(&self, db: &Surreal<C>) -> Result<(), Box<dyn Error>> {
+        let repo = GraphRepo::new(db.clone());
+        repo.upsert_nodes(&self.nodes).await?;
+        repo.relate_all(&self.edges).await
+    }
+
+    /// Read the graph back from SurrealDB, keeping only the edges that match `options`.
+    pub async fn fetch<C: Connection>(
+        db: &Surreal<C>,
+        options: &GraphOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        let repo = GraphRepo::new(db.clone());
+        let mut graph = SocialGraph::new();
+        for kind in NodeKind::ALL {
+            graph.nodes.extend(repo.nodes(kind).await?);
+        }
+        for kind in EdgeKind::ALL {
+            graph.edges.extend(
+                repo.edges(kind)
+                    .await?
+                    .into_iter()
+                    .filter(|edge| edge.overlaps(options.from, options.to)),
+            );
+        }
+        Ok(graph)
+    }
+}
+
+fn player_key(session: &Session) -> String {
+    session
+        .user_id
+        .clone()
+        .unwrap_or_else(|| session.display_name.clone())
+}
+
+/// The end of a session, worked out from `duration` when the leave is missing.
+fn ended_at(session: &Session) -> Option<DateTime<Utc>> {
+    session.left_at.or_else(|| {
+        let duration = Duration::milliseconds(session.duration? as i64);
+        Some(session.joined_at? + duration)
+    })
+}
+
+/// When two sessions started overlapping, if they did.
+fn overlap(a: &Session, b: &Session) -> Option<DateTime<Utc>> {
+    let a_start = a.started_at()?;
+    let b_start = b.started_at()?;
+    let a_end = ended_at(a).unwrap_or(a_start);
+    let b_end = ended_at(b).unwrap_or(b_start);
+
+    (a_start <= b_end && b_start <= a_end).then(|| a_start.max(b_start))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Timelike, Utc};
+
+    use crate::graph::social_graph::{EdgeKind, GraphOptions, SocialGraph};
+    use crate::models::feed_gps::FeedGps;
+    use crate::models::notification::Notification;
+    use crate::models::session::Session;
+    use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+    use crate::zaphkiel::notification_kind::NotificationKind;
+
+    fn session(user_id: &str, hour: u32) -> Session {
+        let joined_at = Utc.with_ymd_and_hms(2023, 4, 29, hour, 0, 0).unwrap();
+        Session {
+            display_name: user_id.to_string(),
+            user_id: Some(user_id.to_string()),
+            location: "wrld_1234:1234".into(),
+            joined_at: Some(joined_at),
+            left_at: Some(joined_at + Duration::minutes(30)),
+            duration: None,
+        }
+    }
+
+    #[test]
+    fn test_build_skips_sessions_that_do_not_overlap() {
+        let graph = SocialGraph::build(
+            &[session("usr_a", 10), session("usr_b", 12)],
+            &[],
+            "usr_me",
+            &GraphOptions::default(),
+        );
+
+        assert_eq!(graph.edges_of(EdgeKind::Met).count(), 0);
+        assert_eq!(graph.edges_of(EdgeKind::Joined).count(), 2);
+    }
+
+    #[test]
+    fn test_build_with_time_filter_and_friends() {
+        let options = GraphOptions {
+            from: Some(Utc.with_ymd_and_hms(2023, 4, 29, 11, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        let friends = [UsrFriendLogCurrent {
+            user_id: "usr_a".to_string(),
+            display_name: "a".to_string(),
+            ..Default::default()
+        }];
+        let graph = SocialGraph::build(
+            &[
+                session("usr_a", 10),
+                session("usr_b", 12),
+                session("usr_b", 12),
+            ],
+            &friends,
+            "usr_me",
+            &options,
+        );
+
+        let joined = graph.edges_of(EdgeKind::Joined).collect::<Vec<_>>();
+        assert_eq!(joined.len(), 1);
+        assert_eq!(joined[0].from, "player:usr_b");
+        assert_eq!(joined[0].weight, Some(2));
+
+        let friend = graph.edges_of(EdgeKind::Friend).collect::<Vec<_>>();
+        assert_eq!(friend.len(), 1);
+        assert_eq!(friend[0].from, "player:usr_me");
+        assert_eq!(friend[0].to, "player:usr_a");
+    }
+
+    #[test]
+    fn test_from_gps_adds_one_edge_per_move() {
+        let hop = |hour: u32, location: &str| FeedGps {
+            created_at: Utc.with_ymd_and_hms(2023, 4, 29, hour, 0, 0).unwrap(),
+            user_id: "usr_a".to_string(),
+            display_name: "a".to_string(),
+            location: location.into(),
+            ..Default::default()
+        };
+        let gps = [
+            hop(12, "wrld_1234:1234"),
+            hop(10, "wrld_1234:1234"),
+            hop(11, "private"),
+        ];
+        let graph = SocialGraph::from_gps(&gps, &GraphOptions::default());
+
+        let visited = graph.edges_of(EdgeKind::Visited).collect::<Vec<_>>();
+        assert_eq!(visited.len(), 2);
+        assert!(visited
+            .iter()
+            .all(|edge| edge.from == "player:usr_a" && edge.to == "instance:wrld_1234:1234"));
+        assert_eq!(visited[0].first_seen.unwrap().hour(), 10);
+        assert_eq!(visited[0].last_seen.unwrap().hour(), 11);
+        assert_eq!(visited[1].last_seen, visited[1].first_seen);
+        assert_ne!(visited[0].key(), visited[1].key());
+        assert_eq!(graph.nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_edge_keys_survive_a_rebuild() {
+        let build = |sessions: &[Session]| {
+            SocialGraph::build(sessions, &[], "usr_me", &GraphOptions::default())
+                .edges
+                .iter()
+                .map(|edge| (edge.kind, edge.key()))
+                .collect::<Vec<_>>()
+        };
+        let once = build(&[session("usr_a", 10), session("usr_b", 10)]);
+        let twice = build(&[
+            session("usr_a", 10),
+            session("usr_b", 10),
+            session("usr_a", 12),
+        ]);
+        assert_eq!(once, twice);
+    }
+
+    #[test]
+    fn test_from_notifications_counts_invites() {
+        let notification = |kind: NotificationKind, sender: &str, hour: u32| Notification {
+            created_at: Utc.with_ymd_and_hms(2023, 4, 29, hour, 0, 0).unwrap(),
+            kind,
+            sender_user_id: sender.to_string(),
+            sender_username: sender.to_string(),
+            receiver_user_id: "usr_me".to_string(),
+            ..Default::default()
+        };
+        let notifications = [
+            notification(NotificationKind::Invite, "usr_a", 12),
+            notification(NotificationKind::Invite, "usr_a", 10),
+            notification(NotificationKind::FriendRequest, "usr_b", 11),
+            notification(NotificationKind::Invite, "", 11),
+        ];
+        let graph = SocialGraph::from_notifications(&notifications, &GraphOptions::default());
+
+        let invited = graph.edges_of(EdgeKind::Invited).collect::<Vec<_>>();
+        assert_eq!(invited.len(), 1);
+        assert_eq!(invited[0].from, "player:usr_a");
+        assert_eq!(invited[0].to, "player:usr_me");
+        assert_eq!(invited[0].weight, Some(2));
+        assert_eq!(invited[0].first_seen.unwrap().hour(), 10);
+        assert_eq!(invited[0].last_seen.unwrap().hour(), 12);
+        assert_eq!(graph.nodes.len(), 2);
+    }
+}
@@ -0,0 +1,261 @@
+use std::io::{Result, Write};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+
+use crate::graph::social_graph::{EdgeKind, GraphOptions, NodeKind, SocialGraph};
+
+/// The file formats a `SocialGraph` can be written as.
+///
+/// # Available Formats
+/// - GraphMl: see `write_graphml`
+/// - Dot: Graphviz DOT, see `write_dot`
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::graph::writers::GraphFormat;
+///
+/// assert_eq!("graphml".parse::<GraphFormat>().unwrap(), GraphFormat::GraphMl);
+/// assert_eq!("dot".parse::<GraphFormat>().unwrap(), GraphFormat::Dot);
+/// assert!("svg".parse::<GraphFormat>().is_err());
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum GraphFormat {
+    #[default]
+    GraphMl,
+    Dot,
+}
+
+impl GraphFormat {
+    /// Write `graph` in this format.
+    pub fn write<W: Write>(
+        &self,
+        graph: &SocialGraph,
+        options: &GraphOptions,
+        out: W,
+    ) -> Result<()> {
+        match self {
+            GraphFormat::GraphMl => write_graphml(graph, options, out),
+            GraphFormat::Dot => write_dot(graph, options, out),
+        }
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "graphml" => Ok(GraphFormat::GraphMl),
+            "dot" | "gv" => Ok(GraphFormat::Dot),
+            _ => Err(format!("Unknown graph format: {}", s)),
+        }
+    }
+}
+
+/// Write the graph as GraphML, which opens in Gephi and yEd.
+///
+/// Nodes carry `kind` and `label` attributes. Edges carry `kind`, `first_seen`, `last_seen`
+/// and, if `options.weights` is set, `weight`.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::graph::social_graph::{GraphOptions, Node, NodeKind, SocialGraph};
+/// use surrealdb_test::graph::writers::write_graphml;
+///
+/// let mut graph = SocialGraph::new();
+/// graph.nodes.push(Node {
+///     kind: NodeKind::Player,
+///     key: "usr_1234".to_string(),
+///     label: "Tom & Jerry".to_string(),
+/// });
+///
+/// let mut out = Vec::new();
+/// write_graphml(&graph, &GraphOptions::default(), &mut out).unwrap();
+/// let out = String::from_utf8(out).unwrap();
+///
+/// assert!(out.contains(r#"<node id="player:usr_1234">"#));
+/// assert!(out.contains("Tom &amp; Jerry"));
+/// ```
+pub fn write_graphml<W: Write>(
+    graph: &SocialGraph,
+    options: &GraphOptions,
+    mut out: W,
+) -> Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    writeln!(
+        out,
+        r#"  <key id="kind" for="node" attr.name="kind" attr.type="string"/>"#
+    )?;
+    writeln!(
+        out,
+        r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+    )?;
+    writeln!(
+        out,
+        r#"  <key id="edge_kind" for="edge" attr.name="kind" attr.type="string"/>"#
+    )?;
+    writeln!(
+        out,
+        r#"  <key id="first_seen" for="edge" attr.name="first_seen" attr.type="string"/>"#
+    )?;
+    writeln!(
+        out,
+        r#"  <key id="last_seen" for="edge" attr.name="last_seen" attr.type="string"/>"#
+    )?;
+    if options.weights {
+        writeln!(
+            out,
+            r#"  <key id="weight" for="edge" attr.name="weight" attr.type="double"/>"#
+        )?;
+    }
+    writeln!(out, r#"  <graph id="social" edgedefault="directed">"#)?;
+
+    for node in &graph.nodes {
+        writeln!(out, r#"    <node id="{}">"#, xml_escape(&node.id()))?;
+        writeln!(
+            out,
+            r#"      <data key="kind">{}</data>"#,
+            node.kind.table()
+        )?;
+        writeln!(
+            out,
+            r#"      <data key="label">{}</data>"#,
+            xml_escape(&node.label)
+        )?;
+        writeln!(out, "    </node>")?;
+    }
+
+    for (index, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            out,
+            r#"    <edge id="e{}" source="{}" target="{}">"#,
+            index,
+            xml_escape(&edge.from),
+            xml_escape(&edge.to)
+        )?;
+        writeln!(
+            out,
+            r#"      <data key="edge_kind">{}</data>"#,
+            edge.kind.table()
+        )?;
+        if let Some(first_seen) = edge.first_seen {
+            writeln!(
+                out,
+                r#"      <data key="first_seen">{}</data>"#,
+                timestamp(first_seen)
+            )?;
+        }
+        if let Some(last_seen) = edge.last_seen {
+            writeln!(
+                out,
+                r#"      <data key="last_seen">{}</data>"#,
+                timestamp(last_seen)
+            )?;
+        }
+        if let (true, Some(weight)) = (options.weights, edge.weight) {
+            writeln!(out, r#"      <data key="weight">{}</data>"#, weight)?;
+        }
+        writeln!(out, "    </edge>")?;
+    }
+
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")?;
+    out.flush()
+}
+
+/// Write the graph as Graphviz DOT.
+///
+/// Players are ellipses, instances are boxes and worlds are double octagons. `met` edges are
+/// drawn without arrows. If `options.weights` is set, edges get `weight` and `label` attributes.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::graph::social_graph::{Edge, EdgeKind, GraphOptions, SocialGraph};
+/// use surrealdb_test::graph::writers::write_dot;
+///
+/// let mut graph = SocialGraph::new();
+/// graph.edges.push(Edge {
+///     kind: EdgeKind::Met,
+///     from: "player:usr_a".to_string(),
+///     to: "player:usr_b".to_string(),
+///     weight: Some(3),
+///     first_seen: None,
+///     last_seen: None,
+/// });
+///
+/// let mut out = Vec::new();
+/// let options = GraphOptions { weights: true, ..Default::default() };
+/// write_dot(&graph, &options, &mut out).unwrap();
+/// let out = String::from_utf8(out).unwrap();
+///
+/// assert!(out.contains(
+///     r#""player:usr_a" -> "player:usr_b" [kind="met", dir=none, weight=3, label="3"];"#
+/// ));
+/// ```
+pub fn write_dot<W: Write>(graph: &SocialGraph, options: &GraphOptions, mut out: W) -> Result<()> {
+    writeln!(out, "digraph social {{")?;
+
+    for node in &graph.nodes {
+        let shape = match node.kind {
+            NodeKind::Player => "ellipse",
+            NodeKind::Instance => "box",
+            NodeKind::World => "doubleoctagon",
+            NodeKind::Avatar => "hexagon",
+        };
+        writeln!(
+            out,
+            r#"  "{}" [label="{}", kind="{}", shape={}];"#,
+            dot_escape(&node.id()),
+            dot_escape(&node.label),
+            node.kind.table(),
+            shape
+        )?;
+    }
+
+    for edge in &graph.edges {
+        let mut attributes = vec![format!(r#"kind="{}""#, edge.kind.table())];
+        if edge.kind == EdgeKind::Met {
+            attributes.push("dir=none".to_string());
+        }
+        if let (true, Some(weight)) = (options.weights, edge.weight) {
+            attributes.push(format!("weight={}", weight));
+            attributes.push(format!(r#"label="{}""#, weight));
+        }
+        writeln!(
+            out,
+            r#"  "{}" -> "{}" [{}];"#,
+            dot_escape(&edge.from),
+            dot_escape(&edge.to),
+            attributes.join(", ")
+        )?;
+    }
+
+    writeln!(out, "}}")?;
+    out.flush()
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339()
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,190 @@
+use std::future::Future;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use tracing::Instrument;
+
+use crate::repo::insert_strategy::InsertStrategy;
+
+/// How the importer splits rows into batches and writes them.
+///
+/// # Values
+///
+/// - `batch_size` - Rows per batch, at least 1.
+/// - `concurrency` - Batches in flight at once, at least 1.
+/// - `strategy` - How each batch is written, see `InsertStrategy`.
+/// - `retries` - Times a failed batch is retried before it's bisected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct BatchOptions {
+    pub batch_size: usize,
+    pub concurrency: usize,
+    pub strategy: InsertStrategy,
+    pub retries: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            batch_size: 1000,
+            concurrency: 4,
+            strategy: InsertStrategy::Insert,
+            retries: 2,
+        }
+    }
+}
+
+/// A row that could not be written, even on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct FailedRow {
+    /// The index of the row in the slice given to `write_batches`.
+    pub index: usize,
+    pub error: String,
+}
+
+/// The outcome of `write_batches`.
+///
+/// # Values
+///
+/// - `written` - Rows written.
+/// - `batches` - Batches the rows were split into.
+/// - `retries` - Writes beyond the first of each batch: retries, and writes of bisected halves.
+/// - `failed` - Rows that could not be written, even on their own.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct BatchReport {
+    pub written: usize,
+    pub batches: usize,
+    pub retries: usize,
+    pub failed: Vec<FailedRow>,
+}
+
+/// Write `rows` in batches of `options.batch_size`, with up to `options.concurrency` batches
+/// in flight.
+///
+/// # What it does
+///
+/// * A failed batch is retried `options.retries` times, backing off a little more each time.
+/// * If it still fails, it is split in half and each half is written the same way, until the
+///   bad rows are isolated. Those end up in `BatchReport::failed`, everything else is written.
+///
+/// `write` gets one batch and returns how many rows it wrote.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::import::batch::{write_batches, BatchOptions};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let rows = (0..10).collect::<Vec<i32>>();
+/// let options = BatchOptions { batch_size: 4, retries: 0, ..Default::default() };
+///
+/// let report = write_batches(&rows, &options, |batch: &[i32]| async move {
+///     match batch.contains(&7) {
+///         true => Err("bad row"),
+///         false => Ok(batch.len()),
+///     }
+/// })
+/// .await;
+///
+/// assert_eq!(report.written, 9);
+/// assert_eq!(report.batches, 3);
+/// assert!(report.retries > 0);
+/// assert_eq!(report.failed.len(), 1);
+/// assert_eq!(report.failed[0].index, 7);
+/// # });
+/// ```
+pub async fn write_batches<'a, T, F, Fut, E>(
+    rows: &'a [T],
+    options: &BatchOptions,
+    write: F,
+) -> BatchReport
+where
+    F: Fn(&'a [T]) -> Fut,
+    Fut: Future<Output = Result<usize, E>>,
+    E: ToString,
+{
+    let batch_size = options.batch_size.max(1);
+    let write = &write;
+
+    let reports = stream::iter(rows.chunks(batch_size).enumerate())
+        .map(|(index, batch)| {
+            let offset = index * batch_size;
+            write_batch(offset, batch, options.retries, write).instrument(tracing::debug_span!(
+                "write_batch",
+                offset,
+                rows = batch.len()
+            ))
+        })
+        .buffer_unordered(options.concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut report = BatchReport::default();
+    for batch in reports {
+        report.written += batch.written;
+        report.batches += batch.batches;
+        report.retries += batch.retries;
+        report.failed.extend(batch.failed);
+    }
+    report.failed.sort_by_key(|failed| failed.index);
+    report
+}
+
+async fn write_batch<'a, T, F, Fut, E>(
+    offset: usize,
+    batch: &'a [T],
+    retries: usize,
+    write: &F,
+) -> BatchReport
+where
+    F: Fn(&'a [T]) -> Fut,
+    Fut: Future<Output = Result<usize, E>>,
+    E: ToString,
+{
+    let mut report = BatchReport {
+        batches: 1,
+        ..Default::default()
+    };
+    let mut writes = 0;
+    let mut pending = vec![(offset, batch)];
+
+    while let Some((offset, batch)) = pending.pop() {
+        let mut attempt = 0;
+        let result = loop {
+            writes += 1;
+            match write(batch).await {
+                Err(error) if attempt < retries => {
+                    attempt += 1;
+                    tracing::warn!(
+                        offset,
+                        rows = batch.len(),
+                        attempt,
+                        error = %error.to_string(),
+                        "batch failed, retrying"
+                    );
+                    tokio::time::sleep(Duration::from_millis(50 * attempt as u64)).await;
+                }
+                result => break result,
+            }
+        };
+
+        match result {
+            Ok(written) => report.written += written,
+            Err(error) if batch.len() == 1 => {
+                tracing::error!(index = offset, error = %error.to_string(), "row failed");
+                report.failed.push(FailedRow {
+                    index: offset,
+                    error: error.to_string(),
+                })
+            }
+            Err(_) => {
+                tracing::debug!(offset, rows = batch.len(), "bisecting failed batch");
+                let (left, right) = batch.split_at(batch.len() / 2);
+                pending.push((offset + left.len(), right));
+                pending.push((offset, left));
+            }
+        }
+    }
+
+    report.retries = writes - 1;
+    report
+}
@@ -0,0 +1,747 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+use chrono::Utc;
+use surrealdb::{Connection, Surreal};
+
+use crate::import::batch::{write_batches, BatchOptions, BatchReport, FailedRow};
+use crate::import::merge::{merge, MergeReport, Mergeable};
+use crate::import::sqlite::VrcxSqlite;
+use crate::measure_time;
+use crate::models::avatar::Avatar;
+use crate::models::avatar_history::AvatarHistory;
+use crate::models::feed_avatar::FeedAvatar;
+use crate::models::feed_bio::FeedBio;
+use crate::models::feed_gps::FeedGps;
+use crate::models::feed_online_offline::FeedOnlineOffline;
+use crate::models::feed_status::FeedStatus;
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::gamelog_location::GamelogLocation;
+use crate::models::moderation::Moderation;
+use crate::models::notification::Notification;
+use crate::models::source::Source;
+use crate::models::tenant::account_prefix;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::models::world::World;
+use crate::repo::avatar::AvatarRepo;
+use crate::repo::avatar_history::AvatarHistoryRepo;
+use crate::repo::feed::{
+    FeedAvatarRepo, FeedBioRepo, FeedGpsRepo, FeedOnlineOfflineRepo, FeedStatusRepo,
+};
+use crate::repo::friend::FriendRepo;
+use crate::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+use crate::repo::gamelog_location::GamelogLocationRepo;
+use crate::repo::insert_strategy::BatchRepo;
+use crate::repo::moderation::ModerationRepo;
+use crate::repo::notification::NotificationRepo;
+use crate::repo::world::WorldRepo;
+use crate::zaphkiel::timestamp::{SourceTimezone, TimestampParseError};
+
+/// The outcome of importing one table.
+///
+/// `read` counts every row read from SQLite, including the `rejected` ones that were never
+/// written and the duplicates dropped by a `merge`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct TableReport {
+    pub table: String,
+    pub read: usize,
+    pub rejected: Vec<RejectedRow>,
+    pub merge: MergeReport,
+    pub batch: BatchReport,
+}
+
+/// A row that was read but not imported, because one of its values could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct RejectedRow {
+    pub id: i64,
+    pub error: String,
+}
+
+/// Every imported table of one or more `vrcx.sqlite` files, parsed into models.
+///
+/// `worlds` and `avatars` are the `cache_world` and `cache_avatar` tables. `feed_avatar`,
+/// `avatar_history`, `feed_status`, `feed_bio`, `feed_online_offline`, `feed_gps`,
+/// `moderation` and `notifications` are the per-account tables of every account.
+/// `moderation_sources` are the `_moderation` tables that were read, even the empty ones. The
+/// `rejected_*` fields are the rows whose `created_at` could not be parsed. `_avatar_history`
+/// and `_notifications` have no integer id, so their rejected rows are numbered by position
+/// instead.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VrcxTables {
+    pub locations: Vec<GamelogLocation>,
+    pub join_leave: Vec<GamelogJoinLeave>,
+    pub friends: Vec<UsrFriendLogCurrent>,
+    pub worlds: Vec<World>,
+    pub avatars: Vec<Avatar>,
+    pub feed_avatar: Vec<FeedAvatar>,
+    pub avatar_history: Vec<AvatarHistory>,
+    pub feed_status: Vec<FeedStatus>,
+    pub feed_bio: Vec<FeedBio>,
+    pub feed_online_offline: Vec<FeedOnlineOffline>,
+    pub feed_gps: Vec<FeedGps>,
+    pub moderation: Vec<Moderation>,
+    pub moderation_sources: Vec<Source>,
+    pub notifications: Vec<Notification>,
+    pub rejected_locations: Vec<RejectedRow>,
+    pub rejected_join_leave: Vec<RejectedRow>,
+    pub rejected_feed_avatar: Vec<RejectedRow>,
+    pub rejected_avatar_history: Vec<RejectedRow>,
+    pub rejected_feed_status: Vec<RejectedRow>,
+    pub rejected_feed_bio: Vec<RejectedRow>,
+    pub rejected_feed_online_offline: Vec<RejectedRow>,
+    pub rejected_feed_gps: Vec<RejectedRow>,
+    pub rejected_notifications: Vec<RejectedRow>,
+}
+
+impl VrcxTables {
+    /// Read `path`, reading timestamps without an offset as local time in `timezone`.
+    ///
+    /// Every record is tagged with `path` as its `Source`. Friends, the avatar history and the
+    /// feeds get the account of their table. The shared game log and cache tables get the
+    /// file's account if it has exactly one.
+    ///
+    /// Every record is keyed by its natural key, see `Mergeable::rekey`, so importing a file on
+    /// its own writes the same records as merging it with others.
+    pub fn read(path: impl AsRef<Path>, timezone: SourceTimezone) -> Result<Self, Box<dyn Error>> {
+        let file = path.as_ref().display().to_string();
+        let sqlite = VrcxSqlite::open(path)?;
+        let accounts = sqlite.accounts()?;
+        let source = Source {
+            file: file.clone(),
+            account: match accounts.as_slice() {
+                [account] => Some(account.clone()),
+                _ => None,
+            },
+        };
+
+        let (locations, rejected_locations) = measure_time!("reading gamelog_location" =>
+            parse_rows(sqlite.gamelog_location()?, |row| {
+                (row.id, GamelogLocation::from_row(row, timezone))
+            })
+        );
+        let (join_leave, rejected_join_leave) = measure_time!("reading gamelog_join_leave" =>
+            parse_rows(sqlite.gamelog_join_leave()?, |row| {
+                (row.id, GamelogJoinLeave::from_row(row, timezone))
+            })
+        );
+        let locations = tag(locations, &source);
+        let join_leave = tag(join_leave, &source);
+        let worlds = measure_time!("reading cache_world" =>
+            tag(sqlite.cache_world()?.into_iter().map(|row| World::from_row(row, timezone)), &source)
+        );
+        let avatars = measure_time!("reading cache_avatar" =>
+            tag(sqlite.cache_avatar()?.into_iter().map(|row| Avatar::from_row(row, timezone)), &source)
+        );
+
+        let mut friends = Vec::new();
+        let mut feed_avatar = Vec::new();
+        let mut avatar_history = Vec::new();
+        let mut rejected_feed_avatar = Vec::new();
+        let mut rejected_avatar_history = Vec::new();
+        let mut feed_status = Vec::new();
+        let mut feed_bio = Vec::new();
+        let mut feed_online_offline = Vec::new();
+        let mut rejected_feed_status = Vec::new();
+        let mut rejected_feed_bio = Vec::new();
+        let mut rejected_feed_online_offline = Vec::new();
+        let mut feed_gps = Vec::new();
+        let mut rejected_feed_gps = Vec::new();
+        let mut moderation = Vec::new();
+        let mut moderation_sources = Vec::new();
+        let mut notifications = Vec::new();
+        let mut rejected_notifications = Vec::new();
+        for account in accounts {
+            let table = format!("{}_friend_log_current", account);
+            let source = Source {
+                file: file.clone(),
+                account: Some(account.clone()),
+            };
+            friends.extend(tag(
+                sqlite
+                    .friend_log_current(&table)?
+                    .into_iter()
+                    .map(UsrFriendLogCurrent::from),
+                &source,
+            ));
+
+            let table = format!("{}_feed_avatar", account);
+            if sqlite.has_table(&table)? {
+                let (rows, rejected) = parse_rows(sqlite.feed_avatar(&table)?, |row| {
+                    (row.id, FeedAvatar::from_row(row, timezone))
+                });
+                feed_avatar.extend(tag(rows, &source));
+                rejected_feed_avatar.extend(rejected);
+            }
+
+            let table = format!("{}_avatar_history", account);
+            if sqlite.has_table(&table)? {
+                let rows = sqlite.avatar_history(&table)?.into_iter().enumerate();
+                let (rows, rejected) = parse_rows(rows, |(index, row)| {
+                    (index as i64 + 1, AvatarHistory::from_row(row, timezone))
+                });
+                avatar_history.extend(tag(rows, &source));
+                rejected_avatar_history.extend(rejected);
+            }
+
+            let table = format!("{}_feed_status", account);
+            if sqlite.has_table(&table)? {
+                let (rows, rejected) = parse_rows(sqlite.feed_status(&table)?, |row| {
+                    (row.id, FeedStatus::from_row(row, timezone))
+                });
+                feed_status.extend(tag(rows, &source));
+                rejected_feed_status.extend(rejected);
+            }
+
+            let table = format!("{}_feed_bio", account);
+            if sqlite.has_table(&table)? {
+                let (rows, rejected) = parse_rows(sqlite.feed_bio(&table)?, |row| {
+                    (row.id, FeedBio::from_row(row, timezone))
+                });
+                feed_bio.extend(tag(rows, &source));
+                rejected_feed_bio.extend(rejected);
+            }
+
+            let table = format!("{}_feed_online_offline", account);
+            if sqlite.has_table(&table)? {
+                let (rows, rejected) = parse_rows(sqlite.feed_online_offline(&table)?, |row| {
+                    (row.id, FeedOnlineOffline::from_row(row, timezone))
+                });
+                feed_online_offline.extend(tag(rows, &source));
+                rejected_feed_online_offline.extend(rejected);
+            }
+
+            let table = format!("{}_feed_gps", account);
+            if sqlite.has_table(&table)? {
+                let (rows, rejected) = parse_rows(sqlite.feed_gps(&table)?, |row| {
+                    (row.id, FeedGps::from_row(row, timezone))
+                });
+                feed_gps.extend(tag(rows, &source));
+                rejected_feed_gps.extend(rejected);
+            }
+
+            let table = format!("{}_moderation", account);
+            if sqlite.has_table(&table)? {
+                moderation.extend(tag(
+                    sqlite
+                        .moderation(&table)?
+                        .into_iter()
+                        .map(|row| Moderation::from_row(row, timezone)),
+                    &source,
+                ));
+                moderation_sources.push(source.clone());
+            }
+
+            let table = format!("{}_notifications", account);
+            if sqlite.has_table(&table)? {
+                let rows = sqlite.notifications(&table)?.into_iter().enumerate();
+                let (rows, rejected) = parse_rows(rows, |(index, row)| {
+                    (index as i64 + 1, Notification::from_row(row, timezone))
+                });
+                notifications.extend(tag(rows, &source));
+                rejected_notifications.extend(rejected);
+            }
+        }
+
+        Ok(VrcxTables {
+            locations,
+            join_leave,
+            friends,
+            worlds,
+            avatars,
+            feed_avatar,
+            avatar_history,
+            feed_status,
+            feed_bio,
+            feed_online_offline,
+            feed_gps,
+            moderation,
+            moderation_sources,
+            notifications,
+            rejected_locations,
+            rejected_join_leave,
+            rejected_feed_avatar,
+            rejected_avatar_history,
+            rejected_feed_status,
+            rejected_feed_bio,
+            rejected_feed_online_offline,
+            rejected_feed_gps,
+            rejected_notifications,
+        })
+    }
+
+    /// Append every table of `other`.
+    pub fn extend(&mut self, other: VrcxTables) {
+        self.locations.extend(other.locations);
+        self.join_leave.extend(other.join_leave);
+        self.friends.extend(other.friends);
+        self.worlds.extend(other.worlds);
+        self.avatars.extend(other.avatars);
+        self.feed_avatar.extend(other.feed_avatar);
+        self.avatar_history.extend(other.avatar_history);
+        self.rejected_locations.extend(other.rejected_locations);
+        self.rejected_join_leave.extend(other.rejected_join_leave);
+        self.rejected_feed_avatar.extend(other.rejected_feed_avatar);
+        self.feed_status.extend(other.feed_status);
+        self.feed_bio.extend(other.feed_bio);
+        self.feed_online_offline.extend(other.feed_online_offline);
+        self.feed_gps.extend(other.feed_gps);
+        self.moderation.extend(other.moderation);
+        self.moderation_sources.extend(other.moderation_sources);
+        self.notifications.extend(other.notifications);
+        self.rejected_avatar_history
+            .extend(other.rejected_avatar_history);
+        self.rejected_feed_status.extend(other.rejected_feed_status);
+        self.rejected_feed_bio.extend(other.rejected_feed_bio);
+        self.rejected_feed_online_offline
+            .extend(other.rejected_feed_online_offline);
+        self.rejected_feed_gps.extend(other.rejected_feed_gps);
+        self.rejected_notifications
+            .extend(other.rejected_notifications);
+    }
+
+    /// Merge every table by its natural key, see `merge`.
+    ///
+    /// Returns the merged tables and the `MergeReport` of every table.
+    pub fn merge(self) -> (Self, Vec<MergeReport>) {
+        let (locations, merged_locations) = merge(self.locations);
+        let (join_leave, merged_join_leave) = merge(self.join_leave);
+        let (friends, merged_friends) = merge(self.friends);
+        let (worlds, merged_worlds) = merge(self.worlds);
+        let (avatars, merged_avatars) = merge(self.avatars);
+        let (feed_avatar, merged_feed_avatar) = merge(self.feed_avatar);
+        let (avatar_history, merged_avatar_history) = merge(self.avatar_history);
+        let (feed_status, merged_feed_status) = merge(self.feed_status);
+        let (feed_bio, merged_feed_bio) = merge(self.feed_bio);
+        let (feed_online_offline, merged_feed_online_offline) = merge(self.feed_online_offline);
+        let (feed_gps, merged_feed_gps) = merge(self.feed_gps);
+        let (moderation, merged_moderation) = merge(self.moderation);
+        let (notifications, merged_notifications) = merge(self.notifications);
+        (
+            VrcxTables {
+                locations,
+                join_leave,
+                friends,
+                worlds,
+                avatars,
+                feed_avatar,
+                avatar_history,
+                feed_status,
+                feed_bio,
+                feed_online_offline,
+                feed_gps,
+                moderation,
+                notifications,
+                ..self
+            },
+            vec![
+                merged_locations,
+                merged_join_leave,
+                merged_friends,
+                merged_worlds,
+                merged_avatars,
+                merged_feed_avatar,
+                merged_avatar_history,
+                merged_feed_status,
+                merged_feed_bio,
+                merged_feed_online_offline,
+                merged_feed_gps,
+                merged_moderation,
+                merged_notifications,
+            ],
+        )
+    }
+
+    /// Split the records by the accounts of their `sources`.
+    ///
+    /// A record merged from several accounts is under each of them. Records with a source
+    /// without an account, such as the game log of a file with several accounts, and the rejected
+    /// rows are shared: they are copied under every account, so they land in the same database as
+    /// the rest of the account's records, and are only under `None` if there is no account at
+    /// all.
+    pub fn split_by_account(self) -> BTreeMap<Option<String>, VrcxTables> {
+        let mut split = BTreeMap::<_, VrcxTables>::new();
+        split_rows(&mut split, self.locations, |tables| &mut tables.locations);
+        split_rows(&mut split, self.join_leave, |tables| &mut tables.join_leave);
+        split_rows(&mut split, self.friends, |tables| &mut tables.friends);
+        split_rows(&mut split, self.worlds, |tables| &mut tables.worlds);
+        split_rows(&mut split, self.avatars, |tables| &mut tables.avatars);
+        split_rows(&mut split, self.feed_avatar, |tables| {
+            &mut tables.feed_avatar
+        });
+        split_rows(&mut split, self.avatar_history, |tables| {
+            &mut tables.avatar_history
+        });
+        split_rows(&mut split, self.feed_status, |tables| {
+            &mut tables.feed_status
+        });
+        split_rows(&mut split, self.feed_bio, |tables| &mut tables.feed_bio);
+        split_rows(&mut split, self.feed_online_offline, |tables| {
+            &mut tables.feed_online_offline
+        });
+        split_rows(&mut split, self.feed_gps, |tables| &mut tables.feed_gps);
+        split_rows(&mut split, self.moderation, |tables| &mut tables.moderation);
+        for source in self.moderation_sources {
+            split
+                .entry(source.account.clone())
+                .or_default()
+                .moderation_sources
+                .push(source);
+        }
+        split_rows(&mut split, self.notifications, |tables| {
+            &mut tables.notifications
+        });
+        if !self.rejected_locations.is_empty()
+            || !self.rejected_join_leave.is_empty()
+            || !self.rejected_feed_avatar.is_empty()
+            || !self.rejected_avatar_history.is_empty()
+            || !self.rejected_feed_status.is_empty()
+            || !self.rejected_feed_bio.is_empty()
+            || !self.rejected_feed_online_offline.is_empty()
+            || !self.rejected_feed_gps.is_empty()
+            || !self.rejected_notifications.is_empty()
+        {
+            let untagged = split.entry(None).or_default();
+            untagged.rejected_locations = self.rejected_locations;
+            untagged.rejected_join_leave = self.rejected_join_leave;
+            untagged.rejected_feed_avatar = self.rejected_feed_avatar;
+            untagged.rejected_avatar_history = self.rejected_avatar_history;
+            untagged.rejected_feed_status = self.rejected_feed_status;
+            untagged.rejected_feed_bio = self.rejected_feed_bio;
+            untagged.rejected_feed_online_offline = self.rejected_feed_online_offline;
+            untagged.rejected_feed_gps = self.rejected_feed_gps;
+            untagged.rejected_notifications = self.rejected_notifications;
+        }
+        if split.keys().any(Option::is_some) {
+            if let Some(shared) = split.remove(&None) {
+                for tables in split.values_mut() {
+                    tables.extend(shared.clone());
+                }
+            }
+        }
+        split
+    }
+
+    /// Keep the records of `account`, given as VRCX prefixes its tables, and the records
+    /// without an account, which are taken to be its own.
+    pub fn for_account(self, account: &str) -> Self {
+        let mine = |sources: &[Source]| {
+            sources.is_empty()
+                || sources.iter().any(|source| match &source.account {
+                    Some(other) => other == account,
+                    None => true,
+                })
+        };
+        VrcxTables {
+            locations: keep(self.locations, mine),
+            join_leave: keep(self.join_leave, mine),
+            friends: keep(self.friends, mine),
+            worlds: keep(self.worlds, mine),
+            avatars: keep(self.avatars, mine),
+            feed_avatar: keep(self.feed_avatar, mine),
+            avatar_history: keep(self.avatar_history, mine),
+            feed_status: keep(self.feed_status, mine),
+            feed_bio: keep(self.feed_bio, mine),
+            feed_online_offline: keep(self.feed_online_offline, mine),
+            feed_gps: keep(self.feed_gps, mine),
+            moderation: keep(self.moderation, mine),
+            moderation_sources: self
+                .moderation_sources
+                .into_iter()
+                .filter(|source| mine(std::slice::from_ref(source)))
+                .collect(),
+            notifications: keep(self.notifications, mine),
+            ..self
+        }
+    }
+
+    /// The user id of `account`, given either as a user id or as VRCX prefixes its tables, if
+    /// the account shows up in `join_leave`.
+    pub fn user_id(&self, account: &str) -> Option<String> {
+        let prefix = account_prefix(account);
+        self.join_leave
+            .iter()
+            .filter_map(|row| row.user_id.as_deref())
+            .find(|user_id| account_prefix(user_id) == prefix)
+            .map(str::to_string)
+    }
+}
+
+/// Tag every row with `source` and key it by its natural key.
+fn tag<T: Mergeable>(rows: impl IntoIterator<Item = T>, source: &Source) -> Vec<T> {
+    rows.into_iter()
+        .map(|mut row| {
+            row.rekey();
+            *row.sources_mut() = vec![source.clone()];
+            row
+        })
+        .collect()
+}
+
+/// Add every row to the tables of each account among its `sources`, or to `None` if one of them
+/// has no account.
+fn split_rows<T: Mergeable>(
+    split: &mut BTreeMap<Option<String>, VrcxTables>,
+    rows: Vec<T>,
+    table: impl Fn(&mut VrcxTables) -> &mut Vec<T>,
+) {
+    for row in rows {
+        let mut accounts = row
+            .sources()
+            .iter()
+            .map(|source| source.account.clone())
+            .collect::<Vec<_>>();
+        accounts.sort();
+        accounts.dedup();
+        if accounts.is_empty() || accounts.contains(&None) {
+            accounts = vec![None];
+        }
+        for account in accounts {
+            table(split.entry(account).or_default()).push(row.clone());
+        }
+    }
+}
+
+/// The rows whose `sources` are `mine`.
+fn keep<T: Mergeable>(rows: Vec<T>, mine: impl Fn(&[Source]) -> bool) -> Vec<T> {
+    rows.into_iter().filter(|row| mine(row.sources())).collect()
+}
+
+/// Imports a VRCX `vrcx.sqlite` file into SurrealDB in batches.
+pub struct Importer<C: Connection> {
+    db: Surreal<C>,
+    options: BatchOptions,
+    timezone: SourceTimezone,
+}
+
+impl<C: Connection> Importer<C> {
+    /// Create a new `Importer` writing to `db`.
+    pub fn new(db: Surreal<C>, options: BatchOptions) -> Self {
+        Importer {
+            db,
+            options,
+            timezone: SourceTimezone::default(),
+        }
+    }
+
+    /// Read timestamps without an offset as local time in `timezone`.
+    pub fn timezone(mut self, timezone: SourceTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Import `gamelog_location`, `gamelog_join_leave`, the world and avatar caches and every
+    /// friend log, avatar history, feed, moderation and notifications table from `path`.
+    #[tracing::instrument(skip_all, fields(path = %path.as_ref().display(), timezone = %self.timezone))]
+    pub async fn import(&self, path: impl AsRef<Path>) -> Result<Vec<TableReport>, Box<dyn Error>> {
+        let tables = VrcxTables::read(path, self.timezone)?;
+        Ok(self.write(tables).await)
+    }
+
+    /// Write every table of `tables`.
+    pub async fn write(&self, tables: VrcxTables) -> Vec<TableReport> {
+        vec![
+            with_rejected(
+                self.import_table(GamelogLocationRepo::new(self.db.clone()), &tables.locations)
+                    .await,
+                tables.rejected_locations,
+            ),
+            with_rejected(
+                self.import_table(
+                    GamelogJoinLeaveRepo::new(self.db.clone()),
+                    &tables.join_leave,
+                )
+                .await,
+                tables.rejected_join_leave,
+            ),
+            self.import_table(FriendRepo::new(self.db.clone()), &tables.friends)
+                .await,
+            self.import_table(WorldRepo::new(self.db.clone()), &tables.worlds)
+                .await,
+            self.import_table(AvatarRepo::new(self.db.clone()), &tables.avatars)
+                .await,
+            with_rejected(
+                self.import_table(FeedAvatarRepo::new(self.db.clone()), &tables.feed_avatar)
+                    .await,
+                tables.rejected_feed_avatar,
+            ),
+            with_rejected(
+                self.import_table(
+                    AvatarHistoryRepo::new(self.db.clone()),
+                    &tables.avatar_history,
+                )
+                .await,
+                tables.rejected_avatar_history,
+            ),
+            with_rejected(
+                self.import_table(FeedStatusRepo::new(self.db.clone()), &tables.feed_status)
+                    .await,
+                tables.rejected_feed_status,
+            ),
+            with_rejected(
+                self.import_table(FeedBioRepo::new(self.db.clone()), &tables.feed_bio)
+                    .await,
+                tables.rejected_feed_bio,
+            ),
+            with_rejected(
+                self.import_table(
+                    FeedOnlineOfflineRepo::new(self.db.clone()),
+                    &tables.feed_online_offline,
+                )
+                .await,
+                tables.rejected_feed_online_offline,
+            ),
+            with_rejected(
+                self.import_table(FeedGpsRepo::new(self.db.clone()), &tables.feed_gps)
+                    .await,
+                tables.rejected_feed_gps,
+            ),
+            self.import_moderation(&tables.moderation, &tables.moderation_sources)
+                .await,
+            with_rejected(
+                self.import_table(
+                    NotificationRepo::new(self.db.clone()),
+                    &tables.notifications,
+                )
+                .await,
+                tables.rejected_notifications,
+            ),
+        ]
+    }
+
+    /// Import several `vrcx.sqlite` files as one history.
+    ///
+    /// # What it does
+    ///
+    /// * Every file is read as by `import`, and its records tagged with their `Source`.
+    /// * Rows describing the same event are merged by their natural key, see `Mergeable`, and
+    ///   keep the `sources` of every file they were read from.
+    /// * Rows are keyed by a hash of their natural key, as by `import`, so merging the same
+    ///   event again always writes the same record. Use `InsertStrategy::Upsert` to merge into
+    ///   a namespace that already holds some of them.
+    #[tracing::instrument(skip_all, fields(files = paths.len(), timezone = %self.timezone))]
+    pub async fn merge(
+        &self,
+        paths: &[impl AsRef<Path>],
+    ) -> Result<Vec<TableReport>, Box<dyn Error>> {
+        let mut tables = VrcxTables::default();
+        for path in paths {
+            tables.extend(VrcxTables::read(path, self.timezone)?);
+        }
+
+        let (tables, mut merged) = tables.merge();
+        Ok(self
+            .write(tables)
+            .await
+            .into_iter()
+            .map(|report| {
+                match merged
+                    .iter()
+                    .position(|merged| merged.table == report.table)
+                {
+                    Some(i) => with_merge(report, merged.swap_remove(i)),
+                    None => report,
+                }
+            })
+            .collect())
+    }
+
+    /// Write `rows` to the table of `repo`, in batches as set by the `BatchOptions`.
+    #[tracing::instrument(skip_all, fields(table = R::TABLE, rows = rows.len()))]
+    pub async fn import_table<R: BatchRepo<C>>(&self, repo: R, rows: &[R::Row]) -> TableReport {
+        let strategy = self.options.strategy;
+        let batch = measure_time!(
+            format!("importing {}", R::TABLE),
+            rows = |report: &BatchReport| report.written =>
+            write_batches(rows, &self.options, |batch| repo.insert_batch(batch, strategy)).await
+        );
+        report(R::TABLE, rows.len(), batch)
+    }
+
+    /// Sync `rows`, read from the `_moderation` tables of `sources`, into `moderation`, see
+    /// `ModerationRepo::sync`.
+    ///
+    /// The list is written as a whole, so if that fails every row is reported failed. Without
+    /// `sources` the file had no `_moderation` table, and nothing is synced, since syncing it
+    /// would unblock everyone. An empty table is synced, since the last player was unblocked.
+    #[tracing::instrument(skip_all, fields(table = "moderation", rows = rows.len()))]
+    pub async fn import_moderation(&self, rows: &[Moderation], sources: &[Source]) -> TableReport {
+        let repo = ModerationRepo::new(self.db.clone());
+        let batch = measure_time!(
+            "importing moderation",
+            rows = |report: &BatchReport| report.written =>
+            match sources {
+                [] => BatchReport::default(),
+                _ => match repo.sync(rows, Utc::now()).await {
+                    Ok(events) => {
+                        tracing::info!(changes = events.len(), "moderation synced");
+                        BatchReport {
+                            written: rows.len(),
+                            batches: 1,
+                            retries: 0,
+                            failed: Vec::new(),
+                        }
+                    }
+                    Err(error) => BatchReport {
+                        written: 0,
+                        batches: 1,
+                        retries: 0,
+                        failed: (0..rows.len())
+                            .map(|index| FailedRow {
+                                index,
+                                error: error.to_string(),
+                            })
+                            .collect(),
+                    },
+                },
+            }
+        );
+        report(ModerationRepo::<C>::TABLE, rows.len(), batch)
+    }
+}
+
+fn report(table: &str, read: usize, batch: BatchReport) -> TableReport {
+    TableReport {
+        table: table.to_string(),
+        read,
+        rejected: Vec::new(),
+        merge: MergeReport::default(),
+        batch,
+    }
+}
+
+/// Add the rows rejected while parsing to a report of the rows that were written.
+fn with_rejected(mut report: TableReport, rejected: Vec<RejectedRow>) -> TableReport {
+    report.read += rejected.len();
+    report.rejected = rejected;
+    report
+}
+
+/// Add the duplicates dropped by a merge to a report of the rows that were written.
+fn with_merge(mut report: TableReport, merge: MergeReport) -> TableReport {
+    report.read += merge.duplicates;
+    report.merge = merge;
+    report
+}
+
+/// Convert every row with `parse`, setting aside the ones that fail with their row id.
+fn parse_rows<R, T>(
+    rows: impl IntoIterator<Item = R>,
+    parse: impl Fn(R) -> (i64, Result<T, TimestampParseError>),
+) -> (Vec<T>, Vec<RejectedRow>) {
+    let mut parsed = Vec::new();
+    let mut rejected = Vec::new();
+    for row in rows {
+        match parse(row) {
+            (_, Ok(row)) => parsed.push(row),
+            (id, Err(error)) => {
+                tracing::warn!(id, %error, "rejected row");
+                rejected.push(RejectedRow {
+                    id,
+                    error: format!("created_at: {}", error),
+                });
+            }
+        }
+    }
+    (parsed, rejected)
+}
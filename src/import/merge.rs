@@ -0,0 +1,691 @@
+use std::cmp::Reverse;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::avatar::Avatar;
+use crate::models::avatar_history::AvatarHistory;
+use crate::models::feed_avatar::FeedAvatar;
+use crate::models::feed_bio::FeedBio;
+use crate::models::feed_gps::FeedGps;
+use crate::models::feed_online_offline::FeedOnlineOffline;
+use crate::models::feed_status::FeedStatus;
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::gamelog_location::GamelogLocation;
+use crate::models::moderation::Moderation;
+use crate::models::notification::Notification;
+use crate::models::source::Source;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::models::world::World;
+use crate::zaphkiel::location::Location;
+use crate::zaphkiel::release_status::ReleaseStatus;
+use crate::zaphkiel::trust_level::TrustLevel;
+
+/// A model whose rows can be merged across `vrcx.sqlite` files.
+///
+/// # Natural keys
+///
+/// The keys are the `unique` constraints in `sql_schema`, with `created_at` compared after it is
+/// parsed to UTC:
+///
+/// - `gamelog_location`: `(created_at, location)`.
+/// - `gamelog_join_leave`: `(created_at, type, display_name)`.
+/// - `_friend_log_current`: `user_id`.
+/// - `cache_world` and `cache_avatar`: `id`.
+/// - `_feed_avatar`: `(created_at, user_id)`.
+/// - `_avatar_history`: `(created_at, avatar_id)`, so every time an avatar was worn is kept.
+/// - `_feed_status` and `_feed_bio`: `(created_at, user_id)`.
+/// - `_feed_online_offline`: `(created_at, user_id, type)`.
+/// - `_feed_gps`: `(created_at, user_id)`.
+/// - `_moderation`: `user_id`.
+/// - `_notifications`: `id`, the VRChat notification id.
+pub trait Mergeable: Clone + PartialEq {
+    /// The SurrealDB table the rows are written to, the same as their repository's `TABLE`.
+    const TABLE: &'static str;
+
+    type Key: Ord;
+
+    /// The key rows describing the same event share.
+    fn natural_key(&self) -> Self::Key;
+
+    /// How many optional values are filled in. The most complete duplicate wins.
+    fn completeness(&self) -> usize;
+
+    /// Every file and account the row was read from, lowest first.
+    fn sources(&self) -> &[Source];
+
+    fn sources_mut(&mut self) -> &mut Vec<Source>;
+
+    /// Set the record id from the natural key, so every merge writes the event to the same
+    /// record.
+    fn rekey(&mut self);
+}
+
+/// What `merge` did to one table.
+///
+/// # Values
+///
+/// - `table` - The SurrealDB table, see `Mergeable::TABLE`.
+/// - `duplicates` - Rows dropped because another row had the same natural key.
+/// - `conflicts` - Of those, the ones that differed from the row that was kept in more than
+///   their `sources`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct MergeReport {
+    pub table: String,
+    pub duplicates: usize,
+    pub conflicts: usize,
+}
+
+/// Deduplicate `rows` by their natural key.
+///
+/// # What it does
+///
+/// * Of the rows sharing a natural key, the one with the highest `completeness` is kept. Ties
+///   go to the lowest `Source`, so the result doesn't depend on the order the files were given.
+/// * The row kept gets the `sources` of every row sharing its key.
+/// * Every row kept is `rekey`ed.
+/// * The rows come back in natural key order.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::import::merge::merge;
+/// use surrealdb_test::models::source::Source;
+/// use surrealdb_test::models::usr_friend_log_current::UsrFriendLogCurrent;
+///
+/// let friend = |file: &str, display_name: &str| UsrFriendLogCurrent {
+///     user_id: "usr_1234".to_string(),
+///     display_name: display_name.to_string(),
+///     sources: vec![Source {
+///         file: file.to_string(),
+///         account: None,
+///     }],
+///     ..Default::default()
+/// };
+///
+/// let (friends, report) = merge(vec![friend("b.sqlite", "new"), friend("a.sqlite", "old")]);
+/// assert_eq!(friends.len(), 1);
+/// assert_eq!(friends[0].display_name, "old");
+/// assert_eq!(friends[0].sources.len(), 2);
+/// assert_eq!(report.table, "friend_log_current");
+/// assert_eq!((report.duplicates, report.conflicts), (1, 1));
+/// ```
+pub fn merge<T: Mergeable>(rows: impl IntoIterator<Item = T>) -> (Vec<T>, MergeReport) {
+    let mut merged = BTreeMap::new();
+    let mut report = MergeReport {
+        table: T::TABLE.to_string(),
+        ..Default::default()
+    };
+
+    for mut row in rows {
+        row.rekey();
+        match merged.entry(row.natural_key()) {
+            Entry::Vacant(entry) => {
+                let sources = row.sources().to_vec();
+                entry.insert((row, sources));
+            }
+            Entry::Occupied(mut entry) => {
+                let (kept, sources) = entry.get_mut();
+                report.duplicates += 1;
+                if !same_event(kept, &row) {
+                    report.conflicts += 1;
+                }
+                sources.extend_from_slice(row.sources());
+                if rank(&row) > rank(kept) {
+                    *kept = row;
+                }
+            }
+        }
+    }
+
+    let merged = merged
+        .into_values()
+        .map(|(mut row, mut sources)| {
+            sources.sort();
+            sources.dedup();
+            *row.sources_mut() = sources;
+            row
+        })
+        .collect();
+    (merged, report)
+}
+
+fn rank<T: Mergeable>(row: &T) -> (usize, Reverse<Option<&Source>>) {
+    (row.completeness(), Reverse(row.sources().iter().min()))
+}
+
+fn same_event<T: Mergeable>(a: &T, b: &T) -> bool {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    a.sources_mut().clear();
+    b.sources_mut().clear();
+    a == b
+}
+
+/// A record id for a natural key: the 64-bit FNV-1a hash of its parts, kept positive.
+pub(crate) fn stable_id(parts: &[&str]) -> i64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain([0x1f]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    (hash & i64::MAX as u64) as i64
+}
+
+impl Mergeable for GamelogLocation {
+    const TABLE: &'static str = "gamelog_locations";
+    type Key = (DateTime<Utc>, String);
+
+    fn natural_key(&self) -> Self::Key {
+        (self.created_at, self.world_instance.to_string())
+    }
+
+    fn completeness(&self) -> usize {
+        [
+            !self.world_name.is_empty(),
+            self.time.is_some(),
+            self.group_name.is_some(),
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count()
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    fn rekey(&mut self) {
+        let (created_at, location) = self.natural_key();
+        self.id = stable_id(&[&created_at.to_rfc3339(), &location]);
+    }
+}
+
+impl Mergeable for GamelogJoinLeave {
+    const TABLE: &'static str = "gamelog_join_leave";
+    type Key = (DateTime<Utc>, String, String);
+
+    fn natural_key(&self) -> Self::Key {
+        (
+            self.created_at,
+            self.event.to_string(),
+            self.display_name.clone(),
+        )
+    }
+
+    fn completeness(&self) -> usize {
+        [
+            self.user_id.is_some(),
+            self.time.is_some(),
+            self.location != Location::Unknown,
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count()
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    fn rekey(&mut self) {
+        let (created_at, event, display_name) = self.natural_key();
+        self.id = stable_id(&[&created_at.to_rfc3339(), &event, &display_name]);
+    }
+}
+
+impl Mergeable for UsrFriendLogCurrent {
+    const TABLE: &'static str = "friend_log_current";
+    type Key = String;
+
+    fn natural_key(&self) -> Self::Key {
+        self.user_id.clone()
+    }
+
+    fn completeness(&self) -> usize {
+        [
+            !self.display_name.is_empty(),
+            self.trust_level != TrustLevel::Unknown,
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count()
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    /// Friends are already keyed by `user_id`.
+    fn rekey(&mut self) {}
+}
+
+impl Mergeable for World {
+    const TABLE: &'static str = "world";
+    type Key = String;
+
+    fn natural_key(&self) -> Self::Key {
+        self.id.clone()
+    }
+
+    /// The newest `version` of a world always wins, and is most complete among copies of the
+    /// same version.
+    fn completeness(&self) -> usize {
+        let filled = [
+            !self.name.is_empty(),
+            self.author_id.is_some(),
+            self.release_status != ReleaseStatus::Unknown,
+            self.updated_at.is_some(),
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count();
+        self.version.max(0) as usize * 5 + filled
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    /// Worlds are already keyed by their world id.
+    fn rekey(&mut self) {}
+}
+
+impl Mergeable for Avatar {
+    const TABLE: &'static str = "avatar";
+    type Key = String;
+
+    fn natural_key(&self) -> Self::Key {
+        self.id.clone()
+    }
+
+    /// Like a `World`, the newest `version` of an avatar always wins.
+    fn completeness(&self) -> usize {
+        let filled = [
+            !self.name.is_empty(),
+            self.author_id.is_some(),
+            self.release_status != ReleaseStatus::Unknown,
+            self.updated_at.is_some(),
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count();
+        self.version.max(0) as usize * 5 + filled
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    /// Avatars are already keyed by their avatar id.
+    fn rekey(&mut self) {}
+}
+
+impl Mergeable for FeedAvatar {
+    const TABLE: &'static str = "feed_avatar";
+    type Key = (DateTime<Utc>, String);
+
+    fn natural_key(&self) -> Self::Key {
+        (self.created_at, self.user_id.clone())
+    }
+
+    fn completeness(&self) -> usize {
+        [
+            self.owner_id.is_some(),
+            self.image_url.is_some(),
+            self.thumbnail_image_url.is_some(),
+            self.previous_image_url.is_some(),
+            self.previous_thumbnail_image_url.is_some(),
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count()
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    fn rekey(&mut self) {
+        let (created_at, user_id) = self.natural_key();
+        self.id = stable_id(&[&created_at.to_rfc3339(), &user_id]);
+    }
+}
+
+impl Mergeable for AvatarHistory {
+    const TABLE: &'static str = "avatar_history";
+    type Key = (DateTime<Utc>, String);
+
+    fn natural_key(&self) -> Self::Key {
+        (self.created_at, self.avatar_id.clone())
+    }
+
+    fn completeness(&self) -> usize {
+        0
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    fn rekey(&mut self) {
+        let (created_at, avatar_id) = self.natural_key();
+        self.id = stable_id(&[&created_at.to_rfc3339(), &avatar_id]);
+    }
+}
+
+impl Mergeable for FeedStatus {
+    const TABLE: &'static str = "feed_status";
+    type Key = (DateTime<Utc>, String);
+
+    fn natural_key(&self) -> Self::Key {
+        (self.created_at, self.user_id.clone())
+    }
+
+    fn completeness(&self) -> usize {
+        [
+            self.status_description.is_some(),
+            self.previous_status_description.is_some(),
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count()
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    fn rekey(&mut self) {
+        let (created_at, user_id) = self.natural_key();
+        self.id = stable_id(&[&created_at.to_rfc3339(), &user_id]);
+    }
+}
+
+impl Mergeable for FeedBio {
+    const TABLE: &'static str = "feed_bio";
+    type Key = (DateTime<Utc>, String);
+
+    fn natural_key(&self) -> Self::Key {
+        (self.created_at, self.user_id.clone())
+    }
+
+    fn completeness(&self) -> usize {
+        self.previous_bio.is_some() as usize
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    fn rekey(&mut self) {
+        let (created_at, user_id) = self.natural_key();
+        self.id = stable_id(&[&created_at.to_rfc3339(), &user_id]);
+    }
+}
+
+impl Mergeable for FeedOnlineOffline {
+    const TABLE: &'static str = "feed_online_offline";
+    type Key = (DateTime<Utc>, String, String);
+
+    fn natural_key(&self) -> Self::Key {
+        (
+            self.created_at,
+            self.user_id.clone(),
+            self.event.to_string(),
+        )
+    }
+
+    fn completeness(&self) -> usize {
+        [
+            self.world_name.is_some(),
+            self.time.is_some(),
+            self.group_name.is_some(),
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count()
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    fn rekey(&mut self) {
+        let (created_at, user_id, event) = self.natural_key();
+        self.id = stable_id(&[&created_at.to_rfc3339(), &user_id, &event]);
+    }
+}
+
+impl Mergeable for FeedGps {
+    const TABLE: &'static str = "feed_gps";
+    type Key = (DateTime<Utc>, String);
+
+    fn natural_key(&self) -> Self::Key {
+        (self.created_at, self.user_id.clone())
+    }
+
+    fn completeness(&self) -> usize {
+        [
+            self.world_name.is_some(),
+            self.previous_location != Location::Unknown,
+            self.time.is_some(),
+            self.group_name.is_some(),
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count()
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    fn rekey(&mut self) {
+        let (created_at, user_id) = self.natural_key();
+        self.id = stable_id(&[&created_at.to_rfc3339(), &user_id]);
+    }
+}
+
+impl Mergeable for Moderation {
+    const TABLE: &'static str = "moderation";
+    type Key = String;
+
+    fn natural_key(&self) -> Self::Key {
+        self.user_id.clone()
+    }
+
+    /// The most recently updated row wins, since it holds the latest flags.
+    fn completeness(&self) -> usize {
+        self.updated_at
+            .map_or(0, |updated_at| updated_at.timestamp().max(0) as usize)
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    /// Moderations are already keyed by `user_id`.
+    fn rekey(&mut self) {}
+}
+
+impl Mergeable for Notification {
+    const TABLE: &'static str = "notification";
+    type Key = String;
+
+    fn natural_key(&self) -> Self::Key {
+        self.id.clone()
+    }
+
+    fn completeness(&self) -> usize {
+        [
+            self.message.is_some(),
+            self.location != Location::Unknown,
+            self.world_name.is_some(),
+            self.image_url.is_some(),
+            self.invite_message.is_some(),
+            self.request_message.is_some(),
+            self.response_message.is_some(),
+        ]
+        .into_iter()
+        .filter(|filled| *filled)
+        .count()
+    }
+
+    fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    /// Notifications are already keyed by their VRChat id.
+    fn rekey(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::import::merge::{merge, stable_id, Mergeable};
+    use crate::models::gamelog_join_leave::GamelogJoinLeave;
+    use crate::models::source::Source;
+    use crate::models::world::World;
+    use crate::zaphkiel::join_leave_event::JoinLeaveEvent;
+
+    fn row(file: &str, seconds: i64, user_id: Option<&str>) -> GamelogJoinLeave {
+        GamelogJoinLeave {
+            id: 1,
+            created_at: Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap()
+                + Duration::seconds(seconds),
+            event: JoinLeaveEvent::Join,
+            display_name: "test".to_string(),
+            user_id: user_id.map(str::to_string),
+            sources: vec![Source {
+                file: file.to_string(),
+                account: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_is_order_independent() {
+        let rows = vec![
+            row("b.sqlite", 0, None),
+            row("a.sqlite", 0, None),
+            row("c.sqlite", 0, Some("usr_1234")),
+            row("b.sqlite", 1, None),
+        ];
+        let mut reversed = rows.clone();
+        reversed.reverse();
+
+        let (merged, report) = merge(rows);
+        assert_eq!(merge(reversed).0, merged);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(report.duplicates, 2);
+        assert_eq!(report.conflicts, 1);
+
+        assert_eq!(merged[0].user_id.as_deref(), Some("usr_1234"));
+        let files = |row: &GamelogJoinLeave| {
+            row.sources
+                .iter()
+                .map(|source| source.file.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(files(&merged[0]), ["a.sqlite", "b.sqlite", "c.sqlite"]);
+        assert_eq!(files(&merged[1]), ["b.sqlite"]);
+    }
+
+    #[test]
+    fn test_merge_ties_go_to_the_lowest_source() {
+        let (merged, report) = merge(vec![row("b.sqlite", 0, None), row("a.sqlite", 0, None)]);
+        assert_eq!(merged[0].sources.len(), 2);
+        assert_eq!(report.conflicts, 0);
+    }
+
+    #[test]
+    fn test_rekey_is_stable() {
+        let mut a = row("a.sqlite", 0, None);
+        let mut b = row("b.sqlite", 0, None);
+        b.id = 99;
+        a.rekey();
+        b.rekey();
+        assert_eq!(a.id, b.id);
+        assert!(a.id >= 0);
+        assert_ne!(stable_id(&["ab", "c"]), stable_id(&["a", "bc"]));
+    }
+
+    #[test]
+    fn test_merge_keeps_the_newest_world() {
+        let world = |file: &str, version: i64, author_id: Option<&str>| World {
+            id: "wrld_1234".to_string(),
+            name: "test".to_string(),
+            author_id: author_id.map(str::to_string),
+            version,
+            sources: vec![Source {
+                file: file.to_string(),
+                account: None,
+            }],
+            ..Default::default()
+        };
+        let (merged, report) = merge(vec![
+            world("a.sqlite", 1, Some("usr_1234")),
+            world("b.sqlite", 2, None),
+        ]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].version, 2);
+        assert_eq!(report.conflicts, 1);
+    }
+}
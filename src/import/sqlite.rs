@@ -0,0 +1,400 @@
+use std::path::Path;
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags, Row};
+
+use crate::rows::avatar_history::AvatarHistoryRow;
+use crate::rows::cache_avatar::CacheAvatarRow;
+use crate::rows::cache_world::CacheWorldRow;
+use crate::rows::feed_avatar::FeedAvatarRow;
+use crate::rows::feed_bio::FeedBioRow;
+use crate::rows::feed_gps::FeedGpsRow;
+use crate::rows::feed_online_offline::FeedOnlineOfflineRow;
+use crate::rows::feed_status::FeedStatusRow;
+use crate::rows::gamelog_join_leave::GamelogJoinLeaveRow;
+use crate::rows::gamelog_location::GamelogLocationRow;
+use crate::rows::moderation::ModerationRow;
+use crate::rows::notification::NotificationRow;
+use crate::rows::sqlite_master::SqliteMaster;
+use crate::rows::usr_friend_log_current::UsrFriendLogCurrentRow;
+
+/// A read-only handle on a VRCX `vrcx.sqlite` file.
+///
+/// VRCX keeps per-account tables prefixed with the account's user id, e.g.
+/// `usr_1234_friend_log_current`. Those are found through `sqlite_master`.
+pub struct VrcxSqlite {
+    conn: Connection,
+}
+
+impl VrcxSqlite {
+    /// Open `path` read-only.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Ok(VrcxSqlite { conn })
+    }
+
+    /// Every row of `sqlite_master`.
+    pub fn sqlite_master(&self) -> rusqlite::Result<Vec<SqliteMaster>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT type, name, tbl_name, COALESCE(rootpage, 0), COALESCE(sql, '') \
+            FROM sqlite_master",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(SqliteMaster {
+                type_: row.get(0)?,
+                name: row.get(1)?,
+                tbl_name: row.get(2)?,
+                rootpage: row.get(3)?,
+                sql: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Check if the file has a table named `name`. Older VRCX versions lack some of them.
+    pub fn has_table(&self, name: &str) -> rusqlite::Result<bool> {
+        Ok(self
+            .sqlite_master()?
+            .iter()
+            .any(|master| master.type_ == "table" && master.name == name))
+    }
+
+    /// The names of every `usr_*_friend_log_current` table.
+    pub fn friend_log_tables(&self) -> rusqlite::Result<Vec<String>> {
+        Ok(self
+            .sqlite_master()?
+            .into_iter()
+            .filter(|master| {
+                master.type_ == "table"
+                    && master.name.starts_with("usr")
+                    && master.name.ends_with("_friend_log_current")
+            })
+            .map(|master| master.name)
+            .collect())
+    }
+
+    /// The accounts with tables in this file, as VRCX prefixes them, e.g. `usr1234abcd`.
+    pub fn accounts(&self) -> rusqlite::Result<Vec<String>> {
+        Ok(self
+            .friend_log_tables()?
+            .into_iter()
+            .filter_map(|table| {
+                table
+                    .strip_suffix("_friend_log_current")
+                    .map(str::to_string)
+            })
+            .collect())
+    }
+
+    /// Every row of `gamelog_location`.
+    pub fn gamelog_location(&self) -> rusqlite::Result<Vec<GamelogLocationRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, created_at, location, world_id, world_name, time, group_name \
+            FROM gamelog_location ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(GamelogLocationRow {
+                id: row.get(0)?,
+                created_at: text(row, 1)?,
+                location: row.get(2)?,
+                world_id: row.get(3)?,
+                world_name: row.get(4)?,
+                time: or_default(row, 5)?,
+                group_name: or_default(row, 6)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of `gamelog_join_leave`.
+    pub fn gamelog_join_leave(&self) -> rusqlite::Result<Vec<GamelogJoinLeaveRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, created_at, type, display_name, location, user_id, time \
+            FROM gamelog_join_leave ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(GamelogJoinLeaveRow {
+                id: row.get(0)?,
+                created_at: text(row, 1)?,
+                event: or_default(row, 2)?,
+                display_name: or_default(row, 3)?,
+                location: or_default(row, 4)?,
+                user_id: or_default(row, 5)?,
+                time: or_default(row, 6)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of `cache_world`.
+    pub fn cache_world(&self) -> rusqlite::Result<Vec<CacheWorldRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, added_at, author_id, author_name, created_at, description, image_url, \
+            name, release_status, thumbnail_image_url, updated_at, version \
+            FROM cache_world ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(CacheWorldRow {
+                id: row.get(0)?,
+                added_at: text(row, 1)?,
+                author_id: or_default(row, 2)?,
+                author_name: or_default(row, 3)?,
+                created_at: text(row, 4)?,
+                description: or_default(row, 5)?,
+                image_url: or_default(row, 6)?,
+                name: or_default(row, 7)?,
+                release_status: or_default(row, 8)?,
+                thumbnail_image_url: or_default(row, 9)?,
+                updated_at: text(row, 10)?,
+                version: or_default(row, 11)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of `cache_avatar`.
+    pub fn cache_avatar(&self) -> rusqlite::Result<Vec<CacheAvatarRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, added_at, author_id, author_name, created_at, description, image_url, \
+            name, release_status, thumbnail_image_url, updated_at, version \
+            FROM cache_avatar ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(CacheAvatarRow {
+                id: row.get(0)?,
+                added_at: text(row, 1)?,
+                author_id: or_default(row, 2)?,
+                author_name: or_default(row, 3)?,
+                created_at: text(row, 4)?,
+                description: or_default(row, 5)?,
+                image_url: or_default(row, 6)?,
+                name: or_default(row, 7)?,
+                release_status: or_default(row, 8)?,
+                thumbnail_image_url: or_default(row, 9)?,
+                updated_at: text(row, 10)?,
+                version: or_default(row, 11)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the friend log table `table`, as returned by `friend_log_tables`.
+    pub fn friend_log_current(&self, table: &str) -> rusqlite::Result<Vec<UsrFriendLogCurrentRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT user_id, display_name, trust_level FROM \"{}\"",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(UsrFriendLogCurrentRow {
+                user_id: row.get(0)?,
+                display_name: or_default(row, 1)?,
+                trust_level: or_default(row, 2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the avatar feed table `table`, e.g. `usr1234_feed_avatar`.
+    pub fn feed_avatar(&self, table: &str) -> rusqlite::Result<Vec<FeedAvatarRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, created_at, user_id, display_name, owner_id, avatar_name, \
+            current_avatar_image_url, current_avatar_thumbnail_image_url, \
+            previous_current_avatar_image_url, previous_current_avatar_thumbnail_image_url \
+            FROM \"{}\" ORDER BY id",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(FeedAvatarRow {
+                id: row.get(0)?,
+                created_at: text(row, 1)?,
+                user_id: or_default(row, 2)?,
+                display_name: or_default(row, 3)?,
+                owner_id: or_default(row, 4)?,
+                avatar_name: or_default(row, 5)?,
+                current_avatar_image_url: or_default(row, 6)?,
+                current_avatar_thumbnail_image_url: or_default(row, 7)?,
+                previous_current_avatar_image_url: or_default(row, 8)?,
+                previous_current_avatar_thumbnail_image_url: or_default(row, 9)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the status feed table `table`, e.g. `usr1234_feed_status`.
+    pub fn feed_status(&self, table: &str) -> rusqlite::Result<Vec<FeedStatusRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, created_at, user_id, display_name, status, status_description, \
+            previous_status, previous_status_description FROM \"{}\" ORDER BY id",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(FeedStatusRow {
+                id: row.get(0)?,
+                created_at: text(row, 1)?,
+                user_id: or_default(row, 2)?,
+                display_name: or_default(row, 3)?,
+                status: or_default(row, 4)?,
+                status_description: or_default(row, 5)?,
+                previous_status: or_default(row, 6)?,
+                previous_status_description: or_default(row, 7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the bio feed table `table`, e.g. `usr1234_feed_bio`.
+    pub fn feed_bio(&self, table: &str) -> rusqlite::Result<Vec<FeedBioRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, created_at, user_id, display_name, bio, previous_bio \
+            FROM \"{}\" ORDER BY id",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(FeedBioRow {
+                id: row.get(0)?,
+                created_at: text(row, 1)?,
+                user_id: or_default(row, 2)?,
+                display_name: or_default(row, 3)?,
+                bio: or_default(row, 4)?,
+                previous_bio: or_default(row, 5)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the online/offline feed table `table`, e.g. `usr1234_feed_online_offline`.
+    pub fn feed_online_offline(&self, table: &str) -> rusqlite::Result<Vec<FeedOnlineOfflineRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, created_at, type, user_id, display_name, location, world_name, time, \
+            group_name FROM \"{}\" ORDER BY id",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(FeedOnlineOfflineRow {
+                id: row.get(0)?,
+                created_at: text(row, 1)?,
+                event: or_default(row, 2)?,
+                user_id: or_default(row, 3)?,
+                display_name: or_default(row, 4)?,
+                location: or_default(row, 5)?,
+                world_name: or_default(row, 6)?,
+                time: or_default(row, 7)?,
+                group_name: or_default(row, 8)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the GPS feed table `table`, e.g. `usr1234_feed_gps`.
+    pub fn feed_gps(&self, table: &str) -> rusqlite::Result<Vec<FeedGpsRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, created_at, user_id, display_name, location, world_name, \
+            previous_location, time, group_name FROM \"{}\" ORDER BY id",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(FeedGpsRow {
+                id: row.get(0)?,
+                created_at: text(row, 1)?,
+                user_id: or_default(row, 2)?,
+                display_name: or_default(row, 3)?,
+                location: or_default(row, 4)?,
+                world_name: or_default(row, 5)?,
+                previous_location: or_default(row, 6)?,
+                time: or_default(row, 7)?,
+                group_name: or_default(row, 8)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the moderation table `table`, e.g. `usr1234_moderation`.
+    pub fn moderation(&self, table: &str) -> rusqlite::Result<Vec<ModerationRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT user_id, updated_at, display_name, block, mute FROM \"{}\" ORDER BY user_id",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ModerationRow {
+                user_id: row.get(0)?,
+                updated_at: text(row, 1)?,
+                display_name: or_default(row, 2)?,
+                block: or_default(row, 3)?,
+                mute: or_default(row, 4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the notifications table `table`, e.g. `usr1234_notifications`, oldest
+    /// first.
+    pub fn notifications(&self, table: &str) -> rusqlite::Result<Vec<NotificationRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, created_at, type, sender_user_id, sender_username, receiver_user_id, \
+            message, world_id, world_name, image_url, invite_message, request_message, \
+            response_message, expired FROM \"{}\" ORDER BY created_at, id",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(NotificationRow {
+                id: row.get(0)?,
+                created_at: text(row, 1)?,
+                kind: or_default(row, 2)?,
+                sender_user_id: or_default(row, 3)?,
+                sender_username: or_default(row, 4)?,
+                receiver_user_id: or_default(row, 5)?,
+                message: or_default(row, 6)?,
+                world_id: or_default(row, 7)?,
+                world_name: or_default(row, 8)?,
+                image_url: or_default(row, 9)?,
+                invite_message: or_default(row, 10)?,
+                request_message: or_default(row, 11)?,
+                response_message: or_default(row, 12)?,
+                expired: or_default(row, 13)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every row of the avatar history table `table`, e.g. `usr1234_avatar_history`, oldest
+    /// first.
+    pub fn avatar_history(&self, table: &str) -> rusqlite::Result<Vec<AvatarHistoryRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT avatar_id, created_at FROM \"{}\" ORDER BY created_at",
+            table.replace('"', "\"\"")
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(AvatarHistoryRow {
+                avatar_id: row.get(0)?,
+                created_at: text(row, 1)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+/// Read a nullable column, turning `NULL` into the default value.
+fn or_default<T: rusqlite::types::FromSql + Default>(
+    row: &Row,
+    index: usize,
+) -> rusqlite::Result<T> {
+    Ok(row.get::<_, Option<T>>(index)?.unwrap_or_default())
+}
+
+/// Read a column as text, whatever SQLite stored it as. `NULL` becomes an empty string.
+///
+/// Older VRCX versions wrote some timestamps as numbers, so `created_at` is read with this and
+/// parsed later.
+fn text(row: &Row, index: usize) -> rusqlite::Result<String> {
+    Ok(match row.get_ref(index)? {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(value) => value.to_string(),
+        ValueRef::Real(value) => value.to_string(),
+        ValueRef::Text(value) | ValueRef::Blob(value) => {
+            String::from_utf8_lossy(value).into_owned()
+        }
+    })
+}
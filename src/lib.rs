@@ -1,22 +1,117 @@
+pub mod api {
+    pub mod server;
+}
+
+pub mod backup {
+    pub mod snapshot;
+}
+
+pub mod export {
+    pub mod anonymise;
+    pub mod exportable;
+    pub mod exporter;
+}
+
+#[cfg(feature = "fixtures")]
+pub mod fixtures {
+    pub mod vrcx;
+}
+
+pub mod graph {
+    pub mod avatar_timeline;
+    pub mod social_graph;
+    pub mod writers;
+}
+
+pub mod import {
+    pub mod batch;
+    pub mod importer;
+    pub mod merge;
+    pub mod sqlite;
+}
+
+pub mod live {
+    pub mod events;
+}
+
+pub mod logging {
+    pub mod subscriber;
+}
+
 pub mod models {
     pub mod app_config;
+    pub mod avatar;
+    pub mod avatar_history;
     pub mod connection;
+    pub mod feed_avatar;
+    pub mod feed_bio;
+    pub mod feed_gps;
+    pub mod feed_online_offline;
+    pub mod feed_status;
     pub mod gamelog_join_leave;
     pub mod gamelog_location;
+    pub mod moderation;
+    pub mod notification;
+    pub mod online_heatmap;
+    pub mod script_migration;
+    pub mod session;
+    pub mod source;
+    pub mod tenant;
     pub mod usr_friend_log_current;
+    pub mod world;
+    pub mod world_stats;
+}
+
+pub mod repo {
+    pub mod avatar;
+    pub mod avatar_history;
+    pub mod feed;
+    pub mod friend;
+    pub mod gamelog_join_leave;
+    pub mod gamelog_location;
+    pub mod graph;
+    pub mod insert_strategy;
+    pub mod moderation;
+    pub mod moderation_history;
+    pub mod notification;
+    pub mod purge;
+    pub mod table;
+    pub mod wear;
+    pub mod world;
+}
+
+pub mod resolvers {
+    pub mod display_name;
 }
 
 pub mod rows {
+    pub mod avatar_history;
+    pub mod cache_avatar;
+    pub mod cache_world;
+    pub mod feed_avatar;
+    pub mod feed_bio;
+    pub mod feed_gps;
+    pub mod feed_online_offline;
+    pub mod feed_status;
     pub mod gamelog_join_leave;
     pub mod gamelog_location;
+    pub mod moderation;
+    pub mod notification;
     pub mod sqlite_master;
     pub mod usr_friend_log_current;
 }
 
 pub mod zaphkiel {
     pub mod join_leave_event;
+    pub mod location;
     pub mod macros;
+    pub mod notification_kind;
+    pub mod online_offline_event;
+    pub mod release_status;
+    pub mod text;
+    pub mod timestamp;
     pub mod trust_level;
+    pub mod user_status;
     pub mod world_instance;
     pub mod world_regions;
 }
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use futures::stream::{self, Stream, StreamExt};
+use surrealdb::{Action, Connection, Surreal};
+
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::repo::friend::FriendRepo;
+use crate::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+use crate::zaphkiel::join_leave_event::JoinLeaveEvent;
+use crate::zaphkiel::trust_level::TrustLevel;
+
+/// The change that caused a live notification.
+///
+/// # Available Variants
+/// - Create
+/// - Update
+/// - Delete
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum LiveAction {
+    #[default]
+    Create,
+    Update,
+    Delete,
+}
+
+impl From<Action> for LiveAction {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::Update => LiveAction::Update,
+            Action::Delete => LiveAction::Delete,
+            _ => LiveAction::Create,
+        }
+    }
+}
+
+/// A typed change event from `subscribe`.
+///
+/// # Available Variants
+/// - JoinLeave: a `gamelog_join_leave` record changed.
+/// - Friend: a `friend_log_current` record changed.
+/// - FriendJoined: a friend joined your instance.
+/// - FriendLeft: a friend left your instance.
+/// - TrustLevelChanged: a friend's trust level changed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum LiveEvent {
+    JoinLeave {
+        action: LiveAction,
+        row: GamelogJoinLeave,
+    },
+    Friend {
+        action: LiveAction,
+        friend: UsrFriendLogCurrent,
+    },
+    FriendJoined {
+        friend: UsrFriendLogCurrent,
+        row: GamelogJoinLeave,
+    },
+    FriendLeft {
+        friend: UsrFriendLogCurrent,
+        row: GamelogJoinLeave,
+    },
+    TrustLevelChanged {
+        friend: UsrFriendLogCurrent,
+        previous: TrustLevel,
+    },
+}
+
+impl fmt::Display for LiveEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiveEvent::JoinLeave { action, row } => {
+                write!(
+                    f,
+                    "[{}] {:?} {:?}: {}",
+                    row.created_at, action, row.event, row.display_name
+                )?;
+                if let Some(location) = row.location.world_instance() {
+                    write!(f, " in {}:{}", location.world_id, location.instance_id)?;
+                }
+                Ok(())
+            }
+            LiveEvent::Friend { action, friend } => match action {
+                LiveAction::Create => write!(f, "{} is now a friend", friend.display_name),
+                LiveAction::Update => write!(f, "{} was updated", friend.display_name),
+                LiveAction::Delete => write!(f, "{} is no longer a friend", friend.display_name),
+            },
+            LiveEvent::FriendJoined { friend, row } => write!(
+                f,
+                "[{}] friend {} joined your instance",
+                row.created_at, friend.display_name
+            ),
+            LiveEvent::FriendLeft { friend, row } => write!(
+                f,
+                "[{}] friend {} left your instance",
+                row.created_at, friend.display_name
+            ),
+            LiveEvent::TrustLevelChanged { friend, previous } => write!(
+                f,
+                "{}'s trust level changed from {:?} to {:?}",
+                friend.display_name, previous, friend.trust_level
+            ),
+        }
+    }
+}
+
+/// Turns raw record changes into `LiveEvent`s, remembering the friend list to spot friends
+/// joining and trust level changes.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::live::events::{LiveAction, LiveEvent, LiveState};
+/// use surrealdb_test::models::gamelog_join_leave::GamelogJoinLeave;
+/// use surrealdb_test::models::usr_friend_log_current::UsrFriendLogCurrent;
+/// use surrealdb_test::zaphkiel::join_leave_event::JoinLeaveEvent;
+/// use surrealdb_test::zaphkiel::trust_level::TrustLevel;
+///
+/// let friend = UsrFriendLogCurrent {
+///     user_id: "usr_1234".to_string(),
+///     display_name: "test".to_string(),
+///     trust_level: TrustLevel::User,
+///     ..Default::default()
+/// };
+/// let mut state = LiveState::new(vec![friend.clone()]);
+///
+/// let row = GamelogJoinLeave {
+///     event: JoinLeaveEvent::Join,
+///     display_name: "test".to_string(),
+///     user_id: Some("usr_1234".to_string()),
+///     ..Default::default()
+/// };
+/// let events = state.on_join_leave(LiveAction::Create, row);
+/// assert!(matches!(events[1], LiveEvent::FriendJoined { .. }));
+///
+/// let promoted = UsrFriendLogCurrent {
+///     trust_level: TrustLevel::KnownUser,
+///     ..friend
+/// };
+/// let events = state.on_friend(LiveAction::Update, promoted);
+/// assert!(matches!(
+///     events[1],
+///     LiveEvent::TrustLevelChanged { previous: TrustLevel::User, .. }
+/// ));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LiveState {
+    friends: HashMap<String, UsrFriendLogCurrent>,
+}
+
+impl LiveState {
+    /// Create a new `LiveState` that knows about `friends`.
+    pub fn new(friends: Vec<UsrFriendLogCurrent>) -> Self {
+        LiveState {
+            friends: friends
+                .into_iter()
+                .map(|friend| (friend.user_id.clone(), friend))
+                .collect(),
+        }
+    }
+
+    /// Handle a change to a `gamelog_join_leave` record.
+    pub fn on_join_leave(&mut self, action: LiveAction, row: GamelogJoinLeave) -> Vec<LiveEvent> {
+        let friend = row
+            .user_id
+            .as_ref()
+            .and_then(|user_id| self.friends.get(user_id))
+            .cloned();
+
+        let derived = match (action, row.event, friend) {
+            (LiveAction::Create, JoinLeaveEvent::Join, Some(friend)) => {
+                Some(LiveEvent::FriendJoined {
+                    friend,
+                    row: row.clone(),
+                })
+            }
+            (LiveAction::Create, JoinLeaveEvent::Leave, Some(friend)) => {
+                Some(LiveEvent::FriendLeft {
+                    friend,
+                    row: row.clone(),
+                })
+            }
+            _ => None,
+        };
+
+        std::iter::once(LiveEvent::JoinLeave { action, row })
+            .chain(derived)
+            .collect()
+    }
+
+    /// Handle a change to a `friend_log_current` record.
+    pub fn on_friend(&mut self, action: LiveAction, friend: UsrFriendLogCurrent) -> Vec<LiveEvent> {
+        let previous = match action {
+            LiveAction::Delete => self.friends.remove(&friend.user_id),
+            _ => self.friends.insert(friend.user_id.clone(), friend.clone()),
+        };
+
+        let derived = previous
+            .filter(|previous| {
+                action == LiveAction::Update && previous.trust_level != friend.trust_level
+            })
+            .map(|previous| LiveEvent::TrustLevelChanged {
+                friend: friend.clone(),
+                previous: previous.trust_level,
+            });
+
+        std::iter::once(LiveEvent::Friend { action, friend })
+            .chain(derived)
+            .collect()
+    }
+}
+
+enum Change {
+    JoinLeave(LiveAction, Box<GamelogJoinLeave>),
+    Friend(LiveAction, UsrFriendLogCurrent),
+    Error(surrealdb::Error),
+}
+
+/// Subscribe to `LIVE SELECT` on `gamelog_join_leave` and `friend_log_current`.
+///
+/// The current friend list is loaded first, so that `FriendJoined` and `TrustLevelChanged`
+/// events work from the start. Records that fail to deserialize are passed on as errors
+/// without ending the stream.
+pub async fn subscribe<C: Connection>(
+    db: &Surreal<C>,
+) -> surrealdb::Result<impl Stream<Item = surrealdb::Result<LiveEvent>>> {
+    let friends = FriendRepo::new(db.clone());
+    let join_leave = GamelogJoinLeaveRepo::new(db.clone())
+        .live()
+        .await?
+        .map(|notification| match notification {
+            Ok(n) => Change::JoinLeave(n.action.into(), Box::new(n.data)),
+            Err(error) => Change::Error(error),
+        });
+    let friend = friends
+        .live()
+        .await?
+        .map(|notification| match notification {
+            Ok(n) => Change::Friend(n.action.into(), n.data),
+            Err(error) => Change::Error(error),
+        });
+
+    let mut state = LiveState::new(friends.all().await?);
+    Ok(stream::select(join_leave, friend).flat_map(move |change| {
+        let events = match change {
+            Change::JoinLeave(action, row) => state.on_join_leave(action, *row),
+            Change::Friend(action, friend) => state.on_friend(action, friend),
+            Change::Error(error) => return stream::iter(vec![Err(error)]),
+        };
+        stream::iter(events.into_iter().map(Ok).collect::<Vec<_>>())
+    }))
+}
@@ -0,0 +1,65 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+/// How log lines are written to stderr.
+///
+/// # Available Formats
+/// - Pretty: human readable lines.
+/// - Json: one JSON object per line, for log collectors.
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::logging::subscriber::LogFormat;
+///
+/// let format: LogFormat = "json".parse().unwrap();
+/// assert_eq!(format, LogFormat::Json);
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pretty" | "text" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format: {}", s)),
+        }
+    }
+}
+
+/// Install the global `tracing` subscriber.
+///
+/// # What it does
+///
+/// * The filter is `level` if given, else `RUST_LOG`, else `debug` when `verbose` is set and
+///   `info` otherwise. `level` accepts anything `EnvFilter` does, e.g. `surrealdb_test=trace`.
+/// * Closed spans are logged, with how long they were busy and idle.
+pub fn init(level: Option<&str>, verbose: bool, format: LogFormat) -> Result<(), Box<dyn Error>> {
+    let filter = match level {
+        Some(level) => EnvFilter::try_new(level)?,
+        None => EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new(if verbose { "debug" } else { "info" })),
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr);
+
+    let result = match format {
+        LogFormat::Pretty => builder.try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    };
+    result.map_err(|error| error as Box<dyn Error>)
+}
@@ -1,16 +1,54 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use clap::Parser;
+use futures::StreamExt;
 use surrealdb::opt::auth::Root;
-use surrealdb::sql::Thing;
+use surrealdb_test::api::server::serve;
+use surrealdb_test::backup::snapshot::{manifest, restore, verify, BackupFormat, Snapshot};
+use surrealdb_test::export::anonymise::Anonymiser;
+use surrealdb_test::export::exporter::{ExportFilter, Exporter};
+use surrealdb_test::graph::avatar_timeline::AvatarTimeline;
+use surrealdb_test::graph::social_graph::{EdgeKind, GraphOptions, SocialGraph};
+use surrealdb_test::import::batch::BatchOptions;
+use surrealdb_test::import::importer::{Importer, TableReport, VrcxTables};
+use surrealdb_test::live::events::subscribe;
+use surrealdb_test::logging::subscriber::init as init_logging;
 use surrealdb_test::measure_time;
 use surrealdb_test::models::app_config::AppConfig;
 use surrealdb_test::models::connection::establish_connection;
+use surrealdb_test::models::session::Session;
+use surrealdb_test::models::tenant::{account_prefix, Tenant};
+use surrealdb_test::models::world::MissingWorld;
+use surrealdb_test::repo::avatar::AvatarRepo;
+use surrealdb_test::repo::avatar_history::AvatarHistoryRepo;
+use surrealdb_test::repo::feed::{FeedAvatarRepo, FeedGpsRepo};
+use surrealdb_test::repo::friend::FriendRepo;
+use surrealdb_test::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+use surrealdb_test::repo::notification::NotificationRepo;
+use surrealdb_test::repo::purge::{Purger, Retention};
+use surrealdb_test::repo::world::WorldRepo;
+use surrealdb_test::resolvers::display_name::DisplayNameResolver;
+
+use crate::cli::{Cli, Command};
+
+mod cli;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Hello, world!");
+    let cli = Cli::parse();
+
+    let settings = AppConfig::get()?.build().await?;
+    let layout = settings.layout();
+    init_logging(cli.log_level.as_deref(), settings.verbose, cli.log_format)?;
+    let connection = (
+        settings.url.clone(),
+        settings.username.clone(),
+        settings.password.clone(),
+        settings.ns.clone(),
+        settings.tb.clone(),
+    );
 
-    let settings =
-        measure_time!("building AppConfig from settings.toml" => AppConfig::get().build().await?);
     let db = measure_time!("connecting to database" =>
         establish_connection(
             settings.url,
@@ -28,66 +66,317 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     })
     .await?;
 
-    db.use_ns("test").use_db("test").await?;
+    let account = cli.account.as_deref();
 
-    #[derive(Debug, Serialize, Deserialize)]
-    struct Name {
-        first: String,
-        last: String,
-    }
+    match cli.command {
+        Command::Import {
+            paths,
+            merge,
+            batch_size,
+            concurrency,
+            strategy,
+            retries,
+            timezone,
+        } => {
+            let mut tables = VrcxTables::default();
+            for path in &paths {
+                tables.extend(VrcxTables::read(path, timezone)?);
+            }
+            if merge || paths.len() > 1 {
+                let (merged, reports) = tables.merge();
+                tables = merged;
+                for report in reports {
+                    println!(
+                        "{}: {} duplicates merged, {} of them conflicting",
+                        report.table, report.duplicates, report.conflicts
+                    );
+                }
+            }
 
-    #[derive(Debug, Serialize, Deserialize)]
-    struct Person {
-        title: String,
-        name: Name,
-        marketing: bool,
-    }
+            let options = BatchOptions {
+                batch_size,
+                concurrency,
+                strategy,
+                retries,
+            };
+            let importer = Importer::new(db.clone(), options);
 
-    #[derive(Debug, Serialize, Deserialize)]
-    struct Responsibility {
-        marketing: bool,
-    }
+            let shared = layout.shared();
+            shared.select(&db).await?;
+            let worlds = std::mem::take(&mut tables.worlds);
+            let avatars = std::mem::take(&mut tables.avatars);
+            let reports = vec![
+                importer
+                    .import_table(WorldRepo::new(db.clone()), &worlds)
+                    .await,
+                importer
+                    .import_table(AvatarRepo::new(db.clone()), &avatars)
+                    .await,
+            ];
+            print_reports(&shared, reports);
+            let cached = WorldRepo::new(db.clone()).all().await?;
+            let cached_avatars = AvatarRepo::new(db.clone()).all().await?;
+            for world in MissingWorld::from_locations(&tables.locations, &cached) {
+                eprintln!(
+                    "{} ({}): visited {} times but not in the world cache",
+                    world.world_id, world.world_name, world.visits
+                );
+            }
 
-    #[derive(Debug, Serialize, Deserialize)]
-    struct Record {
-        #[allow(dead_code)]
-        id: Thing,
-    }
-    measure_time!("creating person record" => {
-        let created: Record = db
-            .create("person")
-            .content(Person {
-                title: "CEO".into(),
-                name: Name {
-                    first: "John".into(),
-                    last: "Doe".into(),
-                },
-                marketing: true,
-            })
-            .await?;
-        dbg!(&created);
-    });
-
-    measure_time!("updating person record" => {
-        let updated: Record = db
-            .update(("person", "jaime"))
-            .merge(Responsibility { marketing: true })
-            .await?;
-        dbg!(&updated);
-    });
+            let tenants = match account {
+                Some(account) => BTreeMap::from([(
+                    Some(account.to_string()),
+                    tables.for_account(&account_prefix(account)),
+                )]),
+                None => tables.split_by_account(),
+            };
+            for (account, mut tables) in tenants {
+                let tenant = layout.tenant(account.as_deref());
+                let owner = account
+                    .as_deref()
+                    .map(|account| {
+                        tables
+                            .user_id(account)
+                            .unwrap_or_else(|| account.to_string())
+                    })
+                    .unwrap_or_default();
+                let backfill =
+                    DisplayNameResolver::from_tables(&tables).backfill(&mut tables.join_leave);
+                println!(
+                    "{} gamelog_join_leave: {} user ids backfilled, {} ambiguous, {} unknown",
+                    tenant,
+                    backfill.backfilled.len(),
+                    backfill.ambiguous.len(),
+                    backfill.unresolved.len()
+                );
+                for row in &backfill.ambiguous {
+                    eprintln!(
+                        "{} gamelog_join_leave:{}: {} could be any of {}",
+                        tenant,
+                        row.id,
+                        row.display_name,
+                        row.candidates.join(", ")
+                    );
+                }
+                let sessions_changed = !tables.join_leave.is_empty() || !tables.friends.is_empty();
+                let avatars_changed =
+                    !tables.feed_avatar.is_empty() || !tables.avatar_history.is_empty();
+                let gps_changed = !tables.feed_gps.is_empty();
+                let notifications_changed = !tables.notifications.is_empty();
+                tenant.select(&db).await?;
+                print_reports(&tenant, importer.write(tables).await);
 
-    measure_time!("selection all people" => {
-        let people: Vec<Record> = db.select("person").await?;
-        dbg!(people);
-    });
-
-    measure_time!("complex select" => {
-        let groups = db
-            .query("SELECT marketing, count() FROM type::table($table) GROUP BY marketing")
-            .bind(("table", "person"))
-            .await?;
-        dbg!(groups);
-    });
+                if sessions_changed {
+                    let sessions = Session::from_join_leave(
+                        &GamelogJoinLeaveRepo::new(db.clone()).all().await?,
+                    );
+                    // Friend edges start at the owner, which only an account has.
+                    let friends = if owner.is_empty() {
+                        Vec::new()
+                    } else {
+                        FriendRepo::new(db.clone()).all().await?
+                    };
+                    let graph =
+                        SocialGraph::build(&sessions, &friends, &owner, &GraphOptions::default());
+                    SocialGraph::clear(&db, EdgeKind::Friend).await?;
+                    graph.store(&db).await?;
+                    println!(
+                        "{} social graph: {} nodes, {} edges",
+                        tenant,
+                        graph.nodes.len(),
+                        graph.edges.len()
+                    );
+                }
+                if avatars_changed {
+                    let timeline = AvatarTimeline::build(
+                        &FeedAvatarRepo::new(db.clone()).all().await?,
+                        &AvatarHistoryRepo::new(db.clone()).all().await?,
+                        &owner,
+                        &cached_avatars,
+                    );
+                    timeline.store(&db).await?;
+                    println!("{} wore: {} avatar changes", tenant, timeline.wears.len());
+                }
+                if gps_changed {
+                    let graph = SocialGraph::from_gps(
+                        &FeedGpsRepo::new(db.clone()).all().await?,
+                        &GraphOptions::default(),
+                    );
+                    SocialGraph::clear(&db, EdgeKind::Visited).await?;
+                    graph.store(&db).await?;
+                    println!("{} visited: {} moves", tenant, graph.edges.len());
+                }
+                if notifications_changed {
+                    let graph = SocialGraph::from_notifications(
+                        &NotificationRepo::new(db.clone()).all().await?,
+                        &GraphOptions::default(),
+                    );
+                    SocialGraph::clear(&db, EdgeKind::Invited).await?;
+                    graph.store(&db).await?;
+                    println!(
+                        "{} invited: {} sender/receiver pairs",
+                        tenant,
+                        graph.edges.len()
+                    );
+                }
+            }
+        }
+        Command::Anonymise {
+            to,
+            key,
+            granularity,
+        } => {
+            let from = layout.tenant(account);
+            let to = Tenant {
+                ns: from.ns.clone(),
+                db: to,
+            };
+            let anonymiser = Anonymiser::new(key).granularity(granularity);
+            let tables = anonymiser
+                .copy_database(&db, &from, &to, BatchOptions::default())
+                .await?;
+            for table in tables {
+                println!(
+                    "{} {}: {} of {} rows written",
+                    to, table.table, table.batch.written, table.read
+                );
+                for failed in table.batch.failed {
+                    eprintln!(
+                        "{} {}: row {} failed: {}",
+                        to, table.table, failed.index, failed.error
+                    );
+                }
+            }
+        }
+        Command::Export {
+            dir,
+            format,
+            tables,
+            from,
+            to,
+            key,
+            granularity,
+        } => {
+            layout.tenant(account).select(&db).await?;
+            let mut exporter = Exporter::new(format).filter(ExportFilter { from, to, tables });
+            if let Some(key) = key {
+                exporter = exporter.anonymise(Anonymiser::new(key).granularity(granularity));
+            }
+            for (table, path, count) in exporter.export_all(&db, &dir).await? {
+                println!(
+                    "{}: {} rows written to {}",
+                    table.name(),
+                    count,
+                    path.display()
+                );
+            }
+        }
+        Command::Purge {
+            older_than,
+            retain,
+            forget,
+            dry_run,
+        } => {
+            layout.tenant(account).select(&db).await?;
+            let purger = Purger::new(db).dry_run(dry_run);
+            if let Some(user_id) = forget {
+                print!("{}", purger.forget(&user_id).await?);
+            }
+            if older_than.is_some() || !retain.is_empty() {
+                let retention = retain
+                    .into_iter()
+                    .fold(Retention::new(older_than), Retention::table);
+                print!("{}", purger.older_than(&retention, Utc::now()).await?);
+            }
+            print!("{}", purger.compact_edges().await?);
+        }
+        Command::Backup { path } => {
+            layout.tenant(account).select(&db).await?;
+            let snapshot = Snapshot::read(&db).await?;
+            let manifest = snapshot.write(&path, BackupFormat::from_path(&path))?;
+            for checksum in manifest {
+                println!("{}", checksum);
+            }
+        }
+        Command::Restore { path } => {
+            layout.tenant(account).select(&db).await?;
+            let report = restore(&db, &path).await?;
+            print!("{}", report);
+            if !report.is_ok() {
+                return Err("restored database doesn't match the backup".into());
+            }
+        }
+        Command::Verify { path } => {
+            layout.tenant(account).select(&db).await?;
+            let report = verify(&db, &manifest(&path)?).await?;
+            print!("{}", report);
+            if !report.is_ok() {
+                return Err("database doesn't match the backup".into());
+            }
+        }
+        Command::Graph {
+            format,
+            from,
+            to,
+            weights,
+        } => {
+            layout.tenant(account).select(&db).await?;
+            let options = GraphOptions { weights, from, to };
+            let graph = SocialGraph::fetch(&db, &options).await?;
+            format.write(&graph, &options, std::io::stdout().lock())?;
+        }
+        Command::Serve { addr } => {
+            layout.tenant(account).select(&db).await?;
+            let (url, username, password, ns, tb) = connection;
+            let shared = establish_connection(url, username, password, ns, tb).await?;
+            shared
+                .signin(Root {
+                    username: "root",
+                    password: "root",
+                })
+                .await?;
+            layout.shared().select(&shared).await?;
+            serve(db, shared, addr).await?
+        }
+        Command::Tail => {
+            layout.tenant(account).select(&db).await?;
+            let mut events = Box::pin(subscribe(&db).await?);
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(event) => println!("{}", event),
+                    Err(error) => eprintln!("Error: {}", error),
+                }
+            }
+        }
+    }
 
     Ok(())
 }
+
+/// Print how many rows of every table were written to `tenant`, and every row that wasn't.
+/// Tables with nothing to write are skipped.
+fn print_reports(tenant: &Tenant, reports: Vec<TableReport>) {
+    for table in reports.into_iter().filter(|table| table.read > 0) {
+        println!(
+            "{} {}: {} of {} rows written in {} batches, {} retries",
+            tenant,
+            table.table,
+            table.batch.written,
+            table.read,
+            table.batch.batches,
+            table.batch.retries
+        );
+        for rejected in table.rejected {
+            eprintln!(
+                "{} {}: row {} rejected: {}",
+                tenant, table.table, rejected.id, rejected.error
+            );
+        }
+        for failed in table.batch.failed {
+            eprintln!(
+                "{} {}: row {} failed: {}",
+                tenant, table.table, failed.index, failed.error
+            );
+        }
+    }
+}
@@ -3,6 +3,8 @@ use std::error::Error;
 
 use config::Config;
 
+use crate::models::tenant::{Tenancy, TenantLayout};
+
 /// This is the application configuration.
 ///
 /// # Values
@@ -10,6 +12,10 @@ use config::Config;
 /// - `url` - The url to the surrealdb database.
 /// - `username` - The username to use for surrealdb.
 /// - `password` - The password to use for surrealdb.
+/// - `ns` - The namespace to use for surrealdb.
+/// - `tb` - The database to use for surrealdb.
+/// - `tenancy` - Whether every account gets its own `database` or `namespace`.
+/// - `shared` - The database or namespace every account shares, `shared` by default.
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct AppConfig {
     pub url: Option<String>,
@@ -17,6 +23,8 @@ pub struct AppConfig {
     pub password: Option<String>,
     pub ns: Option<String>,
     pub tb: Option<String>,
+    pub tenancy: Tenancy,
+    pub shared: Option<String>,
     pub verbose: bool,
 }
 
@@ -41,6 +49,8 @@ impl Default for AppConfig {
             password: None,
             ns: None,
             tb: None,
+            tenancy: Tenancy::default(),
+            shared: None,
             verbose: false,
         }
     }
@@ -81,29 +91,44 @@ impl AppConfig {
             password: self.password,
             ns: self.ns,
             tb: self.tb,
+            tenancy: self.tenancy,
+            shared: self.shared,
             verbose: self.verbose,
         })
     }
 
+    /// Where every account's data lives, see `TenantLayout`.
+    ///
+    /// Unset values are left empty, so this is meant for an `AppConfig` that was `build`.
+    pub fn layout(&self) -> TenantLayout {
+        TenantLayout {
+            tenancy: self.tenancy,
+            ns: self.ns.clone().unwrap_or_default(),
+            db: self.tb.clone().unwrap_or_default(),
+            shared: self.shared.clone().unwrap_or_else(|| "shared".to_string()),
+        }
+    }
+
     /// Get the `AppConfig` from the `Settings.toml` file.
-    pub fn get() -> Self {
+    ///
+    /// Fails if a value can't be parsed, e.g. an unknown `tenancy`.
+    pub fn get() -> Result<Self, Box<dyn Error>> {
         let settings = Config::builder()
             .add_source(config::File::with_name("src/Settings.toml"))
             .build()
             .unwrap_or_default();
 
-        let settings: AppConfig = settings
-            .try_deserialize::<HashMap<String, String>>()
-            .unwrap()
-            .into();
-
         settings
+            .try_deserialize::<HashMap<String, String>>()?
+            .try_into()
     }
 }
 
-impl From<HashMap<String, String>> for AppConfig {
+impl TryFrom<HashMap<String, String>> for AppConfig {
+    type Error = Box<dyn Error>;
+
     /// Create a new `AppConfig` from a `HashMap<String, String>`.
-    fn from(map: HashMap<String, String>) -> Self {
+    fn try_from(map: HashMap<String, String>) -> Result<Self, Self::Error> {
         let mut config = AppConfig::new();
 
         for (key, value) in map {
@@ -111,13 +136,15 @@ impl From<HashMap<String, String>> for AppConfig {
                 "url" => config.url = Some(value),
                 "username" => config.username = Some(value),
                 "password" => config.password = Some(value),
-                "verbose" => config.verbose = value.parse::<bool>().unwrap(),
+                "verbose" => config.verbose = value.parse::<bool>()?,
                 "ns" => config.ns = Some(value),
                 "tb" => config.tb = Some(value),
+                "tenancy" => config.tenancy = value.parse::<Tenancy>()?,
+                "shared" => config.shared = Some(value),
                 _ => {}
             }
         }
 
-        config
+        Ok(config)
     }
 }
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::source::Source;
+use crate::rows::cache_avatar::CacheAvatarRow;
+use crate::zaphkiel::release_status::ReleaseStatus;
+use crate::zaphkiel::text::non_empty;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone};
+
+/// This is a row from the `cache_avatar` table, stored as the `avatar` record keyed by its
+/// avatar id, e.g. `avatar:avtr_1234`.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::avatar::Avatar;
+/// use surrealdb_test::rows::cache_avatar::CacheAvatarRow;
+/// use surrealdb_test::zaphkiel::release_status::ReleaseStatus;
+///
+/// let avatar = Avatar::from(CacheAvatarRow {
+///     id: "avtr_1234".to_string(),
+///     author_id: "usr_1234".to_string(),
+///     name: "Test Avatar".to_string(),
+///     release_status: "private".to_string(),
+///     ..Default::default()
+/// });
+/// assert_eq!(avatar.id, "avtr_1234");
+/// assert_eq!(avatar.author_id.as_deref(), Some("usr_1234"));
+/// assert_eq!(avatar.release_status, ReleaseStatus::Private);
+/// assert_eq!(avatar.description, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Avatar {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub author_id: Option<String>,
+    pub author_name: Option<String>,
+    pub release_status: ReleaseStatus,
+    pub image_url: Option<String>,
+    pub thumbnail_image_url: Option<String>,
+    pub version: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at_raw: String,
+    /// When VRCX cached the avatar.
+    pub added_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub added_at_raw: String,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl Avatar {
+    /// Create a new `Avatar` by calling `Avatar::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `CacheAvatarRow` the same way `World::from_row` converts a world.
+    pub fn from_row(row: CacheAvatarRow, timezone: SourceTimezone) -> Self {
+        Avatar {
+            id: row.id,
+            name: row.name.trim().to_string(),
+            description: non_empty(row.description),
+            author_id: non_empty(row.author_id),
+            author_name: non_empty(row.author_name),
+            release_status: ReleaseStatus::from(row.release_status),
+            image_url: non_empty(row.image_url),
+            thumbnail_image_url: non_empty(row.thumbnail_image_url),
+            version: row.version,
+            created_at: parse_timestamp(&row.created_at, timezone).ok(),
+            created_at_raw: row.created_at,
+            updated_at: parse_timestamp(&row.updated_at, timezone).ok(),
+            updated_at_raw: row.updated_at,
+            added_at: parse_timestamp(&row.added_at, timezone).ok(),
+            added_at_raw: row.added_at,
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl From<CacheAvatarRow> for Avatar {
+    /// Convert a `CacheAvatarRow` into an `Avatar`.
+    /// See `Avatar::from_row`, with the default `SourceTimezone`.
+    fn from(row: CacheAvatarRow) -> Self {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::source::Source;
+use crate::rows::avatar_history::AvatarHistoryRow;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+
+/// This is a row from the `_avatar_history` table: an avatar the account itself wore.
+///
+/// VRCX keys the table by `avatar_id` and moves `created_at` forward whenever the avatar is
+/// worn again, so every row is the last time its avatar was put on.
+///
+/// `id` is 0 until the row is `rekey`ed, see `Mergeable`, since the table has no integer id.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::avatar_history::AvatarHistory;
+/// use surrealdb_test::rows::avatar_history::AvatarHistoryRow;
+///
+/// let row = AvatarHistory::try_from(AvatarHistoryRow {
+///     avatar_id: "avtr_1234".to_string(),
+///     created_at: "2023-04-29T10:00:00.000Z".to_string(),
+/// }).unwrap();
+/// assert_eq!(row.avatar_id, "avtr_1234");
+/// assert_eq!(row.id, 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct AvatarHistory {
+    pub id: i64,
+    pub avatar_id: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl AvatarHistory {
+    /// Create a new `AvatarHistory` by calling `AvatarHistory::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert an `AvatarHistoryRow`, reading `created_at` as local time in `timezone` if it
+    /// has no offset. `created_at` is kept as it was in `created_at_raw`.
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: AvatarHistoryRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
+        Ok(AvatarHistory {
+            id: 0,
+            avatar_id: row.avatar_id,
+            created_at: parse_timestamp(&row.created_at, timezone)?,
+            created_at_raw: row.created_at,
+            sources: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<AvatarHistoryRow> for AvatarHistory {
+    type Error = TimestampParseError;
+
+    /// Convert an `AvatarHistoryRow` into an `AvatarHistory`.
+    /// See `AvatarHistory::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: AvatarHistoryRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
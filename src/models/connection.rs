@@ -3,6 +3,7 @@ use surrealdb::engine::remote::ws::Ws;
 use surrealdb::opt::auth::Root;
 use surrealdb::{Connection, Surreal};
 
+#[tracing::instrument(skip_all, fields(url = url.as_deref(), ns = ns.as_deref(), db = tb.as_deref()))]
 pub async fn establish_connection(
     url: Option<String>,
     username: Option<String>,
@@ -27,8 +28,7 @@ pub async fn establish_connection(
     });
 
     let url = url.as_str();
-    dbg!(url);
-    dbg!("localhost:8000");
+    tracing::debug!(url, "connecting");
     let db = Surreal::new::<Ws>(url).await?;
 
     db.signin(Root {
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::source::Source;
+use crate::rows::feed_avatar::FeedAvatarRow;
+use crate::zaphkiel::text::non_empty;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+
+/// This is a row from the `_feed_avatar` table: a friend switching into an avatar.
+///
+/// The feed doesn't name the avatar by id, only by name, uploader and image, see
+/// `AvatarTimeline` for how it is matched to an `avatar` record.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::feed_avatar::FeedAvatar;
+/// use surrealdb_test::rows::feed_avatar::FeedAvatarRow;
+///
+/// let row = FeedAvatar::try_from(FeedAvatarRow {
+///     id: 1,
+///     created_at: "2023-04-29T10:00:00.000Z".to_string(),
+///     user_id: "usr_1234".to_string(),
+///     display_name: "Some User".to_string(),
+///     avatar_name: "Test Avatar".to_string(),
+///     current_avatar_image_url: "https://example.com/image".to_string(),
+///     ..Default::default()
+/// }).unwrap();
+/// assert_eq!(row.user_id, "usr_1234");
+/// assert_eq!(row.image_url.as_deref(), Some("https://example.com/image"));
+/// assert_eq!(row.previous_image_url, None);
+/// assert_eq!(row.owner_id, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedAvatar {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    pub user_id: String,
+    pub display_name: String,
+    /// The uploader of the avatar.
+    pub owner_id: Option<String>,
+    pub avatar_name: String,
+    pub image_url: Option<String>,
+    pub thumbnail_image_url: Option<String>,
+    pub previous_image_url: Option<String>,
+    pub previous_thumbnail_image_url: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl FeedAvatar {
+    /// Create a new `FeedAvatar` by calling `FeedAvatar::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `FeedAvatarRow`, reading `created_at` as local time in `timezone` if it has no
+    /// offset.
+    ///
+    /// # What it does
+    ///
+    /// * `created_at` is parsed with `parse_timestamp`, and kept as it was in `created_at_raw`.
+    /// * `id`, `user_id` and `display_name` are copied, and `avatar_name` is trimmed.
+    /// * The image urls of the current and previous avatar are copied.
+    /// * Empty text columns are set to `None`.
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: FeedAvatarRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
+        Ok(FeedAvatar {
+            id: row.id,
+            created_at: parse_timestamp(&row.created_at, timezone)?,
+            created_at_raw: row.created_at,
+            user_id: row.user_id,
+            display_name: row.display_name,
+            owner_id: non_empty(row.owner_id),
+            avatar_name: row.avatar_name.trim().to_string(),
+            image_url: non_empty(row.current_avatar_image_url),
+            thumbnail_image_url: non_empty(row.current_avatar_thumbnail_image_url),
+            previous_image_url: non_empty(row.previous_current_avatar_image_url),
+            previous_thumbnail_image_url: non_empty(
+                row.previous_current_avatar_thumbnail_image_url,
+            ),
+            sources: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<FeedAvatarRow> for FeedAvatar {
+    type Error = TimestampParseError;
+
+    /// Convert a `FeedAvatarRow` into a `FeedAvatar`.
+    /// See `FeedAvatar::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: FeedAvatarRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::source::Source;
+use crate::rows::feed_bio::FeedBioRow;
+use crate::zaphkiel::text::non_empty;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+
+/// This is a row from the `_feed_bio` table: a friend editing their bio.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::feed_bio::FeedBio;
+/// use surrealdb_test::rows::feed_bio::FeedBioRow;
+///
+/// let row = FeedBio::try_from(FeedBioRow {
+///     id: 1,
+///     created_at: "2023-04-29T10:00:00.000Z".to_string(),
+///     user_id: "usr_1234".to_string(),
+///     bio: "hello".to_string(),
+///     ..Default::default()
+/// }).unwrap();
+/// assert_eq!(row.bio, "hello");
+/// assert_eq!(row.previous_bio, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedBio {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    pub user_id: String,
+    pub display_name: String,
+    pub bio: String,
+    pub previous_bio: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl FeedBio {
+    /// Create a new `FeedBio` by calling `FeedBio::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `FeedBioRow`, reading `created_at` as local time in `timezone` if it has no
+    /// offset.
+    ///
+    /// `created_at` is parsed with `parse_timestamp`, and kept as it was in `created_at_raw`.
+    /// Everything else is copied, with an empty `previous_bio` set to `None`. An empty `bio` is
+    /// kept, since clearing the bio is an edit too.
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: FeedBioRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
+        Ok(FeedBio {
+            id: row.id,
+            created_at: parse_timestamp(&row.created_at, timezone)?,
+            created_at_raw: row.created_at,
+            user_id: row.user_id,
+            display_name: row.display_name,
+            bio: row.bio,
+            previous_bio: non_empty(row.previous_bio),
+            sources: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<FeedBioRow> for FeedBio {
+    type Error = TimestampParseError;
+
+    /// Convert a `FeedBioRow` into a `FeedBio`.
+    /// See `FeedBio::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: FeedBioRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::source::Source;
+use crate::rows::feed_gps::FeedGpsRow;
+use crate::zaphkiel::location::Location;
+use crate::zaphkiel::text::non_empty;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+use crate::zaphkiel::world_instance::ParseMode;
+
+/// This is a row from the `_feed_gps` table: a friend moving from `previous_location` to
+/// `location`.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::feed_gps::FeedGps;
+/// use surrealdb_test::rows::feed_gps::FeedGpsRow;
+/// use surrealdb_test::zaphkiel::location::Location;
+///
+/// let row = FeedGps::try_from(FeedGpsRow {
+///     id: 1,
+///     created_at: "2023-04-29T10:00:00.000Z".to_string(),
+///     user_id: "usr_1234".to_string(),
+///     location: "wrld_1234:1234~region(eu)".to_string(),
+///     world_name: "test".to_string(),
+///     previous_location: "private".to_string(),
+///     time: 60_000,
+///     ..Default::default()
+/// }).unwrap();
+/// assert_eq!(row.location.world_instance().unwrap().world_id, "wrld_1234");
+/// assert_eq!(row.previous_location, Location::Private);
+/// assert_eq!(row.world_name.as_deref(), Some("test"));
+/// assert_eq!(row.time, Some(60_000));
+/// assert_eq!(row.group_name, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedGps {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    pub user_id: String,
+    pub display_name: String,
+    pub location: Location,
+    pub world_name: Option<String>,
+    pub previous_location: Location,
+    /// How long the friend stayed in `previous_location`, in milliseconds.
+    pub time: Option<u64>,
+    pub group_name: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl FeedGps {
+    /// Create a new `FeedGps` by calling `FeedGps::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `FeedGpsRow`, reading `created_at` as local time in `timezone` if it has no
+    /// offset.
+    ///
+    /// # What it does
+    ///
+    /// * `created_at` is parsed with `parse_timestamp`, and kept as it was in `created_at_raw`.
+    /// * `location` and `previous_location` are parsed leniently into a `Location`, so world
+    ///   instances go through the same `WorldInstance` parser as `gamelog_location`.
+    /// * `time` is copied, but if it is `0` or less, it is set to `None`.
+    /// * Empty text columns are set to `None`.
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: FeedGpsRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
+        Ok(FeedGps {
+            id: row.id,
+            created_at: parse_timestamp(&row.created_at, timezone)?,
+            created_at_raw: row.created_at,
+            user_id: row.user_id,
+            display_name: row.display_name,
+            location: Location::parse(&row.location, ParseMode::Lenient),
+            world_name: non_empty(row.world_name),
+            previous_location: Location::parse(&row.previous_location, ParseMode::Lenient),
+            time: match row.time {
+                ..=0 => None,
+                _ => Some(row.time as u64),
+            },
+            group_name: non_empty(row.group_name),
+            sources: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<FeedGpsRow> for FeedGps {
+    type Error = TimestampParseError;
+
+    /// Convert a `FeedGpsRow` into a `FeedGps`.
+    /// See `FeedGps::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: FeedGpsRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
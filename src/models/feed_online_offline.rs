@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::source::Source;
+use crate::rows::feed_online_offline::FeedOnlineOfflineRow;
+use crate::zaphkiel::location::Location;
+use crate::zaphkiel::online_offline_event::OnlineOfflineEvent;
+use crate::zaphkiel::text::non_empty;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+use crate::zaphkiel::world_instance::ParseMode;
+
+/// This is a row from the `_feed_online_offline` table: a friend coming online or going
+/// offline.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::feed_online_offline::FeedOnlineOffline;
+/// use surrealdb_test::rows::feed_online_offline::FeedOnlineOfflineRow;
+/// use surrealdb_test::zaphkiel::location::Location;
+/// use surrealdb_test::zaphkiel::online_offline_event::OnlineOfflineEvent;
+///
+/// let row = FeedOnlineOffline::try_from(FeedOnlineOfflineRow {
+///     id: 1,
+///     created_at: "2023-04-29T10:00:00.000Z".to_string(),
+///     event: "Offline".to_string(),
+///     user_id: "usr_1234".to_string(),
+///     location: "offline".to_string(),
+///     time: 3_600_000,
+///     ..Default::default()
+/// }).unwrap();
+/// assert_eq!(row.event, OnlineOfflineEvent::Offline);
+/// assert_eq!(row.location, Location::Offline);
+/// assert_eq!(row.time, Some(3_600_000));
+/// assert_eq!(row.world_name, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedOnlineOffline {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    pub event: OnlineOfflineEvent,
+    pub user_id: String,
+    pub display_name: String,
+    pub location: Location,
+    pub world_name: Option<String>,
+    /// On `Offline`, how long the friend was online, in milliseconds.
+    pub time: Option<u64>,
+    pub group_name: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl FeedOnlineOffline {
+    /// Create a new `FeedOnlineOffline` by calling `FeedOnlineOffline::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `FeedOnlineOfflineRow`, reading `created_at` as local time in `timezone` if it
+    /// has no offset.
+    ///
+    /// # What it does
+    ///
+    /// * `created_at` is parsed with `parse_timestamp`, and kept as it was in `created_at_raw`.
+    /// * `event` is parsed into an `OnlineOfflineEvent`.
+    /// * `location` is parsed leniently into a `Location`.
+    /// * `time` is copied, but if it is `0` or less, it is set to `None`.
+    /// * Empty text columns are set to `None`.
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: FeedOnlineOfflineRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
+        Ok(FeedOnlineOffline {
+            id: row.id,
+            created_at: parse_timestamp(&row.created_at, timezone)?,
+            created_at_raw: row.created_at,
+            event: OnlineOfflineEvent::from(row.event),
+            user_id: row.user_id,
+            display_name: row.display_name,
+            location: Location::parse(&row.location, ParseMode::Lenient),
+            world_name: non_empty(row.world_name),
+            time: match row.time {
+                ..=0 => None,
+                _ => Some(row.time as u64),
+            },
+            group_name: non_empty(row.group_name),
+            sources: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<FeedOnlineOfflineRow> for FeedOnlineOffline {
+    type Error = TimestampParseError;
+
+    /// Convert a `FeedOnlineOfflineRow` into a `FeedOnlineOffline`.
+    /// See `FeedOnlineOffline::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: FeedOnlineOfflineRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
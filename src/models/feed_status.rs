@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::source::Source;
+use crate::rows::feed_status::FeedStatusRow;
+use crate::zaphkiel::text::non_empty;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+use crate::zaphkiel::user_status::UserStatus;
+
+/// This is a row from the `_feed_status` table: a friend changing their status or its
+/// description.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::feed_status::FeedStatus;
+/// use surrealdb_test::rows::feed_status::FeedStatusRow;
+/// use surrealdb_test::zaphkiel::user_status::UserStatus;
+///
+/// let row = FeedStatus::try_from(FeedStatusRow {
+///     id: 1,
+///     created_at: "2023-04-29T10:00:00.000Z".to_string(),
+///     user_id: "usr_1234".to_string(),
+///     status: "busy".to_string(),
+///     status_description: "working".to_string(),
+///     previous_status: "join me".to_string(),
+///     ..Default::default()
+/// }).unwrap();
+/// assert_eq!(row.status, UserStatus::Busy);
+/// assert_eq!(row.status_description.as_deref(), Some("working"));
+/// assert_eq!(row.previous_status, UserStatus::JoinMe);
+/// assert_eq!(row.previous_status_description, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedStatus {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    pub user_id: String,
+    pub display_name: String,
+    pub status: UserStatus,
+    pub status_description: Option<String>,
+    pub previous_status: UserStatus,
+    pub previous_status_description: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl FeedStatus {
+    /// Create a new `FeedStatus` by calling `FeedStatus::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `FeedStatusRow`, reading `created_at` as local time in `timezone` if it has no
+    /// offset.
+    ///
+    /// # What it does
+    ///
+    /// * `created_at` is parsed with `parse_timestamp`, and kept as it was in `created_at_raw`.
+    /// * `status` and `previous_status` are parsed into a `UserStatus`.
+    /// * Empty descriptions are set to `None`.
+    /// * `id`, `user_id` and `display_name` are copied.
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: FeedStatusRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
+        Ok(FeedStatus {
+            id: row.id,
+            created_at: parse_timestamp(&row.created_at, timezone)?,
+            created_at_raw: row.created_at,
+            user_id: row.user_id,
+            display_name: row.display_name,
+            status: UserStatus::from(row.status),
+            status_description: non_empty(row.status_description),
+            previous_status: UserStatus::from(row.previous_status),
+            previous_status_description: non_empty(row.previous_status_description),
+            sources: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<FeedStatusRow> for FeedStatus {
+    type Error = TimestampParseError;
+
+    /// Convert a `FeedStatusRow` into a `FeedStatus`.
+    /// See `FeedStatus::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: FeedStatusRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
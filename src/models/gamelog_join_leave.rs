@@ -1,11 +1,14 @@
 use chrono::{DateTime, Utc};
 
+use crate::models::source::Source;
 use crate::rows::gamelog_join_leave::GamelogJoinLeaveRow;
 use crate::zaphkiel::join_leave_event::JoinLeaveEvent;
-use crate::zaphkiel::world_instance::WorldInstance;
+use crate::zaphkiel::location::Location;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+use crate::zaphkiel::world_instance::ParseMode;
 
 /// This is a row from the `gamelog_join_leave` table, but with the `location` field parsed into a
-/// `WorldInstance`.
+/// `Location`.
 ///
 /// # Examples
 ///
@@ -14,22 +17,22 @@ use crate::zaphkiel::world_instance::WorldInstance;
 /// use surrealdb_test::rows::gamelog_join_leave::GamelogJoinLeaveRow;
 /// use surrealdb_test::zaphkiel::join_leave_event::JoinLeaveEvent;
 ///
-/// let row = GamelogJoinLeave::from(
+/// let row = GamelogJoinLeave::try_from(
 ///     GamelogJoinLeaveRow {
 ///         id: 1,
-///         created_at: chrono::Utc::now(),
+///         created_at: "2023-04-29T10:00:00.000Z".to_string(),
 ///         event: "join".to_string(),
 ///         display_name: "test".to_string(),
 ///         location: "wrld_1234:1234".to_string(),
 ///         user_id: "usr_1234".to_string(),
 ///         time: 1234,
 ///     }
-/// );
+/// ).unwrap();
 /// assert_eq!(row.id, 1);
 /// assert_eq!(row.event, JoinLeaveEvent::Join);
 /// assert_eq!(row.display_name, "test");
-/// assert_eq!(row.location.clone().unwrap().world_id, "wrld_1234".to_string());
-/// assert_eq!(row.location.clone().unwrap().instance_id, "1234".to_string());
+/// assert_eq!(row.location.world_instance().unwrap().world_id, "wrld_1234".to_string());
+/// assert_eq!(row.location.world_instance().unwrap().instance_id, "1234".to_string());
 /// assert_eq!(row.user_id.clone().unwrap(), "usr_1234".to_string());
 /// assert_eq!(row.time.unwrap(), 1234);
 /// ```
@@ -37,11 +40,15 @@ use crate::zaphkiel::world_instance::WorldInstance;
 pub struct GamelogJoinLeave {
     pub id: i64,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
     pub event: JoinLeaveEvent,
     pub display_name: String,
-    pub location: Option<WorldInstance>,
+    pub location: Location,
     pub user_id: Option<String>,
     pub time: Option<u64>,
+    #[serde(default)]
+    pub sources: Vec<Source>,
 }
 
 impl GamelogJoinLeave {
@@ -50,30 +57,35 @@ impl GamelogJoinLeave {
     }
 }
 
-impl From<GamelogJoinLeaveRow> for GamelogJoinLeave {
-    /// Convert a `GamelogJoinLeaveRow` into a `GamelogJoinLeave`.
+impl GamelogJoinLeave {
+    /// Convert a `GamelogJoinLeaveRow`, reading `created_at` as local time in `timezone` if it
+    /// has no offset.
     ///
     /// # What it does
     ///
     /// * `id` is copied over.
-    /// * `created_at` is copied over.
+    /// * `created_at` is parsed with `parse_timestamp`, and kept as it was in `created_at_raw`.
     /// * `event` is parsed into a `JoinLeaveEvent`.
     /// * `display_name` is copied over.
-    /// * `location` is parsed into a `WorldInstance`.
+    /// * `location` is parsed into a `Location`, with instances parsed leniently.
     /// * `user_id` is copied over.
     /// * `time` is checked to see if it's 0 or less. If it is, it's set to `None`. Otherwise, it's
     ///  set to `Some(time as u64)`.
-    fn from(row: GamelogJoinLeaveRow) -> Self {
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: GamelogJoinLeaveRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
         let mut ret = Self::new();
         ret.id = row.id;
-        ret.created_at = row.created_at;
-        ret.event = row.event.parse().unwrap();
+        ret.created_at = parse_timestamp(&row.created_at, timezone)?;
+        ret.created_at_raw = row.created_at;
+        ret.event = JoinLeaveEvent::from(row.event);
         ret.display_name = row.display_name;
-        ret.location = if let Ok(location) = row.location.parse() {
-            Some(location)
-        } else {
-            None
-        };
+        ret.location = Location::parse(&row.location, ParseMode::Lenient);
         ret.user_id = match row.user_id {
             x if x.is_empty() => None,
             _ => Some(row.user_id),
@@ -83,6 +95,16 @@ impl From<GamelogJoinLeaveRow> for GamelogJoinLeave {
             _ => Some(row.time as u64),
         };
 
-        ret
+        Ok(ret)
+    }
+}
+
+impl TryFrom<GamelogJoinLeaveRow> for GamelogJoinLeave {
+    type Error = TimestampParseError;
+
+    /// Convert a `GamelogJoinLeaveRow` into a `GamelogJoinLeave`.
+    /// See `GamelogJoinLeave::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: GamelogJoinLeaveRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
     }
 }
@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
 
+use crate::models::source::Source;
 use crate::rows::gamelog_location::GamelogLocationRow;
-use crate::zaphkiel::world_instance::WorldInstance;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+use crate::zaphkiel::world_instance::{ParseMode, WorldInstance};
 
 /// This is a row from the `gamelog_location` table, but with the `location` field parsed into a
 /// `WorldInstance`.
@@ -12,18 +14,19 @@ use crate::zaphkiel::world_instance::WorldInstance;
 /// use surrealdb_test::models::gamelog_location::GamelogLocation;
 /// use surrealdb_test::rows::gamelog_location::GamelogLocationRow;
 ///
-/// let row = GamelogLocation::from(
+/// let row = GamelogLocation::try_from(
 ///     GamelogLocationRow {
 ///         id: 1,
-///         created_at: chrono::Utc::now(),
+///         created_at: "2023-04-29T10:00:00.000Z".to_string(),
 ///         location: "wrld_1234:1234".to_string(),
 ///         world_id: "wrld_1234".to_string(),
 ///         world_name: "test".to_string(),
 ///         time: 1234,
 ///         group_name: "test".to_string(),
 ///     }
-/// );
+/// ).unwrap();
 /// assert_eq!(row.id, 1);
+/// assert_eq!(row.created_at_raw, "2023-04-29T10:00:00.000Z");
 /// assert_eq!(row.world_instance.world_id, "wrld_1234".to_string());
 /// assert_eq!(row.world_instance.instance_id, "1234".to_string());
 /// assert_eq!(row.world_name, "test");
@@ -34,10 +37,14 @@ use crate::zaphkiel::world_instance::WorldInstance;
 pub struct GamelogLocation {
     pub id: i64,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
     pub world_name: String,
     pub world_instance: WorldInstance,
     pub time: Option<u64>,
     pub group_name: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<Source>,
 }
 
 impl GamelogLocation {
@@ -48,24 +55,34 @@ impl GamelogLocation {
     }
 }
 
-impl From<GamelogLocationRow> for GamelogLocation {
-    /// Convert a `GamelogLocationRow` into a `GamelogLocation`.
+impl GamelogLocation {
+    /// Convert a `GamelogLocationRow`, reading `created_at` as local time in `timezone` if it has
+    /// no offset.
     ///
     /// # What it does
     ///
-    /// * `location` is parsed into a `WorldInstance`.
+    /// * `created_at` is parsed with `parse_timestamp`, and kept as it was in `created_at_raw`.
+    /// * `location` is parsed leniently into a `WorldInstance`, since older VRCX versions wrote
+    ///   malformed tags. A location that can't be parsed at all gives `WorldInstance::default()`.
     /// * `id` is copied.
-    /// * `created_at` is copied.
     /// * `world_name` is copied.
     /// * `time` is copied, but if it is `0` or less, it is set to `None`.
     /// * `group_name` is copied, but if it is empty, it is set to `None`.
-    fn from(row: GamelogLocationRow) -> Self {
-        let world_id = row.location;
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: GamelogLocationRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
         let mut ret = Self::new();
-        ret.world_instance = WorldInstance::from(world_id);
+        ret.created_at = parse_timestamp(&row.created_at, timezone)?;
+        ret.created_at_raw = row.created_at;
+        ret.world_instance =
+            WorldInstance::parse(&row.location, ParseMode::Lenient).unwrap_or_default();
 
         ret.id = row.id;
-        ret.created_at = row.created_at;
         ret.world_name = row.world_name.trim().to_string();
         ret.time = match row.time {
             ..=0 => None,
@@ -76,6 +93,16 @@ impl From<GamelogLocationRow> for GamelogLocation {
             _ => Some(row.group_name),
         };
 
-        ret
+        Ok(ret)
+    }
+}
+
+impl TryFrom<GamelogLocationRow> for GamelogLocation {
+    type Error = TimestampParseError;
+
+    /// Convert a `GamelogLocationRow` into a `GamelogLocation`.
+    /// See `GamelogLocation::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: GamelogLocationRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
     }
 }
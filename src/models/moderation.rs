@@ -0,0 +1,335 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use crate::import::merge::stable_id;
+use crate::models::session::Session;
+use crate::models::source::Source;
+use crate::rows::moderation::ModerationRow;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone};
+
+/// This is a row from the `_moderation` table: a player the account blocked or muted, with the
+/// flags as booleans.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::moderation::Moderation;
+/// use surrealdb_test::rows::moderation::ModerationRow;
+///
+/// let row = Moderation::from(ModerationRow {
+///     user_id: "usr_1234".to_string(),
+///     updated_at: "2023-04-29T10:00:00.000Z".to_string(),
+///     display_name: "test".to_string(),
+///     block: 1,
+///     mute: 0,
+/// });
+/// assert!(row.block);
+/// assert!(!row.mute);
+/// assert!(row.updated_at.is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Moderation {
+    pub user_id: String,
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at_raw: String,
+    pub display_name: String,
+    pub block: bool,
+    pub mute: bool,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl Moderation {
+    /// Create a new `Moderation` by calling `Moderation::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `ModerationRow`, reading `updated_at` as local time in `timezone` if it has no
+    /// offset.
+    ///
+    /// # What it does
+    ///
+    /// * `updated_at` is parsed with `parse_timestamp`, and kept as it was in `updated_at_raw`.
+    ///   If it can't be parsed it is `None`, so the flags are still imported.
+    /// * `block` and `mute` are `true` for anything but `0`.
+    /// * `user_id` and `display_name` are copied.
+    pub fn from_row(row: ModerationRow, timezone: SourceTimezone) -> Self {
+        Moderation {
+            user_id: row.user_id,
+            updated_at: parse_timestamp(&row.updated_at, timezone).ok(),
+            updated_at_raw: row.updated_at,
+            display_name: row.display_name,
+            block: row.block != 0,
+            mute: row.mute != 0,
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl From<ModerationRow> for Moderation {
+    /// Convert a `ModerationRow` into a `Moderation`.
+    /// See `Moderation::from_row`, with the default `SourceTimezone`.
+    fn from(row: ModerationRow) -> Self {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
+
+/// A change to a `Moderation`.
+///
+/// # Available Actions
+/// - Block
+/// - Unblock
+/// - Mute
+/// - Unmute
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    #[default]
+    Block,
+    Unblock,
+    Mute,
+    Unmute,
+}
+
+impl fmt::Display for ModerationAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModerationAction::Block => write!(f, "block"),
+            ModerationAction::Unblock => write!(f, "unblock"),
+            ModerationAction::Mute => write!(f, "mute"),
+            ModerationAction::Unmute => write!(f, "unmute"),
+        }
+    }
+}
+
+/// A player being blocked, unblocked, muted or unmuted, as kept in `moderation_history`.
+///
+/// `_moderation` only holds the current flags, and VRCX drops the row when both are cleared,
+/// so the history is worked out by comparing every sync against the previous one.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use surrealdb_test::models::moderation::{Moderation, ModerationAction, ModerationEvent};
+///
+/// let now = Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap();
+/// let blocked = Moderation {
+///     user_id: "usr_1234".to_string(),
+///     block: true,
+///     ..Default::default()
+/// };
+///
+/// let events = ModerationEvent::diff(&[], &[blocked.clone()], now);
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].action, ModerationAction::Block);
+///
+/// let events = ModerationEvent::diff(&[blocked], &[], now);
+/// assert_eq!(events[0].action, ModerationAction::Unblock);
+/// assert_eq!(events[0].at, now);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct ModerationEvent {
+    pub id: i64,
+    pub user_id: String,
+    pub display_name: String,
+    pub action: ModerationAction,
+    pub at: DateTime<Utc>,
+}
+
+impl ModerationEvent {
+    /// Create a new `ModerationEvent`, keyed by a hash of `user_id`, `action` and `at` so the
+    /// same change is always the same record.
+    pub fn new(
+        user_id: &str,
+        display_name: &str,
+        action: ModerationAction,
+        at: DateTime<Utc>,
+    ) -> Self {
+        ModerationEvent {
+            id: stable_id(&[user_id, &action.to_string(), &at.to_rfc3339()]),
+            user_id: user_id.to_string(),
+            display_name: display_name.to_string(),
+            action,
+            at,
+        }
+    }
+
+    /// The changes that turn the moderation list `before` into `after`.
+    ///
+    /// # What it does
+    ///
+    /// * A player missing from a list has neither flag set.
+    /// * Every flag that differs gives one event, so a player can be unblocked and muted at once.
+    /// * Events happen at the `updated_at` of the new row, or at `now` if it has none or the
+    ///   player was dropped from the list.
+    /// * The events come back oldest first.
+    pub fn diff(before: &[Moderation], after: &[Moderation], now: DateTime<Utc>) -> Vec<Self> {
+        let before = before
+            .iter()
+            .map(|row| (row.user_id.as_str(), row))
+            .collect::<BTreeMap<_, _>>();
+        let after = after
+            .iter()
+            .map(|row| (row.user_id.as_str(), row))
+            .collect::<BTreeMap<_, _>>();
+        let flags =
+            |row: Option<&&Moderation>| row.map_or((false, false), |row| (row.block, row.mute));
+
+        let mut events = Vec::new();
+        for user_id in before.keys().chain(after.keys()).collect::<BTreeSet<_>>() {
+            let (old, new) = (before.get(user_id), after.get(user_id));
+            let (old_block, old_mute) = flags(old);
+            let (new_block, new_mute) = flags(new);
+            let at = new.and_then(|row| row.updated_at).unwrap_or(now);
+            let display_name = new.or(old).map_or("", |row| row.display_name.as_str());
+
+            if old_block != new_block {
+                let action = match new_block {
+                    true => ModerationAction::Block,
+                    false => ModerationAction::Unblock,
+                };
+                events.push(ModerationEvent::new(user_id, display_name, action, at));
+            }
+            if old_mute != new_mute {
+                let action = match new_mute {
+                    true => ModerationAction::Mute,
+                    false => ModerationAction::Unmute,
+                };
+                events.push(ModerationEvent::new(user_id, display_name, action, at));
+            }
+        }
+        events.sort_by(|a, b| (a.at, &a.user_id).cmp(&(b.at, &b.user_id)));
+        events
+    }
+}
+
+/// When a player was blocked, and unblocked if they were.
+type BlockedPeriod = (DateTime<Utc>, Option<DateTime<Utc>>);
+
+/// A session of a player while the account had them blocked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct BlockedEncounter {
+    pub user_id: String,
+    pub display_name: String,
+    pub blocked_at: DateTime<Utc>,
+    pub session: Session,
+}
+
+impl BlockedEncounter {
+    /// Find the sessions that overlap a time a player was blocked.
+    ///
+    /// # What it does
+    ///
+    /// * `history` is walked in `at` order. A block starts a blocked period and the next
+    ///   unblock ends it. A player still blocked stays blocked from then on.
+    /// * A session overlaps a period if it started before the period ended and ended, or
+    ///   started if it never saw a leave, after the period started.
+    /// * Sessions without a `user_id` can't be matched and are skipped.
+    pub fn find(history: &[ModerationEvent], sessions: &[Session]) -> Vec<Self> {
+        let mut history = history.iter().collect::<Vec<_>>();
+        history.sort_by_key(|event| event.at);
+
+        let mut periods: BTreeMap<&str, Vec<BlockedPeriod>> = BTreeMap::new();
+        for event in history {
+            let user_periods = periods.entry(event.user_id.as_str()).or_default();
+            match event.action {
+                ModerationAction::Block => user_periods.push((event.at, None)),
+                ModerationAction::Unblock => {
+                    if let Some((_, until @ None)) = user_periods.last_mut() {
+                        *until = Some(event.at);
+                    }
+                }
+                ModerationAction::Mute | ModerationAction::Unmute => {}
+            }
+        }
+
+        let mut encounters = Vec::new();
+        for session in sessions {
+            let (Some(user_id), Some(started_at)) =
+                (session.user_id.as_deref(), session.started_at())
+            else {
+                continue;
+            };
+            let ended_at = session.left_at.unwrap_or(started_at);
+            let blocked = periods.get(user_id).and_then(|periods| {
+                periods.iter().find(|(from, until)| {
+                    ended_at >= *from && until.is_none_or(|until| started_at < until)
+                })
+            });
+            if let Some((blocked_at, _)) = blocked {
+                encounters.push(BlockedEncounter {
+                    user_id: user_id.to_string(),
+                    display_name: session.display_name.clone(),
+                    blocked_at: *blocked_at,
+                    session: session.clone(),
+                });
+            }
+        }
+        encounters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Timelike, Utc};
+
+    use crate::models::moderation::{
+        BlockedEncounter, Moderation, ModerationAction, ModerationEvent,
+    };
+    use crate::models::session::Session;
+
+    fn moderation(block: bool, mute: bool, hour: u32) -> Moderation {
+        Moderation {
+            user_id: "usr_a".to_string(),
+            updated_at: Some(Utc.with_ymd_and_hms(2023, 4, 29, hour, 0, 0).unwrap()),
+            updated_at_raw: String::new(),
+            display_name: "a".to_string(),
+            block,
+            mute,
+            sources: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_keeps_every_flag_change() {
+        let now = Utc.with_ymd_and_hms(2023, 4, 30, 0, 0, 0).unwrap();
+        let events = ModerationEvent::diff(
+            &[moderation(true, false, 10)],
+            &[moderation(false, true, 12)],
+            now,
+        );
+        let actions = events.iter().map(|event| event.action).collect::<Vec<_>>();
+        assert_eq!(actions, [ModerationAction::Unblock, ModerationAction::Mute]);
+        assert!(events.iter().all(|event| event.at.hour() == 12));
+
+        let same = [moderation(true, false, 10)];
+        assert!(ModerationEvent::diff(&same, &same, now).is_empty());
+    }
+
+    #[test]
+    fn test_encounters_only_while_blocked() {
+        let at = |hour| Utc.with_ymd_and_hms(2023, 4, 29, hour, 0, 0).unwrap();
+        let history = [
+            ModerationEvent::new("usr_a", "a", ModerationAction::Block, at(10)),
+            ModerationEvent::new("usr_a", "a", ModerationAction::Unblock, at(12)),
+        ];
+        let session = |hour| Session {
+            display_name: "a".to_string(),
+            user_id: Some("usr_a".to_string()),
+            joined_at: Some(at(hour)),
+            left_at: Some(at(hour) + Duration::minutes(30)),
+            ..Default::default()
+        };
+        let encounters = BlockedEncounter::find(&history, &[session(9), session(11), session(13)]);
+        assert_eq!(encounters.len(), 1);
+        assert_eq!(encounters[0].session.joined_at, Some(at(11)));
+        assert_eq!(encounters[0].blocked_at, at(10));
+    }
+}
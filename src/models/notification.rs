@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::source::Source;
+use crate::rows::notification::NotificationRow;
+use crate::zaphkiel::location::Location;
+use crate::zaphkiel::notification_kind::NotificationKind;
+use crate::zaphkiel::text::non_empty;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseError};
+use crate::zaphkiel::world_instance::ParseMode;
+
+/// This is a row from the `_notifications` table: a friend request, invite or other
+/// notification `sender_user_id` sent to `receiver_user_id`.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::notification::Notification;
+/// use surrealdb_test::rows::notification::NotificationRow;
+/// use surrealdb_test::zaphkiel::notification_kind::NotificationKind;
+///
+/// let row = Notification::try_from(NotificationRow {
+///     id: "not_1234".to_string(),
+///     created_at: "2023-04-29T10:00:00.000Z".to_string(),
+///     kind: "invite".to_string(),
+///     sender_user_id: "usr_1234".to_string(),
+///     sender_username: "test".to_string(),
+///     receiver_user_id: "usr_5678".to_string(),
+///     world_id: "wrld_1234:1234~region(eu)".to_string(),
+///     world_name: "test".to_string(),
+///     ..Default::default()
+/// }).unwrap();
+/// assert_eq!(row.kind, NotificationKind::Invite);
+/// assert_eq!(row.location.world_instance().unwrap().world_id, "wrld_1234");
+/// assert_eq!(row.world_name.as_deref(), Some("test"));
+/// assert_eq!(row.message, None);
+/// assert!(!row.expired);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Notification {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    pub kind: NotificationKind,
+    pub sender_user_id: String,
+    pub sender_username: String,
+    pub receiver_user_id: String,
+    pub message: Option<String>,
+    /// The location an invite is for, from `world_id`.
+    pub location: Location,
+    pub world_name: Option<String>,
+    pub image_url: Option<String>,
+    pub invite_message: Option<String>,
+    pub request_message: Option<String>,
+    pub response_message: Option<String>,
+    pub expired: bool,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl Notification {
+    /// Create a new `Notification` by calling `Notification::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `NotificationRow`, reading `created_at` as local time in `timezone` if it has
+    /// no offset.
+    ///
+    /// # What it does
+    ///
+    /// * `created_at` is parsed with `parse_timestamp`, and kept as it was in `created_at_raw`.
+    /// * `kind` is parsed into a `NotificationKind`.
+    /// * `world_id` holds the whole location of an invite, and is parsed leniently into
+    ///   `location`. Notifications that aren't invites have `Location::Unknown`.
+    /// * `expired` is `true` for anything but `0`.
+    /// * Empty text columns are set to `None`.
+    ///
+    /// # Errors
+    ///
+    /// If `created_at` can't be parsed.
+    pub fn from_row(
+        row: NotificationRow,
+        timezone: SourceTimezone,
+    ) -> Result<Self, TimestampParseError> {
+        Ok(Notification {
+            id: row.id,
+            created_at: parse_timestamp(&row.created_at, timezone)?,
+            created_at_raw: row.created_at,
+            kind: NotificationKind::from(row.kind),
+            sender_user_id: row.sender_user_id,
+            sender_username: row.sender_username,
+            receiver_user_id: row.receiver_user_id,
+            message: non_empty(row.message),
+            location: Location::parse(&row.world_id, ParseMode::Lenient),
+            world_name: non_empty(row.world_name),
+            image_url: non_empty(row.image_url),
+            invite_message: non_empty(row.invite_message),
+            request_message: non_empty(row.request_message),
+            response_message: non_empty(row.response_message),
+            expired: row.expired != 0,
+            sources: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<NotificationRow> for Notification {
+    type Error = TimestampParseError;
+
+    /// Convert a `NotificationRow` into a `Notification`.
+    /// See `Notification::from_row`, with the default `SourceTimezone`.
+    fn try_from(row: NotificationRow) -> Result<Self, Self::Error> {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
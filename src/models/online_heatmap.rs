@@ -0,0 +1,223 @@
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
+
+use crate::models::feed_online_offline::FeedOnlineOffline;
+use crate::zaphkiel::online_offline_event::OnlineOfflineEvent;
+use crate::zaphkiel::timestamp::SourceTimezone;
+
+/// How long a friend was online in every hour of the week, built from `_feed_online_offline`
+/// rows.
+///
+/// # Values
+///
+/// - `user_id` - The friend.
+/// - `timezone` - The timezone the weekdays and hours are in.
+/// - `minutes` - Minutes online, indexed by weekday, Monday first, then by hour.
+/// - `sessions` - How many online periods were counted.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc, Weekday};
+/// use surrealdb_test::models::feed_online_offline::FeedOnlineOffline;
+/// use surrealdb_test::models::online_heatmap::OnlineHeatmap;
+/// use surrealdb_test::zaphkiel::online_offline_event::OnlineOfflineEvent;
+/// use surrealdb_test::zaphkiel::timestamp::SourceTimezone;
+///
+/// let event = |event, hour, minute| FeedOnlineOffline {
+///     created_at: Utc.with_ymd_and_hms(2023, 4, 29, hour, minute, 0).unwrap(),
+///     event,
+///     user_id: "usr_1234".to_string(),
+///     ..Default::default()
+/// };
+/// let events = [
+///     event(OnlineOfflineEvent::Online, 20, 30),
+///     event(OnlineOfflineEvent::Offline, 22, 0),
+/// ];
+///
+/// let heatmap = OnlineHeatmap::from_events("usr_1234", &events, SourceTimezone::Utc);
+/// assert_eq!(heatmap.sessions, 1);
+/// assert_eq!(heatmap.minutes[5][20], 30);
+/// assert_eq!(heatmap.minutes[5][21], 60);
+/// assert_eq!(heatmap.total(), 90);
+/// assert_eq!(heatmap.peak(), Some((Weekday::Sat, 21)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct OnlineHeatmap {
+    pub user_id: String,
+    pub timezone: SourceTimezone,
+    pub minutes: [[u64; 24]; 7],
+    pub sessions: u64,
+}
+
+impl OnlineHeatmap {
+    /// Create a new `OnlineHeatmap` by calling `OnlineHeatmap::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add up the online periods of `user_id` in `events`.
+    ///
+    /// # What it does
+    ///
+    /// * Events of other players are skipped, the rest are sorted by `created_at`.
+    /// * An `Online` event starts a period, and the next `Offline` event ends it.
+    /// * An `Offline` event without an `Online` before it starts its period `time` before it,
+    ///   if it has a `time`. Otherwise it is skipped.
+    /// * A period still open after the last event is not counted.
+    /// * Every period is split over the weekdays and hours it covers in `timezone`.
+    pub fn from_events(
+        user_id: &str,
+        events: &[FeedOnlineOffline],
+        timezone: SourceTimezone,
+    ) -> Self {
+        let mut events = events
+            .iter()
+            .filter(|event| event.user_id == user_id)
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| event.created_at);
+
+        let mut seconds = [[0u64; 24]; 7];
+        let mut sessions = 0;
+        let mut online_since = None;
+        for event in events {
+            match event.event {
+                OnlineOfflineEvent::Online => online_since = Some(event.created_at),
+                OnlineOfflineEvent::Offline => {
+                    let started_at = online_since.take().or_else(|| {
+                        event
+                            .time
+                            .map(|time| event.created_at - Duration::milliseconds(time as i64))
+                    });
+                    if let Some(started_at) = started_at {
+                        if started_at < event.created_at {
+                            spread(&mut seconds, started_at, event.created_at, timezone);
+                            sessions += 1;
+                        }
+                    }
+                }
+                OnlineOfflineEvent::Other => {}
+            }
+        }
+
+        OnlineHeatmap {
+            user_id: user_id.to_string(),
+            timezone,
+            minutes: seconds.map(|day| day.map(|seconds| seconds / 60)),
+            sessions,
+        }
+    }
+
+    /// The minutes online across the whole week.
+    pub fn total(&self) -> u64 {
+        self.minutes.iter().flatten().sum()
+    }
+
+    /// The weekday and hour with the most minutes online, the earliest one on a tie, or `None`
+    /// if the friend was never seen online.
+    pub fn peak(&self) -> Option<(Weekday, u32)> {
+        let mut peak = None;
+        let mut most = 0;
+        for (day, hours) in self.minutes.iter().enumerate() {
+            for (hour, &minutes) in hours.iter().enumerate() {
+                if minutes > most {
+                    most = minutes;
+                    peak = Some((weekday(day), hour as u32));
+                }
+            }
+        }
+        peak
+    }
+}
+
+/// Add the seconds between `from` and `to` to the weekday and hour cells they fall in.
+fn spread(
+    seconds: &mut [[u64; 24]; 7],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    timezone: SourceTimezone,
+) {
+    let mut at = from;
+    while at < to {
+        let local = timezone.local(&at);
+        let into_hour = (local.minute() * 60 + local.second()) as i64;
+        let next = (at + Duration::seconds(3600 - into_hour)).min(to);
+        let day = local.weekday().num_days_from_monday() as usize;
+        seconds[day][local.hour() as usize] += (next - at).num_seconds() as u64;
+        at = next;
+    }
+}
+
+fn weekday(day: usize) -> Weekday {
+    match day {
+        0 => Weekday::Mon,
+        1 => Weekday::Tue,
+        2 => Weekday::Wed,
+        3 => Weekday::Thu,
+        4 => Weekday::Fri,
+        5 => Weekday::Sat,
+        _ => Weekday::Sun,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::models::feed_online_offline::FeedOnlineOffline;
+    use crate::models::online_heatmap::OnlineHeatmap;
+    use crate::zaphkiel::online_offline_event::OnlineOfflineEvent;
+    use crate::zaphkiel::timestamp::SourceTimezone;
+
+    fn event(event: OnlineOfflineEvent, minutes: i64, time: Option<u64>) -> FeedOnlineOffline {
+        FeedOnlineOffline {
+            created_at: Utc.with_ymd_and_hms(2023, 4, 30, 23, 0, 0).unwrap()
+                + Duration::minutes(minutes),
+            event,
+            user_id: "usr_1234".to_string(),
+            time,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_offline_without_online_uses_time() {
+        let events = [event(OnlineOfflineEvent::Offline, 30, Some(3_600_000))];
+        let heatmap = OnlineHeatmap::from_events("usr_1234", &events, SourceTimezone::Utc);
+        assert_eq!(heatmap.sessions, 1);
+        // Sunday 22:30 to 23:30.
+        assert_eq!(heatmap.minutes[6][22], 30);
+        assert_eq!(heatmap.minutes[6][23], 30);
+        assert_eq!(heatmap.total(), 60);
+    }
+
+    #[test]
+    fn test_periods_cross_midnight_in_timezone() {
+        let events = [
+            event(OnlineOfflineEvent::Online, 0, None),
+            event(OnlineOfflineEvent::Offline, 120, Some(7_200_000)),
+            event(OnlineOfflineEvent::Online, 180, None),
+        ];
+        let tokyo = "Asia/Tokyo".parse().unwrap();
+        let heatmap = OnlineHeatmap::from_events("usr_1234", &events, tokyo);
+        // Sunday 23:00 UTC is Monday 08:00 in Tokyo. The last period is still open.
+        assert_eq!(heatmap.sessions, 1);
+        assert_eq!(heatmap.minutes[0][8], 60);
+        assert_eq!(heatmap.minutes[0][9], 60);
+        assert_eq!(heatmap.total(), 120);
+
+        let heatmap = OnlineHeatmap::from_events("usr_1234", &events, SourceTimezone::Utc);
+        assert_eq!(heatmap.minutes[6][23], 60);
+        assert_eq!(heatmap.minutes[0][0], 60);
+    }
+
+    #[test]
+    fn test_other_players_are_skipped() {
+        let events = [
+            event(OnlineOfflineEvent::Online, 0, None),
+            event(OnlineOfflineEvent::Offline, 60, None),
+        ];
+        let heatmap = OnlineHeatmap::from_events("usr_5678", &events, SourceTimezone::Utc);
+        assert_eq!(heatmap.sessions, 0);
+        assert_eq!(heatmap.peak(), None);
+    }
+}
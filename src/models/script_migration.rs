@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+
+/// A migration script that has been run, as `surrealdb-migrations` records it in the
+/// `script_migration` table.
+///
+/// # Values
+///
+/// - `script_name` - The file name of the script in `migrations/`, without `.surql`.
+/// - `executed_at` - When the script was run.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    Default,
+)]
+pub struct ScriptMigration {
+    pub script_name: String,
+    pub executed_at: DateTime<Utc>,
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::zaphkiel::join_leave_event::JoinLeaveEvent;
+use crate::zaphkiel::location::Location;
+
+/// A stretch of time a player spent in a world instance, built by pairing `gamelog_join_leave`
+/// join and leave events.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{Duration, TimeZone, Utc};
+/// use surrealdb_test::models::gamelog_join_leave::GamelogJoinLeave;
+/// use surrealdb_test::models::session::Session;
+/// use surrealdb_test::zaphkiel::join_leave_event::JoinLeaveEvent;
+///
+/// let joined_at = Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap();
+/// let join = GamelogJoinLeave {
+///     id: 1,
+///     created_at: joined_at,
+///     event: JoinLeaveEvent::Join,
+///     display_name: "test".to_string(),
+///     ..Default::default()
+/// };
+/// let leave = GamelogJoinLeave {
+///     id: 2,
+///     created_at: joined_at + Duration::minutes(5),
+///     event: JoinLeaveEvent::Leave,
+///     display_name: "test".to_string(),
+///     time: Some(300_000),
+///     ..Default::default()
+/// };
+///
+/// let sessions = Session::from_join_leave(&[join, leave]);
+/// assert_eq!(sessions.len(), 1);
+/// assert_eq!(sessions[0].joined_at, Some(joined_at));
+/// assert_eq!(sessions[0].duration, Some(300_000));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Session {
+    pub display_name: String,
+    pub user_id: Option<String>,
+    pub location: Location,
+    pub joined_at: Option<DateTime<Utc>>,
+    pub left_at: Option<DateTime<Utc>>,
+    pub duration: Option<u64>,
+}
+
+impl Session {
+    /// Create a new `Session` by calling `Session::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The start of the session, falling back to the leave time.
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.joined_at.or(self.left_at)
+    }
+
+    /// Pair up join and leave events into sessions.
+    ///
+    /// # What it does
+    ///
+    /// * The rows are walked in `created_at` order.
+    /// * A join opens a session for its `display_name` and `location`.
+    /// * A leave closes the open session with the same `display_name` and `location`. If there
+    ///   is none, a session is made from the leave alone, with `joined_at` worked out from `time`.
+    /// * `duration` is the leave's `time` in milliseconds, or the gap between join and leave.
+    /// * Sessions that never see a leave are kept with `left_at` set to `None`.
+    pub fn from_join_leave(rows: &[GamelogJoinLeave]) -> Vec<Session> {
+        let mut rows = rows.iter().collect::<Vec<_>>();
+        rows.sort_by_key(|row| (row.created_at, row.id));
+
+        let mut open: HashMap<(&str, &Location), Session> = HashMap::new();
+        let mut sessions = Vec::new();
+
+        for row in rows {
+            let key = (row.display_name.as_str(), &row.location);
+            match row.event {
+                JoinLeaveEvent::Join => {
+                    let session = Session {
+                        display_name: row.display_name.clone(),
+                        user_id: row.user_id.clone(),
+                        location: row.location.clone(),
+                        joined_at: Some(row.created_at),
+                        left_at: None,
+                        duration: None,
+                    };
+                    if let Some(unfinished) = open.insert(key, session) {
+                        sessions.push(unfinished);
+                    }
+                }
+                JoinLeaveEvent::Leave => {
+                    let mut session = open.remove(&key).unwrap_or_else(|| Session {
+                        display_name: row.display_name.clone(),
+                        location: row.location.clone(),
+                        joined_at: row
+                            .time
+                            .map(|time| row.created_at - Duration::milliseconds(time as i64)),
+                        ..Session::new()
+                    });
+                    session.user_id = session.user_id.or_else(|| row.user_id.clone());
+                    session.left_at = Some(row.created_at);
+                    session.duration = row.time.or_else(|| {
+                        session
+                            .joined_at
+                            .map(|joined_at| (row.created_at - joined_at).num_milliseconds())
+                            .filter(|duration| *duration >= 0)
+                            .map(|duration| duration as u64)
+                    });
+                    sessions.push(session);
+                }
+                JoinLeaveEvent::Other => {}
+            }
+        }
+
+        sessions.extend(open.into_values());
+        sessions.sort_by(|a, b| {
+            (a.started_at(), &a.display_name).cmp(&(b.started_at(), &b.display_name))
+        });
+        sessions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::vrcx::{Fixture, FixtureOptions};
+    use crate::models::gamelog_join_leave::GamelogJoinLeave;
+    use crate::models::session::Session;
+    use crate::zaphkiel::join_leave_event::JoinLeaveEvent;
+
+    #[test]
+    fn test_sessions_pair_every_join() {
+        let fixture = Fixture::generate(&FixtureOptions::default());
+        let rows = fixture
+            .join_leave
+            .into_iter()
+            .map(GamelogJoinLeave::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let joins = rows
+            .iter()
+            .filter(|row| row.event == JoinLeaveEvent::Join)
+            .count();
+
+        let sessions = Session::from_join_leave(&rows);
+        assert_eq!(sessions.len(), joins);
+        for session in &sessions {
+            let (joined_at, left_at) = (session.joined_at.unwrap(), session.left_at.unwrap());
+            assert!(joined_at < left_at);
+            assert_eq!(
+                session.duration,
+                Some((left_at - joined_at).num_milliseconds() as u64)
+            );
+        }
+    }
+}
@@ -0,0 +1,26 @@
+/// Where an imported record came from.
+///
+/// # Values
+///
+/// - `file` - The path of the `vrcx.sqlite` file, as given to the importer.
+/// - `account` - The VRChat account the record belongs to, as VRCX prefixes its tables, e.g.
+///   `usr1234abcd` for `usr_1234-abcd`. `None` for shared tables in a file with several accounts.
+///
+/// Sources are ordered by `file` and then `account`, which is how merge conflicts are broken
+/// when nothing else tells the rows apart.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    Default,
+)]
+pub struct Source {
+    pub file: String,
+    pub account: Option<String>,
+}
@@ -0,0 +1,175 @@
+use std::fmt;
+use std::str::FromStr;
+
+use surrealdb::{Connection, Surreal};
+
+/// How VRChat accounts are laid out in SurrealDB.
+///
+/// # Available Variants
+/// - Database: every account gets a database named after it in the configured namespace.
+/// - Namespace: every account gets a namespace named after it, holding the configured database.
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::models::tenant::Tenancy;
+///
+/// assert_eq!("namespace".parse::<Tenancy>().unwrap(), Tenancy::Namespace);
+/// assert_eq!(Tenancy::default(), Tenancy::Database);
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Tenancy {
+    #[default]
+    Database,
+    Namespace,
+}
+
+impl FromStr for Tenancy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "database" => Ok(Tenancy::Database),
+            "namespace" => Ok(Tenancy::Namespace),
+            _ => Err(format!("Unknown tenancy: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Tenancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tenancy::Database => write!(f, "database"),
+            Tenancy::Namespace => write!(f, "namespace"),
+        }
+    }
+}
+
+/// A SurrealDB namespace and database to read and write.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Tenant {
+    pub ns: String,
+    pub db: String,
+}
+
+impl Tenant {
+    /// Point `db` at this namespace and database.
+    ///
+    /// Clones of a `Surreal` share their connection, so this moves every clone.
+    pub async fn select<C: Connection>(&self, db: &Surreal<C>) -> surrealdb::Result<()> {
+        db.use_ns(&self.ns).use_db(&self.db).await
+    }
+}
+
+impl fmt::Display for Tenant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.ns, self.db)
+    }
+}
+
+/// Where every account's data lives.
+///
+/// # Values
+///
+/// - `tenancy` - Whether accounts get a database or a namespace each.
+/// - `ns` - The configured namespace.
+/// - `db` - The configured database. Data that belongs to no account goes to `ns/db`.
+/// - `shared` - The database holding what every account shares, such as the `world` and
+///   `avatar` caches. With `Tenancy::Namespace` it is a namespace holding `db`.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::tenant::{Tenancy, TenantLayout};
+///
+/// let layout = TenantLayout {
+///     tenancy: Tenancy::Database,
+///     ns: "vrcx".to_string(),
+///     db: "vrcx".to_string(),
+///     shared: "shared".to_string(),
+/// };
+/// assert_eq!(layout.tenant(Some("usr_1234-abcd")).to_string(), "vrcx/usr1234abcd");
+/// assert_eq!(layout.tenant(None).to_string(), "vrcx/vrcx");
+/// assert_eq!(layout.shared().to_string(), "vrcx/shared");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct TenantLayout {
+    pub tenancy: Tenancy,
+    pub ns: String,
+    pub db: String,
+    pub shared: String,
+}
+
+impl TenantLayout {
+    /// The tenant of `account`, given either as a user id or as VRCX prefixes its tables.
+    /// `None` is the configured `ns/db`.
+    pub fn tenant(&self, account: Option<&str>) -> Tenant {
+        match account {
+            Some(account) => self.scoped(account_prefix(account)),
+            None => Tenant {
+                ns: self.ns.clone(),
+                db: self.db.clone(),
+            },
+        }
+    }
+
+    /// The tenant every account shares.
+    pub fn shared(&self) -> Tenant {
+        self.scoped(self.shared.clone())
+    }
+
+    fn scoped(&self, name: String) -> Tenant {
+        match self.tenancy {
+            Tenancy::Database => Tenant {
+                ns: self.ns.clone(),
+                db: name,
+            },
+            Tenancy::Namespace => Tenant {
+                ns: name,
+                db: self.db.clone(),
+            },
+        }
+    }
+}
+
+/// The prefix VRCX puts on an account's tables: its user id without `_` or `-`.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::tenant::account_prefix;
+///
+/// assert_eq!(account_prefix("usr_1234-abcd"), "usr1234abcd");
+/// assert_eq!(account_prefix("usr1234abcd"), "usr1234abcd");
+/// ```
+pub fn account_prefix(user_id: &str) -> String {
+    user_id.replace(['_', '-'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::tenant::{Tenancy, TenantLayout};
+
+    #[test]
+    fn test_namespace_tenancy() {
+        let layout = TenantLayout {
+            tenancy: Tenancy::Namespace,
+            ns: "vrcx".to_string(),
+            db: "vrcx".to_string(),
+            shared: "shared".to_string(),
+        };
+        assert_eq!(layout.tenant(Some("usr_1234")).to_string(), "usr1234/vrcx");
+        assert_eq!(layout.tenant(None).to_string(), "vrcx/vrcx");
+        assert_eq!(layout.shared().to_string(), "shared/vrcx");
+    }
+
+    #[test]
+    fn test_tenancy_round_trips() {
+        for tenancy in [Tenancy::Database, Tenancy::Namespace] {
+            assert_eq!(tenancy.to_string().parse::<Tenancy>(), Ok(tenancy));
+        }
+        assert!("table".parse::<Tenancy>().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::source::Source;
 use crate::rows::usr_friend_log_current::UsrFriendLogCurrentRow;
 use crate::zaphkiel::trust_level::TrustLevel;
 
@@ -27,6 +28,8 @@ pub struct UsrFriendLogCurrent {
     pub user_id: String,
     pub display_name: String,
     pub trust_level: TrustLevel,
+    #[serde(default)]
+    pub sources: Vec<Source>,
 }
 
 impl UsrFriendLogCurrent {
@@ -42,12 +45,13 @@ impl From<UsrFriendLogCurrentRow> for UsrFriendLogCurrent {
     /// # What it does
     ///
     /// It converts the `trust_level` field from a `String` into a `TrustLevel` enum.
-    /// The `user_id` and `display_name` fields are copied as is.
+    /// The `user_id` and `display_name` fields are copied as is. `sources` are left for the importer.
     fn from(row: UsrFriendLogCurrentRow) -> Self {
         Self {
             user_id: row.user_id,
             display_name: row.display_name,
             trust_level: TrustLevel::from(row.trust_level),
+            sources: Vec::new(),
         }
     }
 }
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::gamelog_location::GamelogLocation;
+use crate::models::source::Source;
+use crate::rows::cache_world::CacheWorldRow;
+use crate::zaphkiel::release_status::ReleaseStatus;
+use crate::zaphkiel::text::non_empty;
+use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone};
+
+/// This is a row from the `cache_world` table, stored as the `world` record keyed by its world
+/// id, e.g. `world:wrld_1234`. `GamelogLocation::world_instance.world_id` links to it.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::world::World;
+/// use surrealdb_test::rows::cache_world::CacheWorldRow;
+/// use surrealdb_test::zaphkiel::release_status::ReleaseStatus;
+///
+/// let world = World::from(CacheWorldRow {
+///     id: "wrld_1234".to_string(),
+///     author_name: "Some User".to_string(),
+///     name: " Test World ".to_string(),
+///     release_status: "public".to_string(),
+///     updated_at: "2023-04-29T10:00:00.000Z".to_string(),
+///     version: 3,
+///     ..Default::default()
+/// });
+/// assert_eq!(world.id, "wrld_1234");
+/// assert_eq!(world.name, "Test World");
+/// assert_eq!(world.author_id, None);
+/// assert_eq!(world.author_name.as_deref(), Some("Some User"));
+/// assert_eq!(world.release_status, ReleaseStatus::Public);
+/// assert!(world.updated_at.is_some());
+/// assert_eq!(world.created_at, None);
+/// assert_eq!(world.version, 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct World {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub author_id: Option<String>,
+    pub author_name: Option<String>,
+    pub release_status: ReleaseStatus,
+    pub image_url: Option<String>,
+    pub thumbnail_image_url: Option<String>,
+    pub version: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub created_at_raw: String,
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at_raw: String,
+    /// When VRCX cached the world.
+    pub added_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub added_at_raw: String,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+impl World {
+    /// Create a new `World` by calling `World::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a `CacheWorldRow`, reading timestamps without an offset as local time in
+    /// `timezone`.
+    ///
+    /// # What it does
+    ///
+    /// * `id`, `name` and `version` are copied, with `name` trimmed.
+    /// * Empty text columns are set to `None`.
+    /// * `release_status` is parsed into a `ReleaseStatus`.
+    /// * Timestamps are parsed with `parse_timestamp`. The cache is only used to enrich other
+    ///   records, so a timestamp that can't be parsed is set to `None` instead of rejecting the
+    ///   row.
+    /// * Every timestamp is also kept as it was, e.g. `created_at` in `created_at_raw`.
+    pub fn from_row(row: CacheWorldRow, timezone: SourceTimezone) -> Self {
+        World {
+            id: row.id,
+            name: row.name.trim().to_string(),
+            description: non_empty(row.description),
+            author_id: non_empty(row.author_id),
+            author_name: non_empty(row.author_name),
+            release_status: ReleaseStatus::from(row.release_status),
+            image_url: non_empty(row.image_url),
+            thumbnail_image_url: non_empty(row.thumbnail_image_url),
+            version: row.version,
+            created_at: parse_timestamp(&row.created_at, timezone).ok(),
+            created_at_raw: row.created_at,
+            updated_at: parse_timestamp(&row.updated_at, timezone).ok(),
+            updated_at_raw: row.updated_at,
+            added_at: parse_timestamp(&row.added_at, timezone).ok(),
+            added_at_raw: row.added_at,
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl From<CacheWorldRow> for World {
+    /// Convert a `CacheWorldRow` into a `World`.
+    /// See `World::from_row`, with the default `SourceTimezone`.
+    fn from(row: CacheWorldRow) -> Self {
+        Self::from_row(row, SourceTimezone::default())
+    }
+}
+
+/// A visit with the cached `World` it was to, if there is one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct EnrichedLocation {
+    #[serde(flatten)]
+    pub location: GamelogLocation,
+    pub world: Option<World>,
+}
+
+impl EnrichedLocation {
+    /// Pair every location with the world of its `world_instance.world_id`, keeping their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use surrealdb_test::models::gamelog_location::GamelogLocation;
+    /// use surrealdb_test::models::world::{EnrichedLocation, World};
+    ///
+    /// let visit = |world_id: &str| GamelogLocation {
+    ///     world_instance: format!("{}:1", world_id).parse().unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// let worlds = [World {
+    ///     id: "wrld_1234".to_string(),
+    ///     author_name: Some("Some User".to_string()),
+    ///     ..Default::default()
+    /// }];
+    ///
+    /// let enriched = EnrichedLocation::join(vec![visit("wrld_1234"), visit("wrld_5678")], &worlds);
+    /// assert_eq!(enriched[0].author_name(), Some("Some User"));
+    /// assert_eq!(enriched[1].world, None);
+    /// ```
+    pub fn join(locations: Vec<GamelogLocation>, worlds: &[World]) -> Vec<EnrichedLocation> {
+        let worlds = by_id(worlds);
+        locations
+            .into_iter()
+            .map(|location| EnrichedLocation {
+                world: worlds
+                    .get(location.world_instance.world_id.as_str())
+                    .map(|world| (*world).clone()),
+                location,
+            })
+            .collect()
+    }
+
+    /// The author of the world, if it is cached.
+    pub fn author_name(&self) -> Option<&str> {
+        self.world.as_ref()?.author_name.as_deref()
+    }
+
+    /// The release status of the world, `ReleaseStatus::Unknown` if it isn't cached.
+    pub fn release_status(&self) -> ReleaseStatus {
+        self.world
+            .as_ref()
+            .map_or(ReleaseStatus::Unknown, |world| world.release_status)
+    }
+}
+
+/// A world that was visited but isn't in the world cache.
+///
+/// # Values
+///
+/// - `world_id` - The world's id.
+/// - `world_name` - The name seen on the latest visit.
+/// - `visits` - How many visits there were.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct MissingWorld {
+    pub world_id: String,
+    pub world_name: String,
+    pub visits: u64,
+}
+
+impl MissingWorld {
+    /// The worlds `locations` visited that aren't in `worlds`, most visited first.
+    ///
+    /// Locations without a world id, such as `offline`, are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use surrealdb_test::models::gamelog_location::GamelogLocation;
+    /// use surrealdb_test::models::world::{MissingWorld, World};
+    ///
+    /// let visit = |world_id: &str| GamelogLocation {
+    ///     world_name: "test".to_string(),
+    ///     world_instance: format!("{}:1", world_id).parse().unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// let worlds = [World {
+    ///     id: "wrld_1234".to_string(),
+    ///     ..Default::default()
+    /// }];
+    ///
+    /// let locations = [visit("wrld_1234"), visit("wrld_5678"), visit("wrld_5678")];
+    /// let missing = MissingWorld::from_locations(&locations, &worlds);
+    /// assert_eq!(missing.len(), 1);
+    /// assert_eq!(missing[0].world_id, "wrld_5678");
+    /// assert_eq!(missing[0].visits, 2);
+    /// ```
+    pub fn from_locations(locations: &[GamelogLocation], worlds: &[World]) -> Vec<MissingWorld> {
+        let cached = by_id(worlds);
+        let mut missing: HashMap<&str, (MissingWorld, DateTime<Utc>)> = HashMap::new();
+
+        for location in locations {
+            let world_id = location.world_instance.world_id.as_str();
+            if world_id.is_empty() || cached.contains_key(world_id) {
+                continue;
+            }
+            let (world, last_visit) = missing.entry(world_id).or_insert_with(|| {
+                (
+                    MissingWorld {
+                        world_id: world_id.to_string(),
+                        ..Default::default()
+                    },
+                    location.created_at,
+                )
+            });
+            world.visits += 1;
+            if world.world_name.is_empty() || location.created_at >= *last_visit {
+                world.world_name = location.world_name.clone();
+                *last_visit = location.created_at;
+            }
+        }
+
+        let mut missing = missing
+            .into_values()
+            .map(|(world, _)| world)
+            .collect::<Vec<_>>();
+        missing.sort_by(|a, b| {
+            b.visits
+                .cmp(&a.visits)
+                .then_with(|| a.world_id.cmp(&b.world_id))
+        });
+        missing
+    }
+}
+
+fn by_id(worlds: &[World]) -> HashMap<&str, &World> {
+    worlds
+        .iter()
+        .map(|world| (world.id.as_str(), world))
+        .collect()
+}
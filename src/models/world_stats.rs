@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::gamelog_location::GamelogLocation;
+
+/// Visit statistics for one world, built from `gamelog_location` rows.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::models::gamelog_location::GamelogLocation;
+/// use surrealdb_test::models::world_stats::WorldStats;
+///
+/// let visit = |instance: &str, time: u64| GamelogLocation {
+///     world_name: "test".to_string(),
+///     world_instance: format!("wrld_1234:{}", instance).parse().unwrap(),
+///     time: Some(time),
+///     ..Default::default()
+/// };
+///
+/// let stats = WorldStats::from_locations(&[visit("1", 1000), visit("2", 500)]);
+/// assert_eq!(stats.len(), 1);
+/// assert_eq!(stats[0].world_id, "wrld_1234");
+/// assert_eq!(stats[0].visits, 2);
+/// assert_eq!(stats[0].instances, 2);
+/// assert_eq!(stats[0].total_time, 1500);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct WorldStats {
+    pub world_id: String,
+    pub world_name: String,
+    pub visits: u64,
+    pub instances: u64,
+    pub total_time: u64,
+    pub first_visit: Option<DateTime<Utc>>,
+    pub last_visit: Option<DateTime<Utc>>,
+}
+
+impl WorldStats {
+    /// Create a new `WorldStats` by calling `WorldStats::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Group `locations` by world.
+    ///
+    /// # What it does
+    ///
+    /// * `visits` counts the rows, `instances` the distinct instance ids.
+    /// * `total_time` adds up `time`, in milliseconds.
+    /// * `world_name` is the name seen on the latest visit.
+    /// * The result is sorted by `visits`, most visited first.
+    pub fn from_locations(locations: &[GamelogLocation]) -> Vec<WorldStats> {
+        let mut worlds: HashMap<&str, (WorldStats, Vec<&str>)> = HashMap::new();
+
+        for location in locations {
+            let world_id = location.world_instance.world_id.as_str();
+            let (stats, instances) = worlds.entry(world_id).or_insert_with(|| {
+                (
+                    WorldStats {
+                        world_id: world_id.to_string(),
+                        ..WorldStats::new()
+                    },
+                    Vec::new(),
+                )
+            });
+
+            stats.visits += 1;
+            stats.total_time += location.time.unwrap_or_default();
+            if stats
+                .last_visit
+                .is_none_or(|last| location.created_at >= last)
+            {
+                stats.world_name = location.world_name.clone();
+                stats.last_visit = Some(location.created_at);
+            }
+            if stats
+                .first_visit
+                .is_none_or(|first| location.created_at < first)
+            {
+                stats.first_visit = Some(location.created_at);
+            }
+            if !instances.contains(&location.world_instance.instance_id.as_str()) {
+                instances.push(&location.world_instance.instance_id);
+            }
+        }
+
+        let mut stats = worlds
+            .into_values()
+            .map(|(mut stats, instances)| {
+                stats.instances = instances.len() as u64;
+                stats
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| b.visits.cmp(&a.visits).then(a.world_id.cmp(&b.world_id)));
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::vrcx::{Fixture, FixtureOptions};
+    use crate::models::gamelog_location::GamelogLocation;
+    use crate::models::world_stats::WorldStats;
+
+    #[test]
+    fn test_world_stats_count_every_visit() {
+        let fixture = Fixture::generate(&FixtureOptions::default());
+        let locations = fixture
+            .locations
+            .iter()
+            .cloned()
+            .map(GamelogLocation::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let stats = WorldStats::from_locations(&locations);
+        assert_eq!(
+            stats.iter().map(|stats| stats.visits).sum::<u64>(),
+            fixture.visits.len() as u64
+        );
+        assert_eq!(
+            stats.iter().map(|stats| stats.total_time).sum::<u64>(),
+            fixture
+                .locations
+                .iter()
+                .map(|row| row.time as u64)
+                .sum::<u64>()
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use surrealdb::{Connection, Surreal};
+
+use crate::models::avatar::Avatar;
+use crate::repo::insert_strategy::{upsert_newest, BatchRepo, InsertStrategy};
+
+/// Typed access to the `avatar` table.
+///
+/// Records are keyed by `Avatar::id`, e.g. `avatar:avtr_1234`.
+#[derive(Debug, Clone)]
+pub struct AvatarRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> AvatarRepo<C> {
+    pub const TABLE: &'static str = "avatar";
+
+    /// Create a new `AvatarRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        AvatarRepo { db }
+    }
+
+    /// Get the avatar with `id`.
+    pub async fn get(&self, id: &str) -> surrealdb::Result<Option<Avatar>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM type::thing('avatar', $id)")
+            .bind(("id", id))
+            .await?
+            .take(0)
+    }
+
+    /// Every avatar, by name.
+    pub async fn all(&self) -> surrealdb::Result<Vec<Avatar>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM avatar ORDER BY name")
+            .await?
+            .take(0)
+    }
+
+    /// Every avatar uploaded by `author_id`, by name.
+    pub async fn find_by_author(&self, author_id: &str) -> surrealdb::Result<Vec<Avatar>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM avatar \
+                WHERE author_id = $author_id ORDER BY name",
+            )
+            .bind(("author_id", author_id))
+            .await?
+            .take(0)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for AvatarRepo<C> {
+    type Row = Avatar;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Write every avatar unless a newer `version` of it is already stored, returning how many
+    /// were written.
+    ///
+    /// The cache changes between imports, so avatars are always upserted, whatever `strategy`.
+    async fn insert_batch(
+        &self,
+        avatars: &[Avatar],
+        _strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        upsert_newest(&self.db, Self::TABLE, avatars).await
+    }
+}
@@ -0,0 +1,43 @@
+use surrealdb::{Connection, Surreal};
+
+use crate::models::avatar_history::AvatarHistory;
+use crate::repo::insert_strategy::{insert_rows, BatchRepo, InsertStrategy};
+
+/// Typed access to the `avatar_history` table.
+///
+/// Records are keyed by `AvatarHistory::id`, a hash of `avatar_id` and `created_at`.
+#[derive(Debug, Clone)]
+pub struct AvatarHistoryRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> AvatarHistoryRepo<C> {
+    pub const TABLE: &'static str = "avatar_history";
+
+    /// Create a new `AvatarHistoryRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        AvatarHistoryRepo { db }
+    }
+
+    /// Every row, oldest first.
+    pub async fn all(&self) -> surrealdb::Result<Vec<AvatarHistory>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM avatar_history ORDER BY created_at")
+            .await?
+            .take(0)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for AvatarHistoryRepo<C> {
+    type Row = AvatarHistory;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Insert every row with `strategy`, returning how many were inserted.
+    async fn insert_batch(
+        &self,
+        rows: &[AvatarHistory],
+        strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        insert_rows(&self.db, Self::TABLE, rows, strategy).await
+    }
+}
@@ -0,0 +1,109 @@
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use surrealdb::{Connection, Surreal};
+
+use crate::models::feed_avatar::FeedAvatar;
+use crate::models::feed_bio::FeedBio;
+use crate::models::feed_gps::FeedGps;
+use crate::models::feed_online_offline::FeedOnlineOffline;
+use crate::models::feed_status::FeedStatus;
+use crate::repo::insert_strategy::{insert_rows, BatchRepo, InsertStrategy};
+
+/// A row of one of the `_feed_*` tables: something a friend did at `created_at`, keyed by an
+/// integer `id` and naming the friend in `user_id`.
+pub trait FeedRow: Serialize + DeserializeOwned {
+    /// The SurrealDB table the rows are written to.
+    const TABLE: &'static str;
+}
+
+impl FeedRow for FeedAvatar {
+    const TABLE: &'static str = "feed_avatar";
+}
+
+impl FeedRow for FeedBio {
+    const TABLE: &'static str = "feed_bio";
+}
+
+impl FeedRow for FeedGps {
+    const TABLE: &'static str = "feed_gps";
+}
+
+impl FeedRow for FeedOnlineOffline {
+    const TABLE: &'static str = "feed_online_offline";
+}
+
+impl FeedRow for FeedStatus {
+    const TABLE: &'static str = "feed_status";
+}
+
+/// Typed access to the `feed_avatar` table.
+pub type FeedAvatarRepo<C> = FeedRepo<FeedAvatar, C>;
+
+/// Typed access to the `feed_bio` table.
+pub type FeedBioRepo<C> = FeedRepo<FeedBio, C>;
+
+/// Typed access to the `feed_gps` table.
+pub type FeedGpsRepo<C> = FeedRepo<FeedGps, C>;
+
+/// Typed access to the `feed_online_offline` table.
+pub type FeedOnlineOfflineRepo<C> = FeedRepo<FeedOnlineOffline, C>;
+
+/// Typed access to the `feed_status` table.
+pub type FeedStatusRepo<C> = FeedRepo<FeedStatus, C>;
+
+/// Typed access to the feed table of `T`, see `FeedRow`.
+///
+/// Records are keyed by the row's `id`, e.g. `feed_status:1`.
+#[derive(Debug, Clone)]
+pub struct FeedRepo<T, C: Connection> {
+    db: Surreal<C>,
+    row: PhantomData<T>,
+}
+
+impl<T: FeedRow, C: Connection> FeedRepo<T, C> {
+    pub const TABLE: &'static str = T::TABLE;
+
+    /// Create a new `FeedRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        FeedRepo {
+            db,
+            row: PhantomData,
+        }
+    }
+
+    /// Every row, oldest first.
+    pub async fn all(&self) -> surrealdb::Result<Vec<T>> {
+        self.db
+            .query(format!(
+                "SELECT *, meta::id(id) AS id FROM {} ORDER BY created_at",
+                Self::TABLE
+            ))
+            .await?
+            .take(0)
+    }
+
+    /// Every row about `user_id`, oldest first.
+    pub async fn find_by_player(&self, user_id: &str) -> surrealdb::Result<Vec<T>> {
+        self.db
+            .query(format!(
+                "SELECT *, meta::id(id) AS id FROM {} \
+                WHERE user_id = $user_id ORDER BY created_at",
+                Self::TABLE
+            ))
+            .bind(("user_id", user_id))
+            .await?
+            .take(0)
+    }
+}
+
+impl<T: FeedRow + Sync, C: Connection> BatchRepo<C> for FeedRepo<T, C> {
+    type Row = T;
+    const TABLE: &'static str = T::TABLE;
+
+    /// Insert every row with `strategy`, returning how many were inserted.
+    async fn insert_batch(&self, rows: &[T], strategy: InsertStrategy) -> surrealdb::Result<usize> {
+        insert_rows(&self.db, Self::TABLE, rows, strategy).await
+    }
+}
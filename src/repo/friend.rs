@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use surrealdb::method::QueryStream;
+use surrealdb::{Connection, Notification, Surreal};
+
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::repo::insert_strategy::{insert_rows, BatchRepo, InsertStrategy};
+
+/// A friend with its `user_id` repeated as the record `id`.
+#[derive(serde::Serialize)]
+struct Keyed<'a> {
+    id: &'a str,
+    #[serde(flatten)]
+    friend: &'a UsrFriendLogCurrent,
+}
+
+impl<'a> From<&'a UsrFriendLogCurrent> for Keyed<'a> {
+    fn from(friend: &'a UsrFriendLogCurrent) -> Self {
+        Keyed {
+            id: &friend.user_id,
+            friend,
+        }
+    }
+}
+
+/// Typed access to the `friend_log_current` table.
+///
+/// Records are keyed by `UsrFriendLogCurrent::user_id`, e.g. `friend_log_current:usr_1234`.
+#[derive(Debug, Clone)]
+pub struct FriendRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> FriendRepo<C> {
+    pub const TABLE: &'static str = "friend_log_current";
+
+    /// Create a new `FriendRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        FriendRepo { db }
+    }
+
+    /// Write every friend, see `insert_batch`, returning how many were written.
+    pub async fn insert_many(&self, friends: &[UsrFriendLogCurrent]) -> surrealdb::Result<usize> {
+        self.insert_batch(friends, InsertStrategy::Insert).await
+    }
+
+    /// Create or replace the friend with the same `user_id`.
+    pub async fn upsert(&self, friend: &UsrFriendLogCurrent) -> surrealdb::Result<()> {
+        self.db
+            .query("UPDATE type::thing('friend_log_current', $user_id) CONTENT $friend RETURN NONE")
+            .bind(("user_id", &friend.user_id))
+            .bind(("friend", friend))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Get the friend with `user_id`.
+    pub async fn get(&self, user_id: &str) -> surrealdb::Result<Option<UsrFriendLogCurrent>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS user_id \
+                FROM type::thing('friend_log_current', $user_id)",
+            )
+            .bind(("user_id", user_id))
+            .await?
+            .take(0)
+    }
+
+    /// Every friend.
+    pub async fn all(&self) -> surrealdb::Result<Vec<UsrFriendLogCurrent>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS user_id FROM friend_log_current")
+            .await?
+            .take(0)
+    }
+
+    /// Up to `limit` friends ordered by `user_id`, skipping the first `start`.
+    pub async fn page(
+        &self,
+        start: usize,
+        limit: usize,
+    ) -> surrealdb::Result<Vec<UsrFriendLogCurrent>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS user_id FROM friend_log_current \
+                ORDER BY user_id LIMIT $limit START $start",
+            )
+            .bind(("start", start))
+            .bind(("limit", limit))
+            .await?
+            .take(0)
+    }
+
+    /// Every friend seen joining or leaving `world_id`.
+    pub async fn find_by_world(
+        &self,
+        world_id: &str,
+    ) -> surrealdb::Result<Vec<UsrFriendLogCurrent>> {
+        self.db
+            .query(
+                "LET $players = (SELECT VALUE user_id FROM gamelog_join_leave \
+                WHERE location.instance.world_id = $world_id);
+                SELECT *, meta::id(id) AS user_id FROM friend_log_current \
+                WHERE meta::id(id) INSIDE $players",
+            )
+            .bind(("world_id", world_id))
+            .await?
+            .take(1)
+    }
+
+    /// The friend with `user_id`, as a list so it lines up with the other repos.
+    pub async fn find_by_player(
+        &self,
+        user_id: &str,
+    ) -> surrealdb::Result<Vec<UsrFriendLogCurrent>> {
+        Ok(self.get(user_id).await?.into_iter().collect())
+    }
+
+    /// Every friend seen joining or leaving with `created_at` in `[from, to)`. Missing bounds
+    /// are open.
+    pub async fn range(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> surrealdb::Result<Vec<UsrFriendLogCurrent>> {
+        self.db
+            .query(
+                "LET $players = (SELECT VALUE user_id FROM gamelog_join_leave \
+                WHERE ($from = NONE OR <datetime> created_at >= <datetime> $from) \
+                AND ($to = NONE OR <datetime> created_at < <datetime> $to));
+                SELECT *, meta::id(id) AS user_id FROM friend_log_current \
+                WHERE meta::id(id) INSIDE $players",
+            )
+            .bind(("from", from))
+            .bind(("to", to))
+            .await?
+            .take(1)
+    }
+
+    /// Subscribe to every change to the table with `LIVE SELECT`.
+    pub async fn live(&self) -> surrealdb::Result<QueryStream<Notification<UsrFriendLogCurrent>>> {
+        self.db
+            .query("LIVE SELECT *, meta::id(id) AS user_id FROM friend_log_current")
+            .await?
+            .stream(0)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for FriendRepo<C> {
+    type Row = UsrFriendLogCurrent;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Write every friend, replacing the friend with the same `user_id`, returning how many
+    /// were written.
+    ///
+    /// Names and trust levels change between imports, so friends are always upserted, whatever
+    /// `strategy`.
+    async fn insert_batch(
+        &self,
+        friends: &[UsrFriendLogCurrent],
+        _strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        let rows = friends.iter().map(Keyed::from).collect::<Vec<_>>();
+        insert_rows(&self.db, Self::TABLE, &rows, InsertStrategy::Upsert).await
+    }
+}
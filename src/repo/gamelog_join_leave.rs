@@ -0,0 +1,199 @@
+use chrono::{DateTime, Utc};
+use surrealdb::method::QueryStream;
+use surrealdb::{Connection, Notification, Surreal};
+
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::session::Session;
+use crate::repo::insert_strategy::{insert_rows, BatchRepo, InsertStrategy};
+
+/// Typed access to the `gamelog_join_leave` table.
+///
+/// Records are keyed by `GamelogJoinLeave::id`, e.g. `gamelog_join_leave:1`.
+#[derive(Debug, Clone)]
+pub struct GamelogJoinLeaveRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> GamelogJoinLeaveRepo<C> {
+    pub const TABLE: &'static str = "gamelog_join_leave";
+
+    /// Create a new `GamelogJoinLeaveRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        GamelogJoinLeaveRepo { db }
+    }
+
+    /// Insert every row in one statement, returning how many were inserted.
+    pub async fn insert_many(&self, rows: &[GamelogJoinLeave]) -> surrealdb::Result<usize> {
+        self.insert_batch(rows, InsertStrategy::Insert).await
+    }
+
+    /// Create or replace the row with the same `id`.
+    pub async fn upsert(&self, row: &GamelogJoinLeave) -> surrealdb::Result<()> {
+        self.db
+            .query("UPDATE type::thing('gamelog_join_leave', $id) CONTENT $row RETURN NONE")
+            .bind(("id", row.id))
+            .bind(("row", row))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Get the row with `id`.
+    pub async fn get(&self, id: i64) -> surrealdb::Result<Option<GamelogJoinLeave>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM type::thing('gamelog_join_leave', $id)")
+            .bind(("id", id))
+            .await?
+            .take(0)
+    }
+
+    /// Every row, oldest first.
+    pub async fn all(&self) -> surrealdb::Result<Vec<GamelogJoinLeave>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM gamelog_join_leave ORDER BY created_at")
+            .await?
+            .take(0)
+    }
+
+    /// Every join and leave seen in `world_id`, oldest first.
+    pub async fn find_by_world(&self, world_id: &str) -> surrealdb::Result<Vec<GamelogJoinLeave>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM gamelog_join_leave \
+                WHERE location.instance.world_id = $world_id ORDER BY created_at",
+            )
+            .bind(("world_id", world_id))
+            .await?
+            .take(0)
+    }
+
+    /// Every join and leave of `user_id`, oldest first.
+    pub async fn find_by_player(&self, user_id: &str) -> surrealdb::Result<Vec<GamelogJoinLeave>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM gamelog_join_leave \
+                WHERE user_id = $user_id ORDER BY created_at",
+            )
+            .bind(("user_id", user_id))
+            .await?
+            .take(0)
+    }
+
+    /// Every row with `created_at` in `[from, to)`, oldest first. Missing bounds are open.
+    pub async fn range(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> surrealdb::Result<Vec<GamelogJoinLeave>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM gamelog_join_leave \
+                WHERE ($from = NONE OR <datetime> created_at >= <datetime> $from) \
+                AND ($to = NONE OR <datetime> created_at < <datetime> $to) \
+                ORDER BY created_at",
+            )
+            .bind(("from", from))
+            .bind(("to", to))
+            .await?
+            .take(0)
+    }
+
+    /// Up to `limit` rows with `created_at` in `[from, to)`, skipping the first `start`, oldest
+    /// first. Rows created at the same time are ordered by `id`, so the pages don't overlap.
+    pub async fn page(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        start: usize,
+        limit: usize,
+    ) -> surrealdb::Result<Vec<GamelogJoinLeave>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM gamelog_join_leave \
+                WHERE ($from = NONE OR <datetime> created_at >= <datetime> $from) \
+                AND ($to = NONE OR <datetime> created_at < <datetime> $to) \
+                ORDER BY created_at, id LIMIT $limit START $start",
+            )
+            .bind(("from", from))
+            .bind(("to", to))
+            .bind(("start", start))
+            .bind(("limit", limit))
+            .await?
+            .take(0)
+    }
+
+    /// Every session that started in `[from, to)`, see `Session::from_join_leave`. Missing
+    /// bounds are open.
+    ///
+    /// # What it does
+    ///
+    /// * Reads the rows in `[from, to)` and pairs them.
+    /// * A session still open at `to` may end after it, so the later rows of its `display_name`
+    ///   and `location` are read too, and everything is paired again.
+    /// * A leave whose join is before `from` is paired as if the join was never logged.
+    pub async fn sessions(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> surrealdb::Result<Vec<Session>> {
+        let mut rows = self.range(from, to).await?;
+        if let Some(to) = to {
+            let open = Session::from_join_leave(&rows)
+                .into_iter()
+                .filter(|session| session.joined_at.is_some() && session.left_at.is_none())
+                .map(|session| (session.display_name, session.location))
+                .collect::<Vec<_>>();
+            if !open.is_empty() {
+                // Locations don't compare equal once stored, so they are matched here.
+                let names = open.iter().map(|(name, _)| name).collect::<Vec<_>>();
+                let later: Vec<GamelogJoinLeave> = self
+                    .db
+                    .query(
+                        "SELECT *, meta::id(id) AS id FROM gamelog_join_leave \
+                        WHERE <datetime> created_at >= <datetime> $to \
+                        AND display_name INSIDE $names ORDER BY created_at",
+                    )
+                    .bind(("to", to))
+                    .bind(("names", names))
+                    .await?
+                    .take(0)?;
+                rows.extend(later.into_iter().filter(|row| {
+                    open.iter().any(|(name, location)| {
+                        *name == row.display_name && *location == row.location
+                    })
+                }));
+            }
+        }
+
+        Ok(Session::from_join_leave(&rows)
+            .into_iter()
+            .filter(|session| {
+                session.started_at().is_some_and(|at| {
+                    from.is_none_or(|from| at >= from) && to.is_none_or(|to| at < to)
+                })
+            })
+            .collect())
+    }
+
+    /// Subscribe to every change to the table with `LIVE SELECT`.
+    pub async fn live(&self) -> surrealdb::Result<QueryStream<Notification<GamelogJoinLeave>>> {
+        self.db
+            .query("LIVE SELECT *, meta::id(id) AS id FROM gamelog_join_leave")
+            .await?
+            .stream(0)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for GamelogJoinLeaveRepo<C> {
+    type Row = GamelogJoinLeave;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Insert every row with `strategy`, returning how many were inserted.
+    async fn insert_batch(
+        &self,
+        rows: &[GamelogJoinLeave],
+        strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        insert_rows(&self.db, Self::TABLE, rows, strategy).await
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use surrealdb::{Connection, Surreal};
+
+use crate::models::gamelog_location::GamelogLocation;
+use crate::models::world_stats::WorldStats;
+use crate::repo::insert_strategy::{insert_rows, BatchRepo, InsertStrategy};
+
+/// The visits to one world under one name, as grouped by `GamelogLocationRepo::world_stats`.
+#[derive(serde::Deserialize)]
+struct WorldNameStats {
+    world_id: String,
+    world_name: String,
+    visits: u64,
+    total_time: u64,
+    first_visit: Option<DateTime<Utc>>,
+    last_visit: Option<DateTime<Utc>>,
+    instances: Vec<String>,
+}
+
+/// Typed access to the `gamelog_locations` table.
+///
+/// Records are keyed by `GamelogLocation::id`, e.g. `gamelog_locations:1`.
+#[derive(Debug, Clone)]
+pub struct GamelogLocationRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> GamelogLocationRepo<C> {
+    pub const TABLE: &'static str = "gamelog_locations";
+
+    /// Create a new `GamelogLocationRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        GamelogLocationRepo { db }
+    }
+
+    /// Insert every row in one statement, returning how many were inserted.
+    pub async fn insert_many(&self, rows: &[GamelogLocation]) -> surrealdb::Result<usize> {
+        self.insert_batch(rows, InsertStrategy::Insert).await
+    }
+
+    /// Create or replace the row with the same `id`.
+    pub async fn upsert(&self, row: &GamelogLocation) -> surrealdb::Result<()> {
+        self.db
+            .query("UPDATE type::thing('gamelog_locations', $id) CONTENT $row RETURN NONE")
+            .bind(("id", row.id))
+            .bind(("row", row))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Get the row with `id`.
+    pub async fn get(&self, id: i64) -> surrealdb::Result<Option<GamelogLocation>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM type::thing('gamelog_locations', $id)")
+            .bind(("id", id))
+            .await?
+            .take(0)
+    }
+
+    /// Every row, oldest first.
+    pub async fn all(&self) -> surrealdb::Result<Vec<GamelogLocation>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM gamelog_locations ORDER BY created_at")
+            .await?
+            .take(0)
+    }
+
+    /// Every visit to `world_id`, oldest first.
+    pub async fn find_by_world(&self, world_id: &str) -> surrealdb::Result<Vec<GamelogLocation>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM gamelog_locations \
+                WHERE world_instance.world_id = $world_id ORDER BY created_at",
+            )
+            .bind(("world_id", world_id))
+            .await?
+            .take(0)
+    }
+
+    /// Every visit to an instance `user_id` was seen joining, oldest first.
+    pub async fn find_by_player(&self, user_id: &str) -> surrealdb::Result<Vec<GamelogLocation>> {
+        self.db
+            .query(
+                "LET $instances = (SELECT VALUE location.instance FROM gamelog_join_leave WHERE user_id = $user_id);
+                SELECT *, meta::id(id) AS id FROM gamelog_locations \
+                WHERE world_instance INSIDE $instances ORDER BY created_at",
+            )
+            .bind(("user_id", user_id))
+            .await?
+            .take(1)
+    }
+
+    /// Every row with `created_at` in `[from, to)`, oldest first. Missing bounds are open.
+    pub async fn range(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> surrealdb::Result<Vec<GamelogLocation>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM gamelog_locations \
+                WHERE ($from = NONE OR <datetime> created_at >= <datetime> $from) \
+                AND ($to = NONE OR <datetime> created_at < <datetime> $to) \
+                ORDER BY created_at",
+            )
+            .bind(("from", from))
+            .bind(("to", to))
+            .await?
+            .take(0)
+    }
+
+    /// Up to `limit` rows with `created_at` in `[from, to)`, skipping the first `start`, oldest
+    /// first. Rows created at the same time are ordered by `id`, so the pages don't overlap.
+    pub async fn page(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        start: usize,
+        limit: usize,
+    ) -> surrealdb::Result<Vec<GamelogLocation>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM gamelog_locations \
+                WHERE ($from = NONE OR <datetime> created_at >= <datetime> $from) \
+                AND ($to = NONE OR <datetime> created_at < <datetime> $to) \
+                ORDER BY created_at, id LIMIT $limit START $start",
+            )
+            .bind(("from", from))
+            .bind(("to", to))
+            .bind(("start", start))
+            .bind(("limit", limit))
+            .await?
+            .take(0)
+    }
+
+    /// Visit statistics for every world, as by `WorldStats::from_locations`, grouped in
+    /// SurrealDB so the rows are never loaded.
+    ///
+    /// Rows are grouped by world and name, and the few groups of one world combined here, since
+    /// the name is the one seen on the latest visit.
+    pub async fn world_stats(&self) -> surrealdb::Result<Vec<WorldStats>> {
+        let groups: Vec<WorldNameStats> = self
+            .db
+            .query(
+                "SELECT world_instance.world_id AS world_id, world_name, count() AS visits, \
+                math::sum(time ?? 0) AS total_time, \
+                time::min(<datetime> created_at) AS first_visit, \
+                time::max(<datetime> created_at) AS last_visit, \
+                array::group(world_instance.instance_id) AS instances \
+                FROM gamelog_locations GROUP BY world_id, world_name",
+            )
+            .await?
+            .take(0)?;
+
+        let mut worlds: HashMap<String, (WorldStats, BTreeSet<String>)> = HashMap::new();
+        for group in groups {
+            let (stats, instances) = worlds.entry(group.world_id.clone()).or_insert_with(|| {
+                (
+                    WorldStats {
+                        world_id: group.world_id.clone(),
+                        ..WorldStats::new()
+                    },
+                    BTreeSet::new(),
+                )
+            });
+            stats.visits += group.visits;
+            stats.total_time += group.total_time;
+            if group.last_visit >= stats.last_visit {
+                stats.world_name = group.world_name;
+                stats.last_visit = group.last_visit;
+            }
+            if stats.first_visit.is_none() || group.first_visit < stats.first_visit {
+                stats.first_visit = group.first_visit;
+            }
+            instances.extend(group.instances);
+        }
+
+        let mut stats = worlds
+            .into_values()
+            .map(|(mut stats, instances)| {
+                stats.instances = instances.len() as u64;
+                stats
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| b.visits.cmp(&a.visits).then(a.world_id.cmp(&b.world_id)));
+        Ok(stats)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for GamelogLocationRepo<C> {
+    type Row = GamelogLocation;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Insert every row with `strategy`, returning how many were inserted.
+    async fn insert_batch(
+        &self,
+        rows: &[GamelogLocation],
+        strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        insert_rows(&self.db, Self::TABLE, rows, strategy).await
+    }
+}
@@ -0,0 +1,194 @@
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use surrealdb::{Connection, Surreal};
+
+use crate::graph::social_graph::{Edge, EdgeKind, Node, NodeKind};
+
+/// How many nodes or edges `GraphRepo` writes per query.
+pub(crate) const CHUNK_SIZE: usize = 1000;
+
+/// Typed access to the social graph: a table per `NodeKind` and a relation table per
+/// `EdgeKind`.
+///
+/// Nodes are keyed by `Node::key`, e.g. `player:usr_1234`, and edges by `Edge::key`, e.g.
+/// `met:1234`.
+#[derive(Debug, Clone)]
+pub struct GraphRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> GraphRepo<C> {
+    /// Create a new `GraphRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        GraphRepo { db }
+    }
+
+    /// Set the label of every node in `nodes`, creating those that don't exist, in queries of
+    /// `CHUNK_SIZE` nodes.
+    pub async fn upsert_nodes(&self, nodes: &[Node]) -> surrealdb::Result<()> {
+        #[derive(serde::Serialize)]
+        struct NodeRecord<'a> {
+            id: Thing,
+            label: &'a str,
+        }
+
+        for chunk in nodes.chunks(CHUNK_SIZE) {
+            let records: Vec<NodeRecord> = chunk
+                .iter()
+                .map(|node| NodeRecord {
+                    id: Thing::from((node.kind.table(), node.key.as_str())),
+                    label: &node.label,
+                })
+                .collect();
+            self.db
+                .query(
+                    "FOR $node IN $nodes {
+                        UPDATE $node.id MERGE { label: $node.label } RETURN NONE;
+                    };",
+                )
+                .bind(("nodes", records))
+                .await?
+                .check()?;
+        }
+        Ok(())
+    }
+
+    /// Write every edge in `edges` under `Edge::key`, replacing those written before, in queries
+    /// of up to `CHUNK_SIZE` edges of one kind.
+    pub async fn relate_all(&self, edges: &[Edge]) -> Result<(), Box<dyn Error>> {
+        #[derive(serde::Serialize)]
+        struct EdgeRecord {
+            key: i64,
+            from: Thing,
+            to: Thing,
+            weight: Option<u64>,
+            first_seen: Option<DateTime<Utc>>,
+            last_seen: Option<DateTime<Utc>>,
+        }
+
+        for kind in EdgeKind::ALL {
+            let records = edges
+                .iter()
+                .filter(|edge| edge.kind == kind)
+                .map(|edge| {
+                    Ok(EdgeRecord {
+                        key: edge.key(),
+                        from: parse_thing(&edge.from)?,
+                        to: parse_thing(&edge.to)?,
+                        weight: edge.weight,
+                        first_seen: edge.first_seen,
+                        last_seen: edge.last_seen,
+                    })
+                })
+                .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+            let (first_seen, last_seen) = kind.seen_fields();
+            // `RELATE` takes neither a parameter nor an expression as the relation id, so the
+            // key goes into the content.
+            let sql = format!(
+                "FOR $edge IN $edges {{
+                    LET $from = $edge.from;
+                    LET $to = $edge.to;
+                    RELATE $from->{0}->$to CONTENT {{
+                        id: type::thing('{0}', $edge.key),
+                        weight: $edge.weight,
+                        {1}: $edge.first_seen,
+                        {2}: $edge.last_seen
+                    }} RETURN NONE;
+                }};",
+                kind.table(),
+                first_seen,
+                last_seen
+            );
+            for chunk in records.chunks(CHUNK_SIZE) {
+                self.db
+                    .query(sql.as_str())
+                    .bind(("edges", chunk))
+                    .await?
+                    .check()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every node of `kind`. Nodes without a label are labelled with their key.
+    pub async fn nodes(&self, kind: NodeKind) -> surrealdb::Result<Vec<Node>> {
+        #[derive(serde::Deserialize)]
+        struct NodeRecord {
+            key: String,
+            label: Option<String>,
+        }
+
+        let records: Vec<NodeRecord> = self
+            .db
+            .query("SELECT type::string(meta::id(id)) AS key, label FROM type::table($table)")
+            .bind(("table", kind.table()))
+            .await?
+            .take(0)?;
+        Ok(records
+            .into_iter()
+            .map(|record| Node {
+                kind,
+                label: record.label.unwrap_or_else(|| record.key.clone()),
+                key: record.key,
+            })
+            .collect())
+    }
+
+    /// Every edge of `kind`.
+    pub async fn edges(&self, kind: EdgeKind) -> surrealdb::Result<Vec<Edge>> {
+        #[derive(serde::Deserialize)]
+        struct EdgeRecord {
+            from: String,
+            to: String,
+            weight: Option<u64>,
+            first_seen: Option<DateTime<Utc>>,
+            last_seen: Option<DateTime<Utc>>,
+        }
+
+        let (first_seen, last_seen) = kind.seen_fields();
+        let records: Vec<EdgeRecord> = self
+            .db
+            .query(format!(
+                "SELECT
+                    meta::tb(in) + ':' + type::string(meta::id(in)) AS from,
+                    meta::tb(out) + ':' + type::string(meta::id(out)) AS to,
+                    weight, {} AS first_seen, {} AS last_seen
+                FROM type::table($table)",
+                first_seen, last_seen
+            ))
+            .bind(("table", kind.table()))
+            .await?
+            .take(0)?;
+        Ok(records
+            .into_iter()
+            .map(|record| Edge {
+                kind,
+                from: record.from,
+                to: record.to,
+                weight: record.weight,
+                first_seen: record.first_seen,
+                last_seen: record.last_seen,
+            })
+            .collect())
+    }
+
+    /// Delete every edge of `kind`.
+    pub async fn clear(&self, kind: EdgeKind) -> surrealdb::Result<()> {
+        self.db
+            .query("DELETE type::table($table) RETURN NONE")
+            .bind(("table", kind.table()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
+
+/// Split a node id such as `player:usr_1234` back into a record id.
+fn parse_thing(id: &str) -> Result<Thing, Box<dyn Error>> {
+    let (table, key) = id
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid node id: {}", id))?;
+    Ok(Thing::from((table, key)))
+}
@@ -0,0 +1,150 @@
+use std::future::Future;
+use std::str::FromStr;
+
+use serde::Serialize;
+use surrealdb::{Connection, Surreal};
+
+/// How a batch of rows is written.
+///
+/// # Available Strategies
+/// - Insert: one `INSERT INTO <table> [..]` statement per batch. Records that already exist
+///   are left as they are, and not counted as written.
+/// - Transaction: one `CREATE` per row, inside `BEGIN/COMMIT TRANSACTION`. A record that
+///   already exists fails the batch.
+/// - Upsert: one `UPDATE` per row, inside `BEGIN/COMMIT TRANSACTION`, replacing records that
+///   already exist. Used to merge into a namespace that already holds some of the rows.
+///
+/// Every strategy writes a batch inside one transaction, so it is written as a whole or not
+/// at all. Tables whose rows change between imports, such as the friend list and the caches,
+/// are upserted whatever the strategy, see their repositories.
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::repo::insert_strategy::InsertStrategy;
+///
+/// let strategy: InsertStrategy = "transaction".parse().unwrap();
+/// assert_eq!(strategy, InsertStrategy::Transaction);
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum InsertStrategy {
+    #[default]
+    Insert,
+    Transaction,
+    Upsert,
+}
+
+impl FromStr for InsertStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "insert" => Ok(InsertStrategy::Insert),
+            "transaction" | "tx" => Ok(InsertStrategy::Transaction),
+            "upsert" => Ok(InsertStrategy::Upsert),
+            _ => Err(format!("Unknown insert strategy: {}", s)),
+        }
+    }
+}
+
+/// A repository that writes the rows of one table in batches, see `Importer::import_table`.
+pub trait BatchRepo<C: Connection> {
+    /// The row written to `TABLE`.
+    type Row: Sync;
+
+    /// The SurrealDB table the rows are written to.
+    const TABLE: &'static str;
+
+    /// Write every row with `strategy`, returning how many records were written.
+    fn insert_batch(
+        &self,
+        rows: &[Self::Row],
+        strategy: InsertStrategy,
+    ) -> impl Future<Output = surrealdb::Result<usize>> + Send;
+}
+
+/// Write `rows` to `table` with `strategy`, returning how many records were written.
+///
+/// Every row must serialize with an `id` field, which becomes the record key. `CREATE` and
+/// `UPDATE` refuse a content whose `id` is a string, such as a user id, even when it names the
+/// same record, so the field is dropped from their content.
+///
+/// A bulk `INSERT` skips records that already exist without an error, so the rows it would
+/// skip are left out first and only the rest are counted.
+pub(crate) async fn insert_rows<C: Connection, T: Serialize>(
+    db: &Surreal<C>,
+    table: &str,
+    rows: &[T],
+    strategy: InsertStrategy,
+) -> surrealdb::Result<usize> {
+    if rows.is_empty() {
+        return Ok(0);
+    }
+    let sql = match strategy {
+        InsertStrategy::Insert => format!(
+            "BEGIN TRANSACTION;
+            LET $fresh = $rows[WHERE (type::thing('{0}', $this.id)).id = NONE];
+            INSERT INTO {0} $fresh RETURN NONE;
+            RETURN count($fresh);
+            COMMIT TRANSACTION;",
+            table
+        ),
+        InsertStrategy::Transaction => format!(
+            "BEGIN TRANSACTION;
+            FOR $row IN $rows {{
+                LET $content = object::from_entries(object::entries($row)[WHERE $this[0] != 'id']);
+                CREATE type::thing('{}', $row.id) CONTENT $content RETURN NONE;
+            }};
+            COMMIT TRANSACTION;",
+            table
+        ),
+        InsertStrategy::Upsert => format!(
+            "BEGIN TRANSACTION;
+            FOR $row IN $rows {{
+                LET $content = object::from_entries(object::entries($row)[WHERE $this[0] != 'id']);
+                UPDATE type::thing('{}', $row.id) CONTENT $content RETURN NONE;
+            }};
+            COMMIT TRANSACTION;",
+            table
+        ),
+    };
+    let mut response = db.query(sql).bind(("rows", rows)).await?.check()?;
+    match strategy {
+        InsertStrategy::Insert => {
+            let written: Option<usize> = response.take(response.num_statements() - 1)?;
+            Ok(written.unwrap_or_default())
+        }
+        InsertStrategy::Transaction | InsertStrategy::Upsert => Ok(rows.len()),
+    }
+}
+
+/// Upsert `rows`, versioned records such as worlds and avatars, into `table`, unless the
+/// record already holds a newer `version`. Returns how many records were written.
+///
+/// Records are merged rather than replaced, so fields other writers keep on the same record,
+/// such as the `label` of a graph node, survive.
+pub(crate) async fn upsert_newest<C: Connection, T: Serialize>(
+    db: &Surreal<C>,
+    table: &str,
+    rows: &[T],
+) -> surrealdb::Result<usize> {
+    if rows.is_empty() {
+        return Ok(0);
+    }
+    let sql = format!(
+        "BEGIN TRANSACTION;
+        LET $newer = $rows[WHERE (type::thing('{0}', $this.id)).version = NONE
+            OR (type::thing('{0}', $this.id)).version <= $this.version];
+        FOR $row IN $newer {{
+            LET $content = object::from_entries(object::entries($row)[WHERE $this[0] != 'id']);
+            UPDATE type::thing('{0}', $row.id) MERGE $content RETURN NONE;
+        }};
+        RETURN count($newer);
+        COMMIT TRANSACTION;",
+        table
+    );
+    let mut response = db.query(sql).bind(("rows", rows)).await?.check()?;
+    let written: Option<usize> = response.take(response.num_statements() - 1)?;
+    Ok(written.unwrap_or_default())
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use surrealdb::{Connection, Surreal};
+
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::moderation::{Moderation, ModerationEvent};
+use crate::repo::insert_strategy::{insert_rows, BatchRepo, InsertStrategy};
+
+/// A moderation with its `user_id` repeated as the record `id`.
+#[derive(serde::Serialize)]
+struct Keyed<'a> {
+    id: &'a str,
+    #[serde(flatten)]
+    moderation: &'a Moderation,
+}
+
+impl<'a> From<&'a Moderation> for Keyed<'a> {
+    fn from(moderation: &'a Moderation) -> Self {
+        Keyed {
+            id: &moderation.user_id,
+            moderation,
+        }
+    }
+}
+
+/// Typed access to the `moderation` table, the players the account currently blocks or mutes.
+///
+/// Records are keyed by `Moderation::user_id`, e.g. `moderation:usr_1234`.
+#[derive(Debug, Clone)]
+pub struct ModerationRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> ModerationRepo<C> {
+    pub const TABLE: &'static str = "moderation";
+
+    /// Create a new `ModerationRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        ModerationRepo { db }
+    }
+
+    /// Get the moderation of `user_id`.
+    pub async fn get(&self, user_id: &str) -> surrealdb::Result<Option<Moderation>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS user_id FROM type::thing('moderation', $user_id)")
+            .bind(("user_id", user_id))
+            .await?
+            .take(0)
+    }
+
+    /// Every moderation.
+    pub async fn all(&self) -> surrealdb::Result<Vec<Moderation>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS user_id FROM moderation")
+            .await?
+            .take(0)
+    }
+
+    /// Every player currently blocked.
+    pub async fn blocked(&self) -> surrealdb::Result<Vec<Moderation>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS user_id FROM moderation WHERE block = true")
+            .await?
+            .take(0)
+    }
+
+    /// Replace the table with `rows`, the whole moderation list of one sync, and record what
+    /// changed in `moderation_history`.
+    ///
+    /// # What it does
+    ///
+    /// * The stored list is compared with `rows`, see `ModerationEvent::diff`. Players dropped
+    ///   from the list are unblocked and unmuted at `now`.
+    /// * The changes are added to `moderation_history`, where they are kept across syncs.
+    /// * Every row is written over the stored one, and dropped players are deleted.
+    ///
+    /// All of it happens in one transaction. The next sync diffs against the stored list, so
+    /// if the list were replaced without its history the changes would be lost for good.
+    ///
+    /// Returns the changes.
+    pub async fn sync(
+        &self,
+        rows: &[Moderation],
+        now: DateTime<Utc>,
+    ) -> surrealdb::Result<Vec<ModerationEvent>> {
+        let before = self.all().await?;
+        let events = ModerationEvent::diff(&before, rows, now);
+        let dropped = before
+            .iter()
+            .filter(|old| !rows.iter().any(|row| row.user_id == old.user_id))
+            .map(|old| old.user_id.as_str())
+            .collect::<Vec<_>>();
+
+        self.db
+            .query(
+                "BEGIN TRANSACTION;
+                FOR $event IN $events {
+                    LET $content = object::from_entries(object::entries($event)[WHERE $this[0] != 'id']);
+                    UPDATE type::thing('moderation_history', $event.id) CONTENT $content RETURN NONE;
+                };
+                FOR $row IN $rows {
+                    LET $content = object::from_entries(object::entries($row)[WHERE $this[0] != 'id']);
+                    UPDATE type::thing('moderation', $row.id) CONTENT $content RETURN NONE;
+                };
+                DELETE moderation WHERE meta::id(id) INSIDE $dropped RETURN NONE;
+                COMMIT TRANSACTION;",
+            )
+            .bind(("events", &events))
+            .bind(("rows", rows.iter().map(Keyed::from).collect::<Vec<_>>()))
+            .bind(("dropped", dropped))
+            .await?
+            .check()?;
+        Ok(events)
+    }
+
+    /// Every join or leave of a player currently blocked, oldest first. The game log only holds
+    /// players in the same instance as the account, so each one is an instance shared with
+    /// someone blocked, though maybe from before they were blocked.
+    pub async fn shared_with_blocked(&self) -> surrealdb::Result<Vec<GamelogJoinLeave>> {
+        self.db
+            .query(
+                "LET $blocked = (SELECT VALUE meta::id(id) FROM moderation WHERE block = true);
+                SELECT *, meta::id(id) AS id FROM gamelog_join_leave \
+                WHERE user_id INSIDE $blocked ORDER BY created_at",
+            )
+            .await?
+            .take(1)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for ModerationRepo<C> {
+    type Row = Moderation;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Insert every row with `strategy`, returning how many were inserted.
+    async fn insert_batch(
+        &self,
+        rows: &[Moderation],
+        strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        let rows = rows.iter().map(Keyed::from).collect::<Vec<_>>();
+        insert_rows(&self.db, Self::TABLE, &rows, strategy).await
+    }
+}
@@ -0,0 +1,55 @@
+use surrealdb::{Connection, Surreal};
+
+use crate::models::moderation::ModerationEvent;
+use crate::repo::insert_strategy::{insert_rows, BatchRepo, InsertStrategy};
+
+/// Typed access to the `moderation_history` table.
+///
+/// Records are keyed by `ModerationEvent::id`, e.g. `moderation_history:1`.
+#[derive(Debug, Clone)]
+pub struct ModerationHistoryRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> ModerationHistoryRepo<C> {
+    pub const TABLE: &'static str = "moderation_history";
+
+    /// Create a new `ModerationHistoryRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        ModerationHistoryRepo { db }
+    }
+
+    /// Every event, oldest first.
+    pub async fn all(&self) -> surrealdb::Result<Vec<ModerationEvent>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM moderation_history ORDER BY at")
+            .await?
+            .take(0)
+    }
+
+    /// Every event of `user_id`, oldest first.
+    pub async fn find_by_player(&self, user_id: &str) -> surrealdb::Result<Vec<ModerationEvent>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM moderation_history \
+                WHERE user_id = $user_id ORDER BY at",
+            )
+            .bind(("user_id", user_id))
+            .await?
+            .take(0)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for ModerationHistoryRepo<C> {
+    type Row = ModerationEvent;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Insert every event with `strategy`, returning how many were inserted.
+    async fn insert_batch(
+        &self,
+        rows: &[ModerationEvent],
+        strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        insert_rows(&self.db, Self::TABLE, rows, strategy).await
+    }
+}
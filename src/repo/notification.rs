@@ -0,0 +1,93 @@
+use surrealdb::sql::Thing;
+use surrealdb::{Connection, Surreal};
+
+use crate::graph::social_graph::NodeKind;
+use crate::models::notification::Notification;
+use crate::repo::insert_strategy::{insert_rows, BatchRepo, InsertStrategy};
+
+/// A notification with record links to the `player` records of its sender and receiver.
+#[derive(serde::Serialize)]
+struct Linked<'a> {
+    sender: Option<Thing>,
+    receiver: Option<Thing>,
+    #[serde(flatten)]
+    notification: &'a Notification,
+}
+
+impl<'a> From<&'a Notification> for Linked<'a> {
+    fn from(notification: &'a Notification) -> Self {
+        let player = |user_id: &str| {
+            (!user_id.is_empty()).then(|| Thing::from((NodeKind::Player.table(), user_id)))
+        };
+        Linked {
+            sender: player(&notification.sender_user_id),
+            receiver: player(&notification.receiver_user_id),
+            notification,
+        }
+    }
+}
+
+/// Typed access to the `notification` table.
+///
+/// Records are keyed by `Notification::id`, e.g. `notification:not_1234`, and link their
+/// sender and receiver as `sender` and `receiver`, e.g. `player:usr_1234`.
+#[derive(Debug, Clone)]
+pub struct NotificationRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> NotificationRepo<C> {
+    pub const TABLE: &'static str = "notification";
+
+    /// Create a new `NotificationRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        NotificationRepo { db }
+    }
+
+    /// Every notification, oldest first.
+    pub async fn all(&self) -> surrealdb::Result<Vec<Notification>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM notification ORDER BY created_at")
+            .await?
+            .take(0)
+    }
+
+    /// Every notification `user_id` sent or received, oldest first.
+    pub async fn find_by_player(&self, user_id: &str) -> surrealdb::Result<Vec<Notification>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM notification \
+                WHERE sender_user_id = $user_id OR receiver_user_id = $user_id \
+                ORDER BY created_at",
+            )
+            .bind(("user_id", user_id))
+            .await?
+            .take(0)
+    }
+
+    /// Every invite, oldest first.
+    pub async fn invites(&self) -> surrealdb::Result<Vec<Notification>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM notification \
+                WHERE kind = 'Invite' ORDER BY created_at",
+            )
+            .await?
+            .take(0)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for NotificationRepo<C> {
+    type Row = Notification;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Insert every row with `strategy`, returning how many were inserted.
+    async fn insert_batch(
+        &self,
+        rows: &[Notification],
+        strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        let rows = rows.iter().map(Linked::from).collect::<Vec<_>>();
+        insert_rows(&self.db, Self::TABLE, &rows, strategy).await
+    }
+}
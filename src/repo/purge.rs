@@ -0,0 +1,503 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use surrealdb::sql::{Id, Thing};
+use surrealdb::{Connection, Surreal};
+
+use crate::graph::social_graph::{EdgeKind, NodeKind};
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::resolvers::display_name::{DisplayNameResolver, Resolution};
+
+/// A table the `Purger` knows how to purge.
+///
+/// # Values
+///
+/// - `name` - The SurrealDB table.
+/// - `timestamp` - The field retention is measured from, if the table has one.
+/// - `user` - The condition matching the records of `$user_id`, or of the player records
+///   `$players`, if the table holds any. `$records` lists further records of the player
+///   found by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PurgeTable {
+    pub name: &'static str,
+    pub timestamp: Option<&'static str>,
+    pub user: Option<&'static str>,
+}
+
+/// Every table the `Purger` knows, records first and graph edges last.
+pub const PURGE_TABLES: [PurgeTable; 19] = [
+    PurgeTable {
+        name: "gamelog_locations",
+        timestamp: Some("created_at"),
+        user: None,
+    },
+    PurgeTable {
+        name: "gamelog_join_leave",
+        timestamp: Some("created_at"),
+        user: Some("user_id = $user_id OR id INSIDE $records"),
+    },
+    PurgeTable {
+        name: "friend_log_current",
+        timestamp: None,
+        user: Some("meta::id(id) = $user_id"),
+    },
+    PurgeTable {
+        name: "feed_avatar",
+        timestamp: Some("created_at"),
+        user: Some("user_id = $user_id"),
+    },
+    PurgeTable {
+        name: "avatar_history",
+        timestamp: Some("created_at"),
+        user: None,
+    },
+    PurgeTable {
+        name: "feed_status",
+        timestamp: Some("created_at"),
+        user: Some("user_id = $user_id"),
+    },
+    PurgeTable {
+        name: "feed_bio",
+        timestamp: Some("created_at"),
+        user: Some("user_id = $user_id"),
+    },
+    PurgeTable {
+        name: "feed_online_offline",
+        timestamp: Some("created_at"),
+        user: Some("user_id = $user_id"),
+    },
+    PurgeTable {
+        name: "feed_gps",
+        timestamp: Some("created_at"),
+        user: Some("user_id = $user_id"),
+    },
+    PurgeTable {
+        name: "moderation",
+        timestamp: None,
+        user: Some("meta::id(id) = $user_id"),
+    },
+    PurgeTable {
+        name: "moderation_history",
+        timestamp: Some("at"),
+        user: Some("user_id = $user_id"),
+    },
+    PurgeTable {
+        name: "notification",
+        timestamp: Some("created_at"),
+        user: Some("sender_user_id = $user_id OR receiver_user_id = $user_id"),
+    },
+    PurgeTable {
+        name: "joined",
+        timestamp: Some("last_seen"),
+        user: Some("in INSIDE $players OR out INSIDE $players"),
+    },
+    PurgeTable {
+        name: "met",
+        timestamp: Some("last_seen"),
+        user: Some("in INSIDE $players OR out INSIDE $players"),
+    },
+    PurgeTable {
+        name: "friend",
+        timestamp: None,
+        user: Some("in INSIDE $players OR out INSIDE $players"),
+    },
+    PurgeTable {
+        name: "instance_of",
+        timestamp: Some("last_seen"),
+        user: None,
+    },
+    PurgeTable {
+        name: "wore",
+        timestamp: Some("worn_from"),
+        user: Some("in INSIDE $players"),
+    },
+    PurgeTable {
+        name: "visited",
+        timestamp: Some("last_seen"),
+        user: Some("in INSIDE $players"),
+    },
+    PurgeTable {
+        name: "invited",
+        timestamp: Some("last_seen"),
+        user: Some("in INSIDE $players OR out INSIDE $players"),
+    },
+];
+
+/// How long records are kept, in days.
+///
+/// # Values
+///
+/// - `days` - The retention of every table with a timestamp. `None` keeps them forever.
+/// - `tables` - Retention for single tables, overriding `days`.
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::repo::purge::Retention;
+///
+/// let retention = Retention::new(Some(90)).table("gamelog_join_leave".parse().unwrap());
+/// assert_eq!(retention.days_for("gamelog_locations"), Some(90));
+/// assert_eq!(retention.days_for("gamelog_join_leave"), None);
+///
+/// let retention = Retention::new(None).table("met=30".parse().unwrap());
+/// assert_eq!(retention.days_for("met"), Some(30));
+/// assert_eq!(retention.days_for("joined"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct Retention {
+    pub days: Option<u32>,
+    pub tables: BTreeMap<String, Option<u32>>,
+}
+
+impl Retention {
+    /// Keep every table for `days`.
+    pub fn new(days: Option<u32>) -> Self {
+        Retention {
+            days,
+            tables: BTreeMap::new(),
+        }
+    }
+
+    /// Override the retention of one table.
+    pub fn table(mut self, retention: TableRetention) -> Self {
+        self.tables.insert(retention.table, retention.days);
+        self
+    }
+
+    /// How many days `table` is kept, or `None` to keep it forever.
+    pub fn days_for(&self, table: &str) -> Option<u32> {
+        self.tables.get(table).copied().unwrap_or(self.days)
+    }
+}
+
+/// The retention of one table, written `<table>=<days>`, or just `<table>` to keep it forever.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct TableRetention {
+    pub table: String,
+    pub days: Option<u32>,
+}
+
+impl FromStr for TableRetention {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (table, days) = match s.split_once('=') {
+            Some((table, days)) => {
+                let days = days
+                    .parse::<u32>()
+                    .map_err(|_| format!("Invalid retention days: {}", days))?;
+                (table, Some(days))
+            }
+            None => (s, None),
+        };
+        if !PURGE_TABLES.iter().any(|purge| purge.name == table) {
+            return Err(format!("Unknown purge table: {}", table));
+        }
+        Ok(TableRetention {
+            table: table.to_string(),
+            days,
+        })
+    }
+}
+
+/// The records removed from one table, or that would be in a dry run.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct TablePurge {
+    pub table: String,
+    pub ids: Vec<String>,
+}
+
+/// What a purge removed, or would remove when `dry_run` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct PurgeReport {
+    pub dry_run: bool,
+    pub tables: Vec<TablePurge>,
+}
+
+impl PurgeReport {
+    /// How many records were removed across every table.
+    pub fn total(&self) -> usize {
+        self.tables.iter().map(|table| table.ids.len()).sum()
+    }
+}
+
+impl fmt::Display for PurgeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = if self.dry_run {
+            "would remove"
+        } else {
+            "removed"
+        };
+        for table in &self.tables {
+            writeln!(f, "{}: {} {} records", table.table, verb, table.ids.len())?;
+            for id in &table.ids {
+                writeln!(f, "  {}", id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deletes old records and everything known about a player.
+///
+/// With `dry_run` set nothing is deleted, and the reports list what would have been.
+#[derive(Debug, Clone)]
+pub struct Purger<C: Connection> {
+    db: Surreal<C>,
+    dry_run: bool,
+}
+
+impl<C: Connection> Purger<C> {
+    /// Create a new `Purger` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        Purger { db, dry_run: false }
+    }
+
+    /// Only report what would be removed.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Remove the records older than their table's retention, counting back from `now`.
+    ///
+    /// Tables without a timestamp, such as `friend_log_current` and `moderation`, are never
+    /// purged by age.
+    #[tracing::instrument(skip_all, fields(dry_run = self.dry_run))]
+    pub async fn older_than(
+        &self,
+        retention: &Retention,
+        now: DateTime<Utc>,
+    ) -> surrealdb::Result<PurgeReport> {
+        let mut report = self.report();
+        for table in PURGE_TABLES {
+            let (Some(timestamp), Some(days)) = (table.timestamp, retention.days_for(table.name))
+            else {
+                continue;
+            };
+            let cutoff = now - Duration::days(days as i64);
+            let vars = PurgeVars {
+                cutoff: Some(cutoff),
+                ..Default::default()
+            };
+            let ids = self
+                .purge(table.name, &format!("{} < $cutoff", timestamp), &vars)
+                .await?;
+            report.tables.push(TablePurge {
+                table: table.name.to_string(),
+                ids,
+            });
+        }
+        Ok(report)
+    }
+
+    /// Remove every record of `user_id`: their join/leave events, their friend record, their
+    /// avatar switches, their status, bio, online/offline and GPS feeds, their moderation and its
+    /// history, the notifications they sent or received, their `player` node and every edge
+    /// touching it.
+    ///
+    /// Records without a `user_id` are found by the names the player was seen using, see
+    /// `DisplayNameResolver`:
+    ///
+    /// * Join/leave events with one of the names, unless it belonged to someone else alone at
+    ///   the time.
+    /// * The `player` nodes keyed by one of the names, and every edge touching them.
+    #[tracing::instrument(skip_all, fields(dry_run = self.dry_run))]
+    pub async fn forget(&self, user_id: &str) -> surrealdb::Result<PurgeReport> {
+        let resolver = DisplayNameResolver::fetch(&self.db).await?;
+        let names = resolver
+            .history(user_id)
+            .into_iter()
+            .map(|span| span.display_name)
+            .collect::<BTreeSet<_>>();
+        let unnamed: Vec<GamelogJoinLeave> = self
+            .db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM gamelog_join_leave \
+                WHERE user_id = NONE AND display_name INSIDE $names",
+            )
+            .bind(("names", &names))
+            .await?
+            .take(0)?;
+        let records = unnamed
+            .iter()
+            .filter(
+                |row| match resolver.resolve(&row.display_name, row.created_at) {
+                    Resolution::Resolved(other) => other == user_id,
+                    Resolution::Ambiguous(candidates) => candidates.iter().any(|id| id == user_id),
+                    Resolution::Unknown => true,
+                },
+            )
+            .map(|row| Thing::from(("gamelog_join_leave".to_string(), Id::from(row.id))))
+            .collect();
+        let players = std::iter::once(user_id)
+            .chain(names.iter().map(String::as_str))
+            .map(|key| Thing::from((NodeKind::Player.table(), key)))
+            .collect();
+
+        let vars = PurgeVars {
+            user_id: Some(user_id.to_string()),
+            players,
+            records,
+            ..Default::default()
+        };
+        let mut report = self.report();
+        for table in PURGE_TABLES {
+            let Some(condition) = table.user else {
+                continue;
+            };
+            let ids = self.purge(table.name, condition, &vars).await?;
+            report.tables.push(TablePurge {
+                table: table.name.to_string(),
+                ids,
+            });
+        }
+
+        let ids = self
+            .purge(NodeKind::Player.table(), "id INSIDE $players", &vars)
+            .await?;
+        report.tables.push(TablePurge {
+            table: NodeKind::Player.table().to_string(),
+            ids,
+        });
+        Ok(report)
+    }
+
+    /// Remove the edges whose `in` or `out` record no longer exists, then the `player`,
+    /// `world`, `instance` and `avatar` nodes no edge touches anymore.
+    ///
+    /// Only graph nodes are removed: records with a `name`, such as the `world` and `avatar`
+    /// caches, are kept.
+    #[tracing::instrument(skip_all, fields(dry_run = self.dry_run))]
+    pub async fn compact_edges(&self) -> surrealdb::Result<PurgeReport> {
+        let mut report = self.report();
+        for kind in EdgeKind::ALL {
+            let ids = self
+                .purge(
+                    kind.table(),
+                    "in.id = NONE OR out.id = NONE",
+                    &PurgeVars::default(),
+                )
+                .await?;
+            report.tables.push(TablePurge {
+                table: kind.table().to_string(),
+                ids,
+            });
+        }
+
+        let edges = EdgeKind::ALL
+            .iter()
+            .map(|kind| {
+                format!(
+                    "count((SELECT id FROM {} WHERE in = $parent.id OR out = $parent.id)) = 0",
+                    kind.table()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let orphans = format!("name = NONE AND {}", edges);
+        for kind in NodeKind::ALL {
+            let ids = self
+                .purge(kind.table(), &orphans, &PurgeVars::default())
+                .await?;
+            report.tables.push(TablePurge {
+                table: kind.table().to_string(),
+                ids,
+            });
+        }
+        Ok(report)
+    }
+
+    fn report(&self) -> PurgeReport {
+        PurgeReport {
+            dry_run: self.dry_run,
+            tables: Vec::new(),
+        }
+    }
+
+    /// List the records of `table` matching `condition`, and delete them unless this is a dry
+    /// run.
+    async fn purge(
+        &self,
+        table: &str,
+        condition: &str,
+        vars: &PurgeVars,
+    ) -> surrealdb::Result<Vec<String>> {
+        let ids: Vec<String> = self
+            .db
+            .query(format!(
+                "SELECT VALUE type::string(id) FROM type::table($table) WHERE {}",
+                condition
+            ))
+            .bind(("table", table))
+            .bind(vars)
+            .await?
+            .take(0)?;
+
+        if !self.dry_run && !ids.is_empty() {
+            self.db
+                .query(format!(
+                    "DELETE type::table($table) WHERE {} RETURN NONE",
+                    condition
+                ))
+                .bind(("table", table))
+                .bind(vars)
+                .await?
+                .check()?;
+        }
+        Ok(ids)
+    }
+}
+
+/// The variables the purge conditions can use.
+#[derive(Debug, Clone, serde::Serialize, Default)]
+struct PurgeVars {
+    cutoff: Option<DateTime<Utc>>,
+    user_id: Option<String>,
+    players: Vec<Thing>,
+    records: Vec<Thing>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::social_graph::EdgeKind;
+    use crate::repo::purge::{PurgeReport, TablePurge, TableRetention, PURGE_TABLES};
+
+    #[test]
+    fn test_every_edge_is_purgeable() {
+        for kind in EdgeKind::ALL {
+            assert!(PURGE_TABLES.iter().any(|table| table.name == kind.table()));
+        }
+    }
+
+    #[test]
+    fn test_table_retention_parse() {
+        assert_eq!(
+            "met=30".parse(),
+            Ok(TableRetention {
+                table: "met".to_string(),
+                days: Some(30),
+            })
+        );
+        assert!("met=soon".parse::<TableRetention>().is_err());
+        assert!("player=30".parse::<TableRetention>().is_err());
+    }
+
+    #[test]
+    fn test_dry_run_report() {
+        let report = PurgeReport {
+            dry_run: true,
+            tables: vec![TablePurge {
+                table: "met".to_string(),
+                ids: vec!["met:1".to_string(), "met:2".to_string()],
+            }],
+        };
+        assert_eq!(report.total(), 2);
+        assert_eq!(
+            report.to_string(),
+            "met: would remove 2 records\n  met:1\n  met:2\n"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use serde_json::Value as JsonValue;
+use surrealdb::sql::Value;
+use surrealdb::{Connection, Surreal};
+
+/// Untyped access to every table of the database, for backups.
+///
+/// Tables are found with `INFO FOR DB`, so tables the models don't know about, such as
+/// `script_migration` and the graph relations, are covered too.
+#[derive(Debug, Clone)]
+pub struct TableRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> TableRepo<C> {
+    /// Create a new `TableRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        TableRepo { db }
+    }
+
+    /// The name of every table, sorted.
+    pub async fn tables(&self) -> surrealdb::Result<Vec<String>> {
+        let mut tables = tables(&self.info_for_db().await?);
+        tables.sort();
+        Ok(tables)
+    }
+
+    /// Every record of `table`, ordered by id.
+    pub async fn records(&self, table: &str) -> surrealdb::Result<Vec<Value>> {
+        let records: Value = self
+            .db
+            .query("SELECT * FROM type::table($table) ORDER BY id")
+            .bind(("table", table))
+            .await?
+            .take(0)?;
+        Ok(match records {
+            Value::Array(records) => records.0,
+            _ => Vec::new(),
+        })
+    }
+
+    /// How many records `table` holds.
+    pub async fn count(&self, table: &str) -> surrealdb::Result<usize> {
+        let count: Option<usize> = self
+            .db
+            .query("SELECT count() AS count FROM type::table($table) GROUP ALL")
+            .bind(("table", table))
+            .await?
+            .take((0, "count"))?;
+        Ok(count.unwrap_or_default())
+    }
+
+    /// Every `DEFINE` statement in the database, database level first and then per table.
+    pub async fn schema(&self) -> surrealdb::Result<Vec<String>> {
+        let info = self.info_for_db().await?;
+        let mut definitions = definitions(&info);
+        for table in tables(&info) {
+            let info: Option<JsonValue> = self
+                .db
+                .query(format!("INFO FOR TABLE `{}`", table))
+                .await?
+                .take(0)?;
+            definitions.extend(definitions_of_table(&info.unwrap_or_default()));
+        }
+        Ok(definitions)
+    }
+
+    /// Run `statements` as one query, failing if any of them does.
+    pub async fn execute(&self, statements: &[String]) -> surrealdb::Result<()> {
+        if statements.is_empty() {
+            return Ok(());
+        }
+        self.run(&statements.join("\n")).await
+    }
+
+    /// Run a SurrealQL script, failing if any of its statements does.
+    pub async fn run(&self, script: &str) -> surrealdb::Result<()> {
+        self.db.query(script).await?.check()?;
+        Ok(())
+    }
+
+    async fn info_for_db(&self) -> surrealdb::Result<JsonValue> {
+        let info: Option<JsonValue> = self.db.query("INFO FOR DB").await?.take(0)?;
+        Ok(info.unwrap_or_default())
+    }
+}
+
+/// The tables an `INFO FOR DB` result lists.
+fn tables(info: &JsonValue) -> Vec<String> {
+    // Older SurrealDB versions call it `tb`.
+    info.get("tables")
+        .or_else(|| info.get("tb"))
+        .and_then(JsonValue::as_object)
+        .map(|tables| tables.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// The statements in every sub-object of an `INFO FOR` result, ordered by name.
+fn definitions(info: &JsonValue) -> Vec<String> {
+    let Some(info) = info.as_object() else {
+        return Vec::new();
+    };
+    let mut keys = info.keys().collect::<Vec<_>>();
+    keys.sort();
+    keys.into_iter()
+        .filter_map(|key| info[key].as_object())
+        .flat_map(|definitions| {
+            let mut names = definitions.keys().collect::<Vec<_>>();
+            names.sort();
+            names
+                .into_iter()
+                .filter_map(|name| definitions[name].as_str().map(str::to_string))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Like `definitions`, but leaving out the foreign tables `INFO FOR TABLE` lists, which are
+/// already defined at database level.
+fn definitions_of_table(info: &JsonValue) -> Vec<String> {
+    let mut info = info.clone();
+    if let Some(info) = info.as_object_mut() {
+        info.remove("tables");
+        info.remove("ft");
+    }
+    definitions(&info)
+}
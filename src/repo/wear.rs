@@ -0,0 +1,63 @@
+use surrealdb::{Connection, Surreal};
+
+use crate::graph::avatar_timeline::Wear;
+use crate::graph::social_graph::{EdgeKind, NodeKind};
+use crate::repo::graph::CHUNK_SIZE;
+
+/// Typed access to the `wore` relations of the avatar timeline, `player->wore->avatar`.
+#[derive(Debug, Clone)]
+pub struct WearRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> WearRepo<C> {
+    pub const TABLE: &'static str = "wore";
+
+    /// Create a new `WearRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        WearRepo { db }
+    }
+
+    /// Replace every `wore` relation with `wears`, labelling the `player` and `avatar` records
+    /// they link, in queries of `CHUNK_SIZE` wears.
+    pub async fn replace_all(&self, wears: &[Wear]) -> surrealdb::Result<()> {
+        self.db
+            .query("DELETE type::table($table) RETURN NONE")
+            .bind(("table", Self::TABLE))
+            .await?
+            .check()?;
+        let sql = format!(
+            "FOR $wear IN $wears {{
+                LET $player = type::thing('{}', $wear.user_id);
+                LET $avatar = type::thing('{}', $wear.avatar);
+                UPDATE $player MERGE {{ label: $wear.display_name }} RETURN NONE;
+                UPDATE $avatar MERGE {{ label: $wear.avatar_name }} RETURN NONE;
+                RELATE $player->{}->$avatar CONTENT $wear RETURN NONE;
+            }};",
+            NodeKind::Player.table(),
+            NodeKind::Avatar.table(),
+            EdgeKind::Wore.table()
+        );
+        for chunk in wears.chunks(CHUNK_SIZE) {
+            self.db
+                .query(sql.as_str())
+                .bind(("wears", chunk))
+                .await?
+                .check()?;
+        }
+        Ok(())
+    }
+
+    /// Every wear, only of `user_id` if it is given, ordered by `user_id` and then `worn_from`.
+    pub async fn find(&self, user_id: Option<&str>) -> surrealdb::Result<Vec<Wear>> {
+        self.db
+            .query(
+                "SELECT * FROM type::table($table) WHERE $user_id = NONE OR user_id = $user_id \
+                ORDER BY user_id, worn_from",
+            )
+            .bind(("table", Self::TABLE))
+            .bind(("user_id", user_id))
+            .await?
+            .take(0)
+    }
+}
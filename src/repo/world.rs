@@ -0,0 +1,69 @@
+use surrealdb::{Connection, Surreal};
+
+use crate::models::world::World;
+use crate::repo::insert_strategy::{upsert_newest, BatchRepo, InsertStrategy};
+
+/// Typed access to the `world` table.
+///
+/// Records are keyed by `World::id`, e.g. `world:wrld_1234`. The social graph keeps its world
+/// nodes in the same table; those have a `label` and no `name`, and are skipped by every query
+/// here.
+#[derive(Debug, Clone)]
+pub struct WorldRepo<C: Connection> {
+    db: Surreal<C>,
+}
+
+impl<C: Connection> WorldRepo<C> {
+    pub const TABLE: &'static str = "world";
+
+    /// Create a new `WorldRepo` over `db`.
+    pub fn new(db: Surreal<C>) -> Self {
+        WorldRepo { db }
+    }
+
+    /// Get the world with `id`.
+    pub async fn get(&self, id: &str) -> surrealdb::Result<Option<World>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM type::thing('world', $id) WHERE name != NONE")
+            .bind(("id", id))
+            .await?
+            .take(0)
+    }
+
+    /// Every world, by name.
+    pub async fn all(&self) -> surrealdb::Result<Vec<World>> {
+        self.db
+            .query("SELECT *, meta::id(id) AS id FROM world WHERE name != NONE ORDER BY name")
+            .await?
+            .take(0)
+    }
+
+    /// Every world uploaded by `author_id`, by name.
+    pub async fn find_by_author(&self, author_id: &str) -> surrealdb::Result<Vec<World>> {
+        self.db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM world \
+                WHERE name != NONE AND author_id = $author_id ORDER BY name",
+            )
+            .bind(("author_id", author_id))
+            .await?
+            .take(0)
+    }
+}
+
+impl<C: Connection> BatchRepo<C> for WorldRepo<C> {
+    type Row = World;
+    const TABLE: &'static str = Self::TABLE;
+
+    /// Write every world unless a newer `version` of it is already stored, returning how many
+    /// were written.
+    ///
+    /// The cache changes between imports, so worlds are always upserted, whatever `strategy`.
+    async fn insert_batch(
+        &self,
+        worlds: &[World],
+        _strategy: InsertStrategy,
+    ) -> surrealdb::Result<usize> {
+        upsert_newest(&self.db, Self::TABLE, worlds).await
+    }
+}
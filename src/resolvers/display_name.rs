@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use surrealdb::{Connection, Surreal};
+
+use crate::import::importer::VrcxTables;
+use crate::models::gamelog_join_leave::GamelogJoinLeave;
+use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+use crate::repo::feed::{
+    FeedAvatarRepo, FeedBioRepo, FeedGpsRepo, FeedOnlineOfflineRepo, FeedStatusRepo,
+};
+use crate::repo::friend::FriendRepo;
+use crate::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+
+/// The span of time during which a `user_id` was seen using a `display_name`.
+///
+/// `first_seen` and `last_seen` are `None` when the name was only seen in a source without
+/// timestamps, such as `usr_friend_log_current`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct NameSpan {
+    pub user_id: String,
+    pub display_name: String,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl NameSpan {
+    /// Check if `at` falls inside the span. Missing bounds are treated as open.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.first_seen.is_none_or(|first| first <= at)
+            && self.last_seen.is_none_or(|last| at <= last)
+    }
+
+    fn extend(&mut self, seen_at: Option<DateTime<Utc>>) {
+        let Some(seen_at) = seen_at else {
+            return;
+        };
+        self.first_seen = Some(self.first_seen.map_or(seen_at, |first| first.min(seen_at)));
+        self.last_seen = Some(self.last_seen.map_or(seen_at, |last| last.max(seen_at)));
+    }
+}
+
+/// The result of resolving a `display_name` to a `user_id`.
+///
+/// # Variants
+/// - Resolved: exactly one `user_id` used the name at that time.
+/// - Ambiguous: several `user_id`s used the name at that time.
+/// - Unknown: no `user_id` was seen using the name at that time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Resolution {
+    Resolved(String),
+    Ambiguous(Vec<String>),
+    Unknown,
+}
+
+/// A `gamelog_join_leave` row whose `display_name` matches more than one `user_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct AmbiguousRow {
+    pub id: i64,
+    pub display_name: String,
+    pub candidates: Vec<String>,
+}
+
+/// What `DisplayNameResolver::backfill` did to a batch of rows.
+///
+/// # Values
+///
+/// - `backfilled` - The ids of the rows that got a `user_id`.
+/// - `ambiguous` - The rows that were left alone because the name matches several users.
+/// - `unresolved` - The ids of the rows whose name was never seen with a `user_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct BackfillReport {
+    pub backfilled: Vec<i64>,
+    pub ambiguous: Vec<AmbiguousRow>,
+    pub unresolved: Vec<i64>,
+}
+
+/// Builds a `user_id ↔ display_name` history and uses it to fill in missing `user_id`s.
+///
+/// VRChat users rename often, and older `gamelog_join_leave` rows only carry the name that was
+/// used at the time. The resolver collects every sighting of a `user_id` together with a
+/// `display_name`, and then answers which user was behind a name at a given moment.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use surrealdb_test::resolvers::display_name::{DisplayNameResolver, Resolution};
+///
+/// let mut resolver = DisplayNameResolver::new();
+/// resolver.observe("usr_a", "Alice", Some(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()));
+/// resolver.observe("usr_a", "Alicia", Some(Utc.with_ymd_and_hms(2023, 3, 1, 0, 0, 0).unwrap()));
+/// resolver.observe("usr_b", "Alice", Some(Utc.with_ymd_and_hms(2023, 6, 1, 0, 0, 0).unwrap()));
+///
+/// let jan = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+/// let jun = Utc.with_ymd_and_hms(2023, 6, 1, 0, 0, 0).unwrap();
+/// assert_eq!(resolver.resolve("Alice", jan), Resolution::Resolved("usr_a".to_string()));
+/// assert_eq!(resolver.resolve("Alice", jun), Resolution::Resolved("usr_b".to_string()));
+/// assert_eq!(resolver.resolve("Alicia", jan), Resolution::Unknown);
+/// assert_eq!(resolver.resolve("Bob", jan), Resolution::Unknown);
+/// assert_eq!(resolver.history("usr_a").len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DisplayNameResolver {
+    by_name: HashMap<String, HashMap<String, NameSpan>>,
+}
+
+impl DisplayNameResolver {
+    /// Create a new, empty `DisplayNameResolver`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a resolver from every name sighting in `tables`: the friends, the join/leave
+    /// rows with a `user_id`, and the avatar, status, bio, online/offline and GPS feeds.
+    pub fn from_tables(tables: &VrcxTables) -> Self {
+        let mut resolver = Self::new();
+        resolver.observe_friends(&tables.friends);
+        resolver.observe_join_leave(&tables.join_leave);
+        for row in &tables.feed_avatar {
+            resolver.observe(&row.user_id, &row.display_name, Some(row.created_at));
+        }
+        for row in &tables.feed_status {
+            resolver.observe(&row.user_id, &row.display_name, Some(row.created_at));
+        }
+        for row in &tables.feed_bio {
+            resolver.observe(&row.user_id, &row.display_name, Some(row.created_at));
+        }
+        for row in &tables.feed_online_offline {
+            resolver.observe(&row.user_id, &row.display_name, Some(row.created_at));
+        }
+        for row in &tables.feed_gps {
+            resolver.observe(&row.user_id, &row.display_name, Some(row.created_at));
+        }
+        resolver
+    }
+
+    /// Create a resolver from every name sighting stored in SurrealDB, as by `from_tables`.
+    pub async fn fetch<C: Connection>(db: &Surreal<C>) -> surrealdb::Result<Self> {
+        let tables = VrcxTables {
+            friends: FriendRepo::new(db.clone()).all().await?,
+            join_leave: GamelogJoinLeaveRepo::new(db.clone()).all().await?,
+            feed_avatar: FeedAvatarRepo::new(db.clone()).all().await?,
+            feed_status: FeedStatusRepo::new(db.clone()).all().await?,
+            feed_bio: FeedBioRepo::new(db.clone()).all().await?,
+            feed_online_offline: FeedOnlineOfflineRepo::new(db.clone()).all().await?,
+            feed_gps: FeedGpsRepo::new(db.clone()).all().await?,
+            ..Default::default()
+        };
+        Ok(Self::from_tables(&tables))
+    }
+
+    /// Record that `user_id` was seen using `display_name`, optionally at `seen_at`.
+    ///
+    /// Empty ids and names are ignored.
+    pub fn observe(
+        &mut self,
+        user_id: impl Into<String>,
+        display_name: impl Into<String>,
+        seen_at: Option<DateTime<Utc>>,
+    ) {
+        let user_id = user_id.into();
+        let display_name = display_name.into();
+        if user_id.is_empty() || display_name.is_empty() {
+            return;
+        }
+
+        self.by_name
+            .entry(display_name.clone())
+            .or_default()
+            .entry(user_id.clone())
+            .or_insert_with(|| NameSpan {
+                user_id,
+                display_name,
+                first_seen: None,
+                last_seen: None,
+            })
+            .extend(seen_at);
+    }
+
+    /// Record the current names of every friend.
+    pub fn observe_friends(&mut self, friends: &[UsrFriendLogCurrent]) {
+        for friend in friends {
+            self.observe(&friend.user_id, &friend.display_name, None);
+        }
+    }
+
+    /// Record the names of every `gamelog_join_leave` row that has a `user_id`.
+    pub fn observe_join_leave(&mut self, rows: &[GamelogJoinLeave]) {
+        for row in rows {
+            if let Some(user_id) = &row.user_id {
+                self.observe(user_id, &row.display_name, Some(row.created_at));
+            }
+        }
+    }
+
+    /// Every name `user_id` was seen using, oldest first.
+    pub fn history(&self, user_id: &str) -> Vec<NameSpan> {
+        let mut spans = self
+            .by_name
+            .values()
+            .filter_map(|users| users.get(user_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        spans.sort_by(|a, b| (a.first_seen, &a.display_name).cmp(&(b.first_seen, &b.display_name)));
+        spans
+    }
+
+    /// Resolve `display_name` to a `user_id` as of `at`.
+    ///
+    /// Only the users whose `NameSpan` of the name contains `at` are candidates. A name seen
+    /// without a timestamp, such as a friend's current name, counts at any time.
+    pub fn resolve(&self, display_name: &str, at: DateTime<Utc>) -> Resolution {
+        let Some(users) = self.by_name.get(display_name) else {
+            return Resolution::Unknown;
+        };
+
+        let mut candidates = users
+            .values()
+            .filter(|span| span.contains(at))
+            .map(|span| span.user_id.clone())
+            .collect::<Vec<_>>();
+        candidates.sort();
+        match candidates.len() {
+            0 => Resolution::Unknown,
+            1 => Resolution::Resolved(candidates.remove(0)),
+            _ => Resolution::Ambiguous(candidates),
+        }
+    }
+
+    /// Fill in `user_id` on every row that lacks one and whose name resolves unambiguously.
+    ///
+    /// Rows that already have a `user_id` are not touched.
+    pub fn backfill(&self, rows: &mut [GamelogJoinLeave]) -> BackfillReport {
+        let mut report = BackfillReport::default();
+
+        for row in rows.iter_mut().filter(|row| row.user_id.is_none()) {
+            match self.resolve(&row.display_name, row.created_at) {
+                Resolution::Resolved(user_id) => {
+                    row.user_id = Some(user_id);
+                    report.backfilled.push(row.id);
+                }
+                Resolution::Ambiguous(candidates) => report.ambiguous.push(AmbiguousRow {
+                    id: row.id,
+                    display_name: row.display_name.clone(),
+                    candidates,
+                }),
+                Resolution::Unknown => report.unresolved.push(row.id),
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::fixtures::vrcx::{Fixture, FixtureOptions};
+    use crate::import::importer::VrcxTables;
+    use crate::models::feed_status::FeedStatus;
+    use crate::models::gamelog_join_leave::GamelogJoinLeave;
+    use crate::models::usr_friend_log_current::UsrFriendLogCurrent;
+    use crate::resolvers::display_name::{AmbiguousRow, DisplayNameResolver, Resolution};
+
+    fn row(id: i64, display_name: &str, month: u32) -> GamelogJoinLeave {
+        let mut row = GamelogJoinLeave::new();
+        row.id = id;
+        row.display_name = display_name.to_string();
+        row.created_at = Utc.with_ymd_and_hms(2023, month, 1, 0, 0, 0).unwrap();
+        row
+    }
+
+    #[test]
+    fn test_resolve_reused_name_by_time() {
+        let mut resolver = DisplayNameResolver::new();
+        resolver.observe_join_leave(&[
+            GamelogJoinLeave {
+                user_id: Some("usr_a".to_string()),
+                ..row(1, "Sam", 1)
+            },
+            GamelogJoinLeave {
+                user_id: Some("usr_a".to_string()),
+                ..row(2, "Sam", 2)
+            },
+            GamelogJoinLeave {
+                user_id: Some("usr_b".to_string()),
+                ..row(3, "Sam", 6)
+            },
+        ]);
+
+        let feb = Utc.with_ymd_and_hms(2023, 2, 1, 0, 0, 0).unwrap();
+        let apr = Utc.with_ymd_and_hms(2023, 4, 1, 0, 0, 0).unwrap();
+        let jun = Utc.with_ymd_and_hms(2023, 6, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            resolver.resolve("Sam", feb),
+            Resolution::Resolved("usr_a".to_string())
+        );
+        assert_eq!(resolver.resolve("Sam", apr), Resolution::Unknown);
+        assert_eq!(
+            resolver.resolve("Sam", jun),
+            Resolution::Resolved("usr_b".to_string())
+        );
+
+        resolver.observe("usr_c", "Sam", None);
+        assert_eq!(
+            resolver.resolve("Sam", feb),
+            Resolution::Ambiguous(vec!["usr_a".to_string(), "usr_c".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_single_user_only_resolves_inside_the_span() {
+        let mut resolver = DisplayNameResolver::new();
+        resolver.observe("usr_a", "Sam", Some(row(1, "Sam", 3).created_at));
+        resolver.observe("usr_a", "Sam", Some(row(2, "Sam", 5).created_at));
+
+        let mut rows = vec![row(3, "Sam", 4), row(4, "Sam", 1)];
+        let report = resolver.backfill(&mut rows);
+
+        assert_eq!(rows[0].user_id, Some("usr_a".to_string()));
+        assert_eq!(rows[1].user_id, None);
+        assert_eq!(report.unresolved, vec![4]);
+    }
+
+    #[test]
+    fn test_feeds_are_observed() {
+        let tables = VrcxTables {
+            feed_status: vec![FeedStatus {
+                user_id: "usr_a".to_string(),
+                display_name: "Sam".to_string(),
+                created_at: row(1, "Sam", 2).created_at,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let resolver = DisplayNameResolver::from_tables(&tables);
+
+        assert_eq!(
+            resolver.resolve("Sam", row(1, "Sam", 2).created_at),
+            Resolution::Resolved("usr_a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_backfill() {
+        let mut resolver = DisplayNameResolver::new();
+        resolver.observe("usr_a", "Alice", None);
+        resolver.observe("usr_b", "Bob", None);
+        resolver.observe("usr_c", "Bob", None);
+
+        let mut rows = vec![row(1, "Alice", 1), row(2, "Bob", 1), row(3, "Carol", 1)];
+        let report = resolver.backfill(&mut rows);
+
+        assert_eq!(rows[0].user_id, Some("usr_a".to_string()));
+        assert_eq!(rows[1].user_id, None);
+        assert_eq!(report.backfilled, vec![1]);
+        assert_eq!(
+            report.ambiguous,
+            vec![AmbiguousRow {
+                id: 2,
+                display_name: "Bob".to_string(),
+                candidates: vec!["usr_b".to_string(), "usr_c".to_string()],
+            }]
+        );
+        assert_eq!(report.unresolved, vec![3]);
+    }
+
+    #[test]
+    fn test_resolver_backfills_renamed_players() {
+        let fixture = Fixture::generate(&FixtureOptions::default());
+        let rows = fixture
+            .join_leave
+            .iter()
+            .cloned()
+            .map(GamelogJoinLeave::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(fixture.players.iter().any(|player| player.names.len() > 1));
+
+        let mut resolver = DisplayNameResolver::new();
+        resolver.observe_join_leave(&rows);
+        resolver.observe_friends(
+            &fixture
+                .friends
+                .iter()
+                .cloned()
+                .map(UsrFriendLogCurrent::from)
+                .collect::<Vec<_>>(),
+        );
+
+        let mut stripped = rows.clone();
+        for row in &mut stripped {
+            row.user_id = None;
+        }
+        let report = resolver.backfill(&mut stripped);
+        assert_eq!(report.backfilled.len(), rows.len());
+        assert_eq!(stripped, rows);
+    }
+}
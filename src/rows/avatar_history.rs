@@ -0,0 +1,7 @@
+/// This is a row from the `_avatar_history` table, with the account prefix in front of its name.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct AvatarHistoryRow {
+    pub avatar_id: String,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub created_at: String,
+}
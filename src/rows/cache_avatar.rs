@@ -0,0 +1,17 @@
+/// This is a row from the `cache_avatar` table.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct CacheAvatarRow {
+    pub id: String,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub added_at: String,
+    pub author_id: String,
+    pub author_name: String,
+    pub created_at: String,
+    pub description: String,
+    pub image_url: String,
+    pub name: String,
+    pub release_status: String,
+    pub thumbnail_image_url: String,
+    pub updated_at: String,
+    pub version: i64,
+}
@@ -0,0 +1,15 @@
+/// This is a row from the `_feed_avatar` table, with the account prefix in front of its name.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedAvatarRow {
+    pub id: i64,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub created_at: String,
+    pub user_id: String,
+    pub display_name: String,
+    pub owner_id: String,
+    pub avatar_name: String,
+    pub current_avatar_image_url: String,
+    pub current_avatar_thumbnail_image_url: String,
+    pub previous_current_avatar_image_url: String,
+    pub previous_current_avatar_thumbnail_image_url: String,
+}
@@ -0,0 +1,11 @@
+/// This is a row from the `_feed_bio` table, with the account prefix in front of its name.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedBioRow {
+    pub id: i64,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub created_at: String,
+    pub user_id: String,
+    pub display_name: String,
+    pub bio: String,
+    pub previous_bio: String,
+}
@@ -0,0 +1,16 @@
+/// This is a row from the `_feed_online_offline` table, with the account prefix in front of its
+/// name.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedOnlineOfflineRow {
+    pub id: i64,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub created_at: String,
+    /// `type` in SQLite.
+    pub event: String,
+    pub user_id: String,
+    pub display_name: String,
+    pub location: String,
+    pub world_name: String,
+    pub time: i64,
+    pub group_name: String,
+}
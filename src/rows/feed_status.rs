@@ -0,0 +1,13 @@
+/// This is a row from the `_feed_status` table, with the account prefix in front of its name.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct FeedStatusRow {
+    pub id: i64,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub created_at: String,
+    pub user_id: String,
+    pub display_name: String,
+    pub status: String,
+    pub status_description: String,
+    pub previous_status: String,
+    pub previous_status_description: String,
+}
@@ -1,10 +1,9 @@
-use chrono::{DateTime, Utc};
-
 /// This is a row from the `gamelog_join_leave` table.
 #[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize)]
 pub struct GamelogJoinLeaveRow {
     pub id: i64,
-    pub created_at: DateTime<Utc>,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub created_at: String,
     // #[sqlx(rename = "type")]
     pub event: String,
     pub display_name: String,
@@ -0,0 +1,12 @@
+/// This is a row from the `_moderation` table, with the account prefix in front of its name.
+///
+/// `block` and `mute` are `0` or `1`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct ModerationRow {
+    pub user_id: String,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub updated_at: String,
+    pub display_name: String,
+    pub block: i64,
+    pub mute: i64,
+}
@@ -0,0 +1,21 @@
+/// This is a row from the `_notifications` table, with the account prefix in front of its name.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct NotificationRow {
+    /// The VRChat notification id, e.g. `not_1234`.
+    pub id: String,
+    /// The raw TEXT VRCX wrote, see `zaphkiel::timestamp::parse_timestamp`.
+    pub created_at: String,
+    /// `type` in SQLite.
+    pub kind: String,
+    pub sender_user_id: String,
+    pub sender_username: String,
+    pub receiver_user_id: String,
+    pub message: String,
+    pub world_id: String,
+    pub world_name: String,
+    pub image_url: String,
+    pub invite_message: String,
+    pub request_message: String,
+    pub response_message: String,
+    pub expired: i64,
+}
@@ -9,6 +9,7 @@
 ///    user_id: "usr_12345678-1234-1234-1234-123456789abc".to_string(),
 ///   display_name: "Some User".to_string(),
 ///  trust_level: TrustLevel::User,
+///  ..Default::default()
 /// };
 /// ```
 #[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize)]
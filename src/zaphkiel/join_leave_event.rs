@@ -1,3 +1,4 @@
+use std::fmt;
 use std::str::FromStr;
 
 /// JoinLeaveEvent is an enum that represents the different types of join/leave events that can be
@@ -11,13 +12,14 @@ use std::str::FromStr;
 /// # Examples
 ///
 /// ```
-/// use sqlx_vrchat_sqlite_test::zaphkiel::join_leave_event::JoinLeaveEvent;
+/// use surrealdb_test::zaphkiel::join_leave_event::JoinLeaveEvent;
 ///
 /// let join = JoinLeaveEvent::from("OnPlayerJoined");
 /// let leave = JoinLeaveEvent::from("OnPlayerLeft");
 ///
 /// assert_eq!(join, JoinLeaveEvent::Join);
 /// assert_eq!(leave, JoinLeaveEvent::Leave);
+/// assert_eq!(JoinLeaveEvent::from("OnPlayerRespawned"), JoinLeaveEvent::Other);
 /// ```
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
@@ -52,7 +54,7 @@ impl From<String> for JoinLeaveEvent {
             "onplayerjoined" => JoinLeaveEvent::Join,
             "onplayerleft" => JoinLeaveEvent::Leave,
 
-            _ => panic!("Unknown join/leave event: {}", value),
+            _ => JoinLeaveEvent::Other,
         }
     }
 }
@@ -64,3 +66,50 @@ impl FromStr for JoinLeaveEvent {
         Ok(Self::from(s))
     }
 }
+
+impl fmt::Display for JoinLeaveEvent {
+    /// Write the event the way VRCX stores it, which parses back to the same variant.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinLeaveEvent::Join => write!(f, "OnPlayerJoined"),
+            JoinLeaveEvent::Leave => write!(f, "OnPlayerLeft"),
+            JoinLeaveEvent::Other => write!(f, "Other"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::zaphkiel::join_leave_event::JoinLeaveEvent;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    fn variant() -> impl Strategy<Value = JoinLeaveEvent> {
+        select(vec![
+            JoinLeaveEvent::Join,
+            JoinLeaveEvent::Leave,
+            JoinLeaveEvent::Other,
+        ])
+    }
+
+    proptest! {
+        #[test]
+        fn test_join_leave_event_never_panics(s in "\\PC*") {
+            let _ = JoinLeaveEvent::from(s.as_str());
+        }
+
+        #[test]
+        fn test_join_leave_event_round_trips(variant in variant()) {
+            prop_assert_eq!(JoinLeaveEvent::from(variant.to_string()), variant);
+        }
+
+        #[test]
+        fn test_join_leave_event_ignores_case(variant in variant(), upper in any::<bool>()) {
+            let s = match upper {
+                true => variant.to_string().to_uppercase(),
+                false => variant.to_string().to_lowercase(),
+            };
+            prop_assert_eq!(s.parse::<JoinLeaveEvent>().unwrap(), variant);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::zaphkiel::world_instance::{ParseMode, WorldInstance};
+
+/// Where a player is, as VRCX writes it in `gamelog_join_leave.location`, `_feed_gps` and
+/// `_feed_online_offline`.
+///
+/// # Available Locations
+/// - Instance: a world instance, e.g. `wrld_1234:1234~region(eu)`.
+/// - Offline: `offline`.
+/// - Private: `private`, a location the player hides from you.
+/// - Traveling: `traveling` or `traveling:traveling`, between instances.
+/// - Unknown: an empty string, or anything that isn't one of the above.
+///
+/// # Serialization
+///
+/// The kind is stored next to the instance, so SurrealDB queries can filter on either, e.g.
+/// `WHERE location.kind = 'instance' AND location.instance.world_id = $world_id`.
+///
+/// ```
+/// use surrealdb_test::zaphkiel::location::Location;
+///
+/// let location = Location::from("wrld_1234:1234");
+/// let value = serde_json::to_value(&location).unwrap();
+/// assert_eq!(value["kind"], "instance");
+/// assert_eq!(value["instance"]["world_id"], "wrld_1234");
+///
+/// let value = serde_json::to_value(Location::Offline).unwrap();
+/// assert_eq!(value, serde_json::json!({ "kind": "offline" }));
+/// ```
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::zaphkiel::location::Location;
+///
+/// assert_eq!(Location::from("offline"), Location::Offline);
+/// assert_eq!(Location::from("traveling:traveling"), Location::Traveling);
+/// assert_eq!(Location::from(""), Location::Unknown);
+/// assert_eq!(
+///     Location::from("wrld_1234:1234").world_instance().unwrap().world_id,
+///     "wrld_1234"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+#[serde(tag = "kind", content = "instance", rename_all = "snake_case")]
+pub enum Location {
+    Instance(WorldInstance),
+    Offline,
+    Private,
+    Traveling,
+    #[default]
+    Unknown,
+}
+
+impl Location {
+    /// Parse a `Location`, with instances parsed as strictly as `mode` says.
+    ///
+    /// Anything that is neither a special location nor a `WorldInstance` is `Unknown`.
+    pub fn parse(s: &str, mode: ParseMode) -> Self {
+        match s {
+            "offline" => Location::Offline,
+            "private" => Location::Private,
+            "traveling" | "traveling:traveling" => Location::Traveling,
+            _ => match WorldInstance::parse(s, mode) {
+                Ok(world_instance) => Location::Instance(world_instance),
+                Err(_) => Location::Unknown,
+            },
+        }
+    }
+
+    /// The world instance the player is in, if they are in one.
+    pub fn world_instance(&self) -> Option<&WorldInstance> {
+        match self {
+            Location::Instance(world_instance) => Some(world_instance),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Location {
+    /// Parse a `Location` strictly.
+    /// See `Location::parse` for more information.
+    fn from(s: &str) -> Self {
+        Self::parse(s, ParseMode::Strict)
+    }
+}
+
+impl From<String> for Location {
+    /// Parse a `Location` strictly.
+    /// See `Location::parse` for more information.
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<WorldInstance> for Location {
+    fn from(world_instance: WorldInstance) -> Self {
+        Location::Instance(world_instance)
+    }
+}
+
+impl FromStr for Location {
+    type Err = std::string::ParseError;
+
+    fn from_str(s: &str) -> Result<Location, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for Location {
+    /// Write the location string VRCX would, which parses back to the same `Location`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use surrealdb_test::zaphkiel::location::Location;
+    ///
+    /// assert_eq!(Location::from("wrld_1234:1234").to_string(), "wrld_1234:1234");
+    /// assert_eq!(Location::Traveling.to_string(), "traveling");
+    /// assert_eq!(Location::Unknown.to_string(), "");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Instance(world_instance) => write!(f, "{}", world_instance),
+            Location::Offline => write!(f, "offline"),
+            Location::Private => write!(f, "private"),
+            Location::Traveling => write!(f, "traveling"),
+            Location::Unknown => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::zaphkiel::location::Location;
+    use crate::zaphkiel::world_instance::{ParseMode, WorldInstance};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_location_never_panics(s in "\\PC*") {
+            let _ = Location::parse(&s, ParseMode::Strict);
+            let _ = Location::parse(&s, ParseMode::Lenient);
+        }
+
+        #[test]
+        fn test_location_round_trips(s in "offline|private|traveling|wrld_[a-f0-9]{1,8}:[0-9]{1,5}|") {
+            let location = Location::from(s.as_str());
+            prop_assert_eq!(Location::from(location.to_string()), location);
+        }
+    }
+
+    #[test]
+    fn test_location_lenient_instance() {
+        let s = "wrld_1234:1234~private(usr_1234)junk";
+        assert_eq!(Location::parse(s, ParseMode::Strict), Location::Unknown);
+        assert_eq!(
+            Location::parse(s, ParseMode::Lenient),
+            Location::Instance(WorldInstance::parse(s, ParseMode::Lenient).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_location_serde_round_trips() {
+        let locations = [
+            Location::from("wrld_1234:1234~region(eu)"),
+            Location::Offline,
+            Location::Private,
+            Location::Traveling,
+            Location::Unknown,
+        ];
+        for location in locations {
+            let json = serde_json::to_string(&location).unwrap();
+            assert_eq!(serde_json::from_str::<Location>(&json).unwrap(), location);
+        }
+        // Rows written while `Traveling` could carry a target still read back.
+        assert_eq!(
+            serde_json::from_str::<Location>(r#"{"kind":"traveling","instance":null}"#).unwrap(),
+            Location::Traveling
+        );
+    }
+}
@@ -1,10 +1,50 @@
+/// Time an expression and report it as a `measure_time` span.
+///
+/// The span carries `name` and `elapsed_ms`. With `rows = |result| ...` it also carries `rows`
+/// and `rows_per_sec`, worked out from the result. The span is not entered while `$stmt` runs,
+/// so `$stmt` may `.await`.
 #[macro_export]
 macro_rules! measure_time {
     ($comment:expr => $stmt:expr) => {{
+        let comment = $comment;
+        let span = ::tracing::info_span!(
+            "measure_time",
+            name = %comment,
+            elapsed_ms = ::tracing::field::Empty,
+        );
         let start = std::time::Instant::now();
         let result = { $stmt };
         let duration = start.elapsed();
-        println!("Execution time for {}: {:?}", $comment, duration);
+        span.record("elapsed_ms", duration.as_secs_f64() * 1000.0);
+        span.in_scope(|| ::tracing::info!("{} took {:?}", comment, duration));
+        result
+    }};
+    ($comment:expr, rows = $rows:expr => $stmt:expr) => {{
+        let comment = $comment;
+        let span = ::tracing::info_span!(
+            "measure_time",
+            name = %comment,
+            elapsed_ms = ::tracing::field::Empty,
+            rows = ::tracing::field::Empty,
+            rows_per_sec = ::tracing::field::Empty,
+        );
+        let start = std::time::Instant::now();
+        let result = { $stmt };
+        let duration = start.elapsed();
+        let rows: usize = ($rows)(&result);
+        let rows_per_sec = rows as f64 / duration.as_secs_f64().max(f64::EPSILON);
+        span.record("elapsed_ms", duration.as_secs_f64() * 1000.0);
+        span.record("rows", rows);
+        span.record("rows_per_sec", rows_per_sec);
+        span.in_scope(|| {
+            ::tracing::info!(
+                "{} took {:?} ({} rows, {:.0} rows/s)",
+                comment,
+                duration,
+                rows,
+                rows_per_sec
+            )
+        });
         result
     }};
 }
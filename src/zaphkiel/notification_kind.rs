@@ -0,0 +1,108 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The kind of a notification, as VRCX writes it in `_notifications.type`.
+///
+/// # Available Variants
+/// - FriendRequest: `friendRequest`
+/// - Invite: `invite`, the sender invites the receiver to a world instance.
+/// - RequestInvite: `requestInvite`, the sender asks the receiver for an invite.
+/// - InviteResponse: `inviteResponse`
+/// - RequestInviteResponse: `requestInviteResponse`
+/// - Other
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::zaphkiel::notification_kind::NotificationKind;
+///
+/// assert_eq!(NotificationKind::from("invite"), NotificationKind::Invite);
+/// assert_eq!(NotificationKind::from("friendRequest"), NotificationKind::FriendRequest);
+/// assert_eq!(NotificationKind::from("votetokick"), NotificationKind::Other);
+/// assert_eq!(NotificationKind::RequestInvite.to_string(), "requestInvite");
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum NotificationKind {
+    FriendRequest,
+    Invite,
+    RequestInvite,
+    InviteResponse,
+    RequestInviteResponse,
+    #[default]
+    Other,
+}
+
+impl From<&str> for NotificationKind {
+    fn from(value: &str) -> Self {
+        let value = value.to_lowercase();
+        Self::from(value)
+    }
+}
+
+impl From<String> for NotificationKind {
+    fn from(value: String) -> Self {
+        let value = value.to_lowercase();
+        match value.as_str() {
+            "friendrequest" => NotificationKind::FriendRequest,
+            "invite" => NotificationKind::Invite,
+            "requestinvite" => NotificationKind::RequestInvite,
+            "inviteresponse" => NotificationKind::InviteResponse,
+            "requestinviteresponse" => NotificationKind::RequestInviteResponse,
+            _ => NotificationKind::Other,
+        }
+    }
+}
+
+impl FromStr for NotificationKind {
+    type Err = std::string::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for NotificationKind {
+    /// Write the kind the way VRCX stores it, which parses back to the same variant.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotificationKind::FriendRequest => write!(f, "friendRequest"),
+            NotificationKind::Invite => write!(f, "invite"),
+            NotificationKind::RequestInvite => write!(f, "requestInvite"),
+            NotificationKind::InviteResponse => write!(f, "inviteResponse"),
+            NotificationKind::RequestInviteResponse => write!(f, "requestInviteResponse"),
+            NotificationKind::Other => write!(f, "Other"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::zaphkiel::notification_kind::NotificationKind;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    fn variant() -> impl Strategy<Value = NotificationKind> {
+        select(vec![
+            NotificationKind::FriendRequest,
+            NotificationKind::Invite,
+            NotificationKind::RequestInvite,
+            NotificationKind::InviteResponse,
+            NotificationKind::RequestInviteResponse,
+            NotificationKind::Other,
+        ])
+    }
+
+    proptest! {
+        #[test]
+        fn test_notification_kind_never_panics(s in "\\PC*") {
+            let _ = NotificationKind::from(s.as_str());
+        }
+
+        #[test]
+        fn test_notification_kind_round_trips(variant in variant()) {
+            prop_assert_eq!(NotificationKind::from(variant.to_string()), variant);
+        }
+    }
+}
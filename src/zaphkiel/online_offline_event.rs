@@ -0,0 +1,92 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A friend coming online or going offline, as VRCX writes it in `_feed_online_offline.type`.
+///
+/// # Available Variants
+/// - Online
+/// - Offline
+/// - Other
+///
+/// # Examples
+///
+/// ```
+/// use surrealdb_test::zaphkiel::online_offline_event::OnlineOfflineEvent;
+///
+/// assert_eq!(OnlineOfflineEvent::from("Online"), OnlineOfflineEvent::Online);
+/// assert_eq!(OnlineOfflineEvent::from("Offline"), OnlineOfflineEvent::Offline);
+/// assert_eq!(OnlineOfflineEvent::from("Away"), OnlineOfflineEvent::Other);
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum OnlineOfflineEvent {
+    Online,
+    Offline,
+    #[default]
+    Other,
+}
+
+impl From<&str> for OnlineOfflineEvent {
+    fn from(value: &str) -> Self {
+        let value = value.to_lowercase();
+        Self::from(value)
+    }
+}
+
+impl From<String> for OnlineOfflineEvent {
+    fn from(value: String) -> Self {
+        let value = value.to_lowercase();
+        match value.as_str() {
+            "online" => OnlineOfflineEvent::Online,
+            "offline" => OnlineOfflineEvent::Offline,
+            _ => OnlineOfflineEvent::Other,
+        }
+    }
+}
+
+impl FromStr for OnlineOfflineEvent {
+    type Err = std::string::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for OnlineOfflineEvent {
+    /// Write the event the way VRCX stores it, which parses back to the same variant.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnlineOfflineEvent::Online => write!(f, "Online"),
+            OnlineOfflineEvent::Offline => write!(f, "Offline"),
+            OnlineOfflineEvent::Other => write!(f, "Other"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::zaphkiel::online_offline_event::OnlineOfflineEvent;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    fn variant() -> impl Strategy<Value = OnlineOfflineEvent> {
+        select(vec![
+            OnlineOfflineEvent::Online,
+            OnlineOfflineEvent::Offline,
+            OnlineOfflineEvent::Other,
+        ])
+    }
+
+    proptest! {
+        #[test]
+        fn test_online_offline_event_never_panics(s in "\\PC*") {
+            let _ = OnlineOfflineEvent::from(s.as_str());
+        }
+
+        #[test]
+        fn test_online_offline_event_round_trips(variant in variant()) {
+            prop_assert_eq!(OnlineOfflineEvent::from(variant.to_string()), variant);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Who can see a world or avatar, as VRChat reports it in `cache_world` and `cache_avatar`.
+///
+/// # Release Statuses
+/// - Unknown
+/// - Public
+/// - Private
+/// - Hidden
+/// - All, used by VRChat as a search filter
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::zaphkiel::release_status::ReleaseStatus;
+///
+/// assert_eq!(ReleaseStatus::from("public"), ReleaseStatus::Public);
+/// assert_eq!(ReleaseStatus::from("Private"), ReleaseStatus::Private);
+/// assert_eq!(ReleaseStatus::from(""), ReleaseStatus::Unknown);
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum ReleaseStatus {
+    #[default]
+    Unknown,
+    Public,
+    Private,
+    Hidden,
+    All,
+}
+
+impl From<&str> for ReleaseStatus {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "public" => ReleaseStatus::Public,
+            "private" => ReleaseStatus::Private,
+            "hidden" => ReleaseStatus::Hidden,
+            "all" => ReleaseStatus::All,
+            _ => ReleaseStatus::Unknown,
+        }
+    }
+}
+
+impl From<String> for ReleaseStatus {
+    fn from(value: String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl FromStr for ReleaseStatus {
+    type Err = std::string::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for ReleaseStatus {
+    /// Write the release status the way VRChat does, which parses back to the same variant.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReleaseStatus::Unknown => "",
+            ReleaseStatus::Public => "public",
+            ReleaseStatus::Private => "private",
+            ReleaseStatus::Hidden => "hidden",
+            ReleaseStatus::All => "all",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::zaphkiel::release_status::ReleaseStatus;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    fn variant() -> impl Strategy<Value = ReleaseStatus> {
+        select(vec![
+            ReleaseStatus::Unknown,
+            ReleaseStatus::Public,
+            ReleaseStatus::Private,
+            ReleaseStatus::Hidden,
+            ReleaseStatus::All,
+        ])
+    }
+
+    proptest! {
+        #[test]
+        fn test_release_status_never_panics(s in "\\PC*") {
+            let _ = ReleaseStatus::from(s.as_str());
+        }
+
+        #[test]
+        fn test_release_status_round_trips(status in variant()) {
+            prop_assert_eq!(ReleaseStatus::from(status.to_string()), status);
+        }
+    }
+}
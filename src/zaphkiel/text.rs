@@ -0,0 +1,15 @@
+/// `None` for an empty or whitespace-only column, the column as it is otherwise.
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::zaphkiel::text::non_empty;
+///
+/// assert_eq!(non_empty(" ".to_string()), None);
+/// assert_eq!(non_empty("test ".to_string()), Some("test ".to_string()));
+/// ```
+pub fn non_empty(value: String) -> Option<String> {
+    match value.trim() {
+        "" => None,
+        _ => Some(value),
+    }
+}
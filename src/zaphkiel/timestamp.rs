@@ -0,0 +1,377 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::offset::LocalResult;
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// The timezone a timestamp without an offset was written in.
+///
+/// VRCX writes `created_at` as TEXT. Newer versions write UTC with a `Z`, older versions and game
+/// log files write local time without an offset. Timestamps that carry an offset ignore this.
+///
+/// # Available Timezones
+/// - Utc: `utc`.
+/// - Local: `local`, the timezone of this machine.
+/// - Named: an IANA name such as `Europe/Berlin`.
+/// - Fixed: an offset such as `+09:00`, `-0500` or `+01`.
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::zaphkiel::timestamp::SourceTimezone;
+///
+/// let timezone: SourceTimezone = "Asia/Tokyo".parse().unwrap();
+/// assert_eq!(timezone.to_string(), "Asia/Tokyo");
+/// assert_eq!("+09:00".parse::<SourceTimezone>().unwrap().to_string(), "+09:00");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SourceTimezone {
+    Utc,
+    #[default]
+    Local,
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl SourceTimezone {
+    /// Turn a local date and time in this timezone into UTC.
+    fn to_utc(self, naive: &NaiveDateTime) -> LocalResult<DateTime<Utc>> {
+        match self {
+            SourceTimezone::Utc => LocalResult::Single(Utc.from_utc_datetime(naive)),
+            SourceTimezone::Local => Local
+                .from_local_datetime(naive)
+                .map(|at| at.with_timezone(&Utc)),
+            SourceTimezone::Named(tz) => tz
+                .from_local_datetime(naive)
+                .map(|at| at.with_timezone(&Utc)),
+            SourceTimezone::Fixed(offset) => offset
+                .from_local_datetime(naive)
+                .map(|at| at.with_timezone(&Utc)),
+        }
+    }
+
+    /// The local date and time of `at` in this timezone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use surrealdb_test::zaphkiel::timestamp::SourceTimezone;
+    ///
+    /// let at = Utc.with_ymd_and_hms(2023, 4, 29, 20, 0, 0).unwrap();
+    /// let tokyo: SourceTimezone = "Asia/Tokyo".parse().unwrap();
+    /// assert_eq!(tokyo.local(&at).to_string(), "2023-04-30 05:00:00");
+    /// assert_eq!(SourceTimezone::Utc.local(&at).to_string(), "2023-04-29 20:00:00");
+    /// ```
+    pub fn local(self, at: &DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            SourceTimezone::Utc => at.naive_utc(),
+            SourceTimezone::Local => at.with_timezone(&Local).naive_local(),
+            SourceTimezone::Named(tz) => at.with_timezone(&tz).naive_local(),
+            SourceTimezone::Fixed(offset) => at.with_timezone(&offset).naive_local(),
+        }
+    }
+}
+
+impl FromStr for SourceTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utc" | "z" => return Ok(SourceTimezone::Utc),
+            "local" => return Ok(SourceTimezone::Local),
+            _ => {}
+        }
+        if s.starts_with(['+', '-']) {
+            return parse_offset(s)
+                .map(SourceTimezone::Fixed)
+                .ok_or_else(|| format!("Unknown UTC offset: {}", s));
+        }
+        s.parse::<Tz>()
+            .map(SourceTimezone::Named)
+            .map_err(|_| format!("Unknown timezone: {}", s))
+    }
+}
+
+impl fmt::Display for SourceTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceTimezone::Utc => write!(f, "utc"),
+            SourceTimezone::Local => write!(f, "local"),
+            SourceTimezone::Named(tz) => write!(f, "{}", tz.name()),
+            SourceTimezone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+impl serde::Serialize for SourceTimezone {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SourceTimezone {
+    /// Read the timezone the way `FromStr` parses it, e.g. from a `?tz=` query parameter.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parse `+09:00`, `-0500` or `+01`.
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let (sign, digits) = s.split_at(1);
+    let digits = digits.replace(':', "");
+    if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if minutes >= 60 {
+        return None;
+    }
+    let seconds = (hours * 60 + minutes) * 60;
+    match sign {
+        "-" => FixedOffset::west_opt(seconds),
+        _ => FixedOffset::east_opt(seconds),
+    }
+}
+
+/// What went wrong while parsing a timestamp.
+///
+/// - `Empty`: The string is empty.
+/// - `UnknownFormat`: The string is not in any known VRCX format.
+/// - `OutOfRange`: The date or epoch is out of range.
+/// - `NonexistentLocalTime`: The local time was skipped by a daylight saving change.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum TimestampParseErrorKind {
+    Empty,
+    #[default]
+    UnknownFormat,
+    OutOfRange,
+    NonexistentLocalTime,
+}
+
+/// A timestamp that could not be parsed, with the original string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct TimestampParseError {
+    pub kind: TimestampParseErrorKind,
+    pub raw: String,
+}
+
+impl fmt::Display for TimestampParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            TimestampParseErrorKind::Empty => "empty timestamp",
+            TimestampParseErrorKind::UnknownFormat => "unknown timestamp format",
+            TimestampParseErrorKind::OutOfRange => "timestamp out of range",
+            TimestampParseErrorKind::NonexistentLocalTime => "local time does not exist",
+        };
+        write!(f, "{}: `{}`", message, self.raw)
+    }
+}
+
+impl Error for TimestampParseError {}
+
+/// Formats with an offset, e.g. `2023-04-29 10:00:00+09:00`.
+const OFFSET_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f%:z",
+    "%Y-%m-%d %H:%M:%S%.f%:z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+];
+
+/// Formats without an offset, e.g. `2023-04-29 10:00:00` or the game log's `2023.04.29 10:00:00`.
+const NAIVE_FORMATS: [&str; 6] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y.%m.%d %H:%M:%S%.f",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parse a VRCX timestamp into UTC.
+///
+/// # What it does
+///
+/// * Digits only are a Unix epoch: seconds if 10 digits or fewer, else milliseconds.
+/// * A trailing `Z` means UTC, and an offset such as `+09:00` is used as it is.
+/// * Anything else is taken to be local time in `timezone`. A time repeated by a daylight
+///   saving change is the earlier of the two.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use surrealdb_test::zaphkiel::timestamp::{parse_timestamp, SourceTimezone};
+///
+/// let expected = Utc.with_ymd_and_hms(2023, 4, 29, 1, 0, 0).unwrap();
+/// let tokyo = "Asia/Tokyo".parse().unwrap();
+///
+/// assert_eq!(parse_timestamp("2023-04-29T01:00:00.000Z", tokyo), Ok(expected));
+/// assert_eq!(parse_timestamp("2023-04-29 10:00:00+09:00", SourceTimezone::Utc), Ok(expected));
+/// assert_eq!(parse_timestamp("2023.04.29 10:00:00", tokyo), Ok(expected));
+/// assert_eq!(parse_timestamp("1682730000", tokyo), Ok(expected));
+/// assert!(parse_timestamp("yesterday", tokyo).is_err());
+/// ```
+pub fn parse_timestamp(
+    raw: &str,
+    timezone: SourceTimezone,
+) -> Result<DateTime<Utc>, TimestampParseError> {
+    let error = |kind| TimestampParseError {
+        kind,
+        raw: raw.to_string(),
+    };
+    let s = raw.trim();
+    if s.is_empty() {
+        return Err(error(TimestampParseErrorKind::Empty));
+    }
+
+    if s.bytes().all(|byte| byte.is_ascii_digit()) {
+        let epoch = s
+            .parse::<i64>()
+            .map_err(|_| error(TimestampParseErrorKind::OutOfRange))?;
+        let at = match s.len() {
+            ..=10 => Utc.timestamp_opt(epoch, 0),
+            _ => Utc.timestamp_millis_opt(epoch),
+        };
+        return at
+            .single()
+            .ok_or_else(|| error(TimestampParseErrorKind::OutOfRange));
+    }
+
+    if let Some(utc) = s.strip_suffix(['Z', 'z']) {
+        return parse_naive(utc)
+            .map(|naive| Utc.from_utc_datetime(&naive))
+            .ok_or_else(|| error(TimestampParseErrorKind::UnknownFormat));
+    }
+
+    for format in OFFSET_FORMATS {
+        if let Ok(at) = DateTime::parse_from_str(s, format) {
+            return Ok(at.with_timezone(&Utc));
+        }
+    }
+
+    let naive = parse_naive(s).ok_or_else(|| error(TimestampParseErrorKind::UnknownFormat))?;
+    match timezone.to_utc(&naive) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => Ok(at),
+        LocalResult::None => Err(error(TimestampParseErrorKind::NonexistentLocalTime)),
+    }
+}
+
+fn parse_naive(s: &str) -> Option<NaiveDateTime> {
+    NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::zaphkiel::timestamp::{parse_timestamp, SourceTimezone, TimestampParseErrorKind};
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        let expected = Utc.with_ymd_and_hms(2023, 4, 29, 10, 0, 0).unwrap();
+        let formats = [
+            "2023-04-29T10:00:00.000Z",
+            "2023-04-29T10:00:00Z",
+            "2023-04-29 10:00:00.000Z",
+            "2023-04-29T10:00:00+00:00",
+            "2023-04-29 10:00:00+00:00",
+            "2023-04-29T12:00:00.000+02:00",
+            "2023-04-29T05:00:00-0500",
+            "2023-04-29 10:00:00",
+            "2023-04-29T10:00:00",
+            "2023-04-29 10:00",
+            "2023.04.29 10:00:00",
+            "2023/04/29 10:00:00",
+            "1682762400",
+            "1682762400000",
+            "  2023-04-29 10:00:00  ",
+        ];
+        for raw in formats {
+            assert_eq!(
+                parse_timestamp(raw, SourceTimezone::Utc),
+                Ok(expected),
+                "{}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_timestamp_source_timezone() {
+        let naive = "2023-04-29 10:00:00";
+        let utc = parse_timestamp(naive, SourceTimezone::Utc).unwrap();
+        let berlin = parse_timestamp(naive, "Europe/Berlin".parse().unwrap()).unwrap();
+        let fixed = parse_timestamp(naive, "-05:00".parse().unwrap()).unwrap();
+        assert_eq!(utc - berlin, Duration::hours(2));
+        assert_eq!(fixed - utc, Duration::hours(5));
+
+        let zulu = "2023-04-29T10:00:00.000Z";
+        assert_eq!(
+            parse_timestamp(zulu, "Europe/Berlin".parse().unwrap()),
+            Ok(utc)
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp_daylight_saving() {
+        let berlin = "Europe/Berlin".parse().unwrap();
+        let skipped = parse_timestamp("2023-03-26 02:30:00", berlin).unwrap_err();
+        assert_eq!(skipped.kind, TimestampParseErrorKind::NonexistentLocalTime);
+
+        let repeated = parse_timestamp("2023-10-29 02:30:00", berlin).unwrap();
+        assert_eq!(
+            repeated,
+            Utc.with_ymd_and_hms(2023, 10, 29, 0, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp_errors() {
+        let errors = [
+            ("", TimestampParseErrorKind::Empty),
+            ("yesterday", TimestampParseErrorKind::UnknownFormat),
+            (
+                "2023-13-01 10:00:00",
+                TimestampParseErrorKind::UnknownFormat,
+            ),
+            (
+                "2023-04-29T10:00:00.000ZZ",
+                TimestampParseErrorKind::UnknownFormat,
+            ),
+            ("99999999999999999999", TimestampParseErrorKind::OutOfRange),
+        ];
+        for (raw, kind) in errors {
+            let error = parse_timestamp(raw, SourceTimezone::Utc).unwrap_err();
+            assert_eq!((error.kind, error.raw.as_str()), (kind, raw));
+        }
+    }
+
+    #[test]
+    fn test_source_timezone_from_str() {
+        for s in ["utc", "local", "Asia/Tokyo", "+09:00", "-05:30"] {
+            let timezone: SourceTimezone = s.parse().unwrap();
+            assert_eq!(timezone.to_string(), s);
+        }
+        assert_eq!(
+            "+0900".parse::<SourceTimezone>().unwrap().to_string(),
+            "+09:00"
+        );
+        assert!("Mars/Olympus".parse::<SourceTimezone>().is_err());
+        assert!("+25:00".parse::<SourceTimezone>().is_err());
+    }
+}
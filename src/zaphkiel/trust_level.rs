@@ -1,3 +1,6 @@
+use std::fmt;
+use std::str::FromStr;
+
 /// Trust level of a user.
 ///
 /// # Trust Levels
@@ -22,6 +25,9 @@
 ///
 /// let trust_level = TrustLevel::from("USER");
 /// assert_eq!(trust_level, TrustLevel::User);
+///
+/// let trust_level = TrustLevel::from("legend");
+/// assert_eq!(trust_level, TrustLevel::Unknown);
 /// ```
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
@@ -62,7 +68,73 @@ impl From<String> for TrustLevel {
             "trusted_user" => TrustLevel::TrustedUser,
             "vrchat_team" => TrustLevel::VRChatTeam,
 
-            _ => panic!("Unknown trust level: {}", value),
+            _ => TrustLevel::Unknown,
+        }
+    }
+}
+
+impl FromStr for TrustLevel {
+    type Err = std::string::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for TrustLevel {
+    /// Write the trust level the way VRCX stores it, which parses back to the same variant.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TrustLevel::Unknown => "Unknown",
+            TrustLevel::Visitor => "Visitor",
+            TrustLevel::NewUser => "New User",
+            TrustLevel::User => "User",
+            TrustLevel::KnownUser => "Known User",
+            TrustLevel::TrustedUser => "Trusted User",
+            TrustLevel::VRChatTeam => "VRChat Team",
+            TrustLevel::Nuisance => "Nuisance",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::zaphkiel::trust_level::TrustLevel;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    fn variant() -> impl Strategy<Value = TrustLevel> {
+        select(vec![
+            TrustLevel::Unknown,
+            TrustLevel::Visitor,
+            TrustLevel::NewUser,
+            TrustLevel::User,
+            TrustLevel::KnownUser,
+            TrustLevel::TrustedUser,
+            TrustLevel::VRChatTeam,
+            TrustLevel::Nuisance,
+        ])
+    }
+
+    proptest! {
+        #[test]
+        fn test_trust_level_never_panics(s in "\\PC*") {
+            let _ = TrustLevel::from(s.as_str());
+        }
+
+        #[test]
+        fn test_trust_level_round_trips(variant in variant()) {
+            prop_assert_eq!(TrustLevel::from(variant.to_string()), variant);
+        }
+
+        #[test]
+        fn test_trust_level_ignores_case(variant in variant(), upper in any::<bool>()) {
+            let s = match upper {
+                true => variant.to_string().to_uppercase(),
+                false => variant.to_string().to_lowercase(),
+            };
+            prop_assert_eq!(s.parse::<TrustLevel>().unwrap(), variant);
         }
     }
 }
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The status a player sets in VRChat, as VRCX writes it in `_feed_status`.
+///
+/// # Statuses
+/// - Unknown
+/// - Join Me
+/// - Active
+/// - Ask Me
+/// - Busy
+/// - Offline
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::zaphkiel::user_status::UserStatus;
+///
+/// assert_eq!(UserStatus::from("join me"), UserStatus::JoinMe);
+/// assert_eq!(UserStatus::from("Ask Me"), UserStatus::AskMe);
+/// assert_eq!(UserStatus::from("busy"), UserStatus::Busy);
+/// assert_eq!(UserStatus::from("sleeping"), UserStatus::Unknown);
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum UserStatus {
+    #[default]
+    Unknown,
+    JoinMe,
+    Active,
+    AskMe,
+    Busy,
+    Offline,
+}
+
+impl From<&str> for UserStatus {
+    fn from(value: &str) -> Self {
+        let value = value.to_lowercase();
+        Self::from(value)
+    }
+}
+
+impl From<String> for UserStatus {
+    fn from(value: String) -> Self {
+        let value = value.to_lowercase();
+        match value.as_str() {
+            "join me" => UserStatus::JoinMe,
+            "active" => UserStatus::Active,
+            "ask me" => UserStatus::AskMe,
+            "busy" => UserStatus::Busy,
+            "offline" => UserStatus::Offline,
+
+            "joinme" | "join_me" => UserStatus::JoinMe,
+            "askme" | "ask_me" => UserStatus::AskMe,
+
+            _ => UserStatus::Unknown,
+        }
+    }
+}
+
+impl FromStr for UserStatus {
+    type Err = std::string::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for UserStatus {
+    /// Write the status the way VRChat does, which parses back to the same variant.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            UserStatus::Unknown => "",
+            UserStatus::JoinMe => "join me",
+            UserStatus::Active => "active",
+            UserStatus::AskMe => "ask me",
+            UserStatus::Busy => "busy",
+            UserStatus::Offline => "offline",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::zaphkiel::user_status::UserStatus;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    fn variant() -> impl Strategy<Value = UserStatus> {
+        select(vec![
+            UserStatus::Unknown,
+            UserStatus::JoinMe,
+            UserStatus::Active,
+            UserStatus::AskMe,
+            UserStatus::Busy,
+            UserStatus::Offline,
+        ])
+    }
+
+    proptest! {
+        #[test]
+        fn test_user_status_never_panics(s in "\\PC*") {
+            let _ = UserStatus::from(s.as_str());
+        }
+
+        #[test]
+        fn test_user_status_round_trips(variant in variant()) {
+            prop_assert_eq!(UserStatus::from(variant.to_string()), variant);
+        }
+
+        #[test]
+        fn test_user_status_ignores_case(variant in variant(), upper in any::<bool>()) {
+            let s = match upper {
+                true => variant.to_string().to_uppercase(),
+                false => variant.to_string().to_lowercase(),
+            };
+            prop_assert_eq!(s.parse::<UserStatus>().unwrap(), variant);
+        }
+    }
+}
@@ -1,7 +1,12 @@
+use std::error::Error;
+use std::fmt;
 use std::str::FromStr;
 
 use crate::zaphkiel::world_regions::Regions;
 
+/// Locations VRChat writes in place of an instance, which are not `WorldInstance`s.
+pub const SPECIAL_LOCATIONS: [&str; 4] = ["offline", "private", "traveling", "traveling:traveling"];
+
 /// A struct representing a world instance.
 ///
 /// # Examples
@@ -34,6 +39,11 @@ use crate::zaphkiel::world_regions::Regions;
 /// - `hidden`: The optional hidden of the world instance.
 /// - `private`: The optional private of the world instance.
 /// - `region`: The optional region of the world instance.
+/// - `friends`: The optional friends of the world instance.
+/// - `group`: The optional group of the world instance.
+/// - `group_access_type`: The optional group access type of the world instance.
+/// - `can_request_invite`: Whether the `canRequestInvite` flag is set.
+/// - `strict`: Whether the `strict` flag is set.
 ///
 /// # Creating a new `WorldInstance`:
 /// ```
@@ -61,7 +71,7 @@ use crate::zaphkiel::world_regions::Regions;
 ///
 /// let world_instance_string = "wrld_1234:1234~private(usr_1234)".to_string();
 ///
-/// let world_instance: WorldInstance = WorldInstance::from(world_instance_string);
+/// let world_instance = WorldInstance::try_from(world_instance_string).unwrap();
 ///
 /// assert_eq!(world_instance.world_id, "wrld_1234");
 /// assert_eq!(world_instance.instance_id, "1234");
@@ -77,6 +87,12 @@ pub struct WorldInstance {
     pub region: Option<Regions>,
     pub friends: Option<String>,
     pub group: Option<String>,
+    #[serde(default)]
+    pub group_access_type: Option<String>,
+    #[serde(default)]
+    pub can_request_invite: bool,
+    #[serde(default)]
+    pub strict: bool,
 }
 
 impl WorldInstance {
@@ -87,179 +103,596 @@ impl WorldInstance {
     }
 }
 
-/// A struct representing a world instance parse error.
+/// How strictly `WorldInstance::parse` follows the location grammar.
+///
+/// # Available Modes
+/// - Strict: any malformed tag is an error. This is what `FromStr` uses.
+/// - Lenient: malformed and unknown tags are skipped, a missing `)` closes at the end of the tag
+///   and text after `)` is ignored. Meant for importing legacy data.
+///
+/// Both modes reject a missing world or instance id and special locations.
+///
+/// # Examples
+/// ```
+/// use surrealdb_test::zaphkiel::world_instance::ParseMode;
+///
+/// let mode: ParseMode = "lenient".parse().unwrap();
+/// assert_eq!(mode, ParseMode::Lenient);
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
+)]
+pub enum ParseMode {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+impl FromStr for ParseMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(ParseMode::Strict),
+            "lenient" => Ok(ParseMode::Lenient),
+            _ => Err(format!("Unknown parse mode: {}", s)),
+        }
+    }
+}
+
+/// What went wrong while parsing a `WorldInstance`.
 ///
 /// Valid parse errors:
 ///
 /// - `Empty`: The string is empty.
-/// - `InvalidFormat`: The string is not in the correct format.
-/// - `InvalidWorldId`: The world id is invalid.
-/// - `InvalidInstanceId`: The instance id is invalid.
-/// - `InvalidOptionalField`: The optional field is invalid.
+/// - `SpecialLocation`: The string is one of `SPECIAL_LOCATIONS`, e.g. `offline`.
+/// - `InvalidFormat`: The string is not `<world_id>:<instance_id>`, or has a second `:`.
+/// - `InvalidWorldId`: The world id is empty or contains `(` or `)`.
+/// - `InvalidInstanceId`: The instance id is empty or contains `(` or `)`.
+/// - `InvalidOptionalField`: A tag is empty, its value contains `(`, or a flag has a value.
+/// - `UnknownKey`: A tag's key is not one of the known keys or flags.
+/// - `MissingValue`: A key that takes a value has no `(...)`.
+/// - `UnclosedParen`: A tag's `(` is never closed.
+/// - `TrailingCharacters`: A tag has text after its closing `)`.
 /// - `Other`: Other errors.
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
 )]
-pub enum WorldInstanceParseError {
+pub enum WorldInstanceParseErrorKind {
     Empty,
+    SpecialLocation,
     InvalidFormat,
     InvalidWorldId,
     InvalidInstanceId,
     InvalidOptionalField,
+    UnknownKey,
+    MissingValue,
+    UnclosedParen,
+    TrailingCharacters,
     #[default]
     Other,
 }
 
-impl FromStr for WorldInstance {
-    type Err = WorldInstanceParseError;
+impl fmt::Display for WorldInstanceParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            WorldInstanceParseErrorKind::Empty => "empty location",
+            WorldInstanceParseErrorKind::SpecialLocation => "special location",
+            WorldInstanceParseErrorKind::InvalidFormat => "expected `<world_id>:<instance_id>`",
+            WorldInstanceParseErrorKind::InvalidWorldId => "invalid world id",
+            WorldInstanceParseErrorKind::InvalidInstanceId => "invalid instance id",
+            WorldInstanceParseErrorKind::InvalidOptionalField => "invalid tag",
+            WorldInstanceParseErrorKind::UnknownKey => "unknown tag",
+            WorldInstanceParseErrorKind::MissingValue => "tag is missing its `(value)`",
+            WorldInstanceParseErrorKind::UnclosedParen => "unclosed `(`",
+            WorldInstanceParseErrorKind::TrailingCharacters => "unexpected text after `)`",
+            WorldInstanceParseErrorKind::Other => "invalid location",
+        };
+        f.write_str(message)
+    }
+}
 
-    /// Parse a `WorldInstance` from a string.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use std::str::FromStr;
-    /// use surrealdb_test::zaphkiel::world_instance::WorldInstance;
-    ///
-    /// let world_instance = WorldInstance::from_str("wrld_1234:1234~private(usr_1234)").unwrap();
-    /// assert_eq!(world_instance.world_id, "wrld_1234");
-    /// assert_eq!(world_instance.instance_id, "1234");
-    /// assert_eq!(world_instance.private, Some("usr_1234".to_string()));
-    /// ```
+/// A struct representing a world instance parse error.
+///
+/// # Member variables:
+///
+/// - `kind`: What went wrong.
+/// - `offset`: The byte offset in the input where it went wrong.
+/// - `segment`: The `~` separated segment of the input containing `offset`.
+///
+/// # Examples
+/// ```
+/// use std::str::FromStr;
+/// use surrealdb_test::zaphkiel::world_instance::{WorldInstance, WorldInstanceParseErrorKind};
+///
+/// let error = WorldInstance::from_str("wrld_1234:1234~private(usr_1234").unwrap_err();
+/// assert_eq!(error.kind, WorldInstanceParseErrorKind::UnclosedParen);
+/// assert_eq!(error.offset, 22);
+/// assert_eq!(error.segment, "private(usr_1234");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default)]
+pub struct WorldInstanceParseError {
+    pub kind: WorldInstanceParseErrorKind,
+    pub offset: usize,
+    pub segment: String,
+}
+
+impl WorldInstanceParseError {
+    fn new(kind: WorldInstanceParseErrorKind, offset: usize, segment: &str) -> Self {
+        WorldInstanceParseError {
+            kind,
+            offset,
+            segment: segment.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for WorldInstanceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at byte {} in `{}`",
+            self.kind, self.offset, self.segment
+        )
+    }
+}
+
+impl Error for WorldInstanceParseError {}
+
+impl WorldInstance {
+    /// Parse a `WorldInstance` from a string, following the grammar as strictly as `mode` says.
     ///
     /// World instance format:
     ///
     /// ```text
-    /// <world_id>:<instance_id>~<optional_field>(<optional_field_value>)
+    /// location    = world_id ":" instance_id *( "~" tag )
+    /// tag         = flag / key "(" value ")"
+    /// key         = "hidden" / "friends" / "private" / "group" / "groupAccessType" / "region" / "nonce"
+    /// flag        = "canRequestInvite" / "strict"
+    /// world_id    = 1*( any char except ":" "~" "(" ")" )
+    /// instance_id = 1*( any char except ":" "~" "(" ")" )
+    /// value       = *( any char except ":" "~" "(" ")" )
     /// ```
     ///
-    /// Valid optional fields:
-    ///
-    /// - `nonce`: The optional nonce of the world instance.
-    /// - `hidden`: The optional hidden of the world instance.
-    /// - `private`: The optional private of the world instance.
-    /// - `region`: The optional region of the world instance.
+    /// # Examples
     ///
-    /// # Errors
+    /// ```
+    /// use surrealdb_test::zaphkiel::world_instance::{ParseMode, WorldInstance};
     ///
-    /// - `Empty`: The string is empty.
-    /// - `InvalidFormat`: The string is not in the correct format.
-    /// - `InvalidWorldId`: The world id is invalid.
-    /// - `InvalidInstanceId`: The instance id is invalid.
-    /// - `InvalidOptionalField`: The optional field is invalid.
-    /// - `Other`: Other errors.
+    /// let location = "wrld_1234:1234~private(usr_1234)junk~unknown(1)";
+    /// assert!(WorldInstance::parse(location, ParseMode::Strict).is_err());
     ///
-    /// # Working
+    /// let world_instance = WorldInstance::parse(location, ParseMode::Lenient).unwrap();
+    /// assert_eq!(world_instance.private, Some("usr_1234".to_string()));
+    /// ```
     ///
-    /// 1. Split the string by `:`.
-    /// 2. Check if the length of the split is 2.
-    /// 3. Check if the first part is empty.
-    /// 4. Set the world id to the first part.
-    /// 5. Split the second part by `~`.
-    /// 6. Check if the length of the split is 2.
-    /// 7. Check if the first part is empty.
-    /// 8. Set the instance id to the first part.
-    /// 9. Split the second part by `(`.
-    /// 10. Check if the length of the split is 2.
-    /// 11. Check if the first part is empty.
-    /// 12. Check if the first part is a valid optional field.
-    /// 13. Set the optional field to the first part.
-    /// 14. Check if the second part ends with `)`.
-    /// 15. Check if the second part is empty.
-    /// 16. Set the optional field value to the second part.
+    /// # Errors
     ///
-    /// # Notes
-    ///     
-    /// - The optional field value is not checked for validity.
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    /// See `WorldInstanceParseErrorKind`. In lenient mode only `Empty`, `SpecialLocation`,
+    /// `InvalidFormat` and an empty world or instance id are errors.
+    pub fn parse(s: &str, mode: ParseMode) -> Result<Self, WorldInstanceParseError> {
+        use WorldInstanceParseErrorKind as Kind;
+
         if s.is_empty() {
-            return Err(WorldInstanceParseError::Empty);
+            return Err(WorldInstanceParseError::new(Kind::Empty, 0, s));
+        }
+        if SPECIAL_LOCATIONS.contains(&s) {
+            return Err(WorldInstanceParseError::new(Kind::SpecialLocation, 0, s));
+        }
+
+        let mut segments = segments(s);
+        let (_, head) = segments.next().unwrap_or_default();
+        let Some((world_id, instance_id)) = head.split_once(':') else {
+            return Err(WorldInstanceParseError::new(Kind::InvalidFormat, 0, head));
+        };
+        let instance_offset = world_id.len() + 1;
+        if let Some(colon) = instance_id.find(':') {
+            let offset = instance_offset + colon;
+            return Err(WorldInstanceParseError::new(
+                Kind::InvalidFormat,
+                offset,
+                head,
+            ));
+        }
+
+        if world_id.is_empty() {
+            return Err(WorldInstanceParseError::new(Kind::InvalidWorldId, 0, head));
+        }
+        if instance_id.is_empty() {
+            let offset = instance_offset;
+            return Err(WorldInstanceParseError::new(
+                Kind::InvalidInstanceId,
+                offset,
+                head,
+            ));
+        }
+        if mode == ParseMode::Strict {
+            if let Some(paren) = world_id.find(['(', ')']) {
+                return Err(WorldInstanceParseError::new(
+                    Kind::InvalidWorldId,
+                    paren,
+                    head,
+                ));
+            }
+            if let Some(paren) = instance_id.find(['(', ')']) {
+                let offset = instance_offset + paren;
+                return Err(WorldInstanceParseError::new(
+                    Kind::InvalidInstanceId,
+                    offset,
+                    head,
+                ));
+            }
         }
 
         let mut ret = Self::new();
+        ret.world_id = world_id.to_string();
+        ret.instance_id = instance_id.to_string();
 
-        let world_id = s;
-        let parts = world_id.split(':').collect::<Vec<_>>();
-        if parts.len() != 2 {
-            return Err(WorldInstanceParseError::InvalidFormat);
+        for (offset, segment) in segments {
+            if let Some(colon) = segment.find(':') {
+                let offset = offset + colon;
+                return Err(WorldInstanceParseError::new(
+                    Kind::InvalidFormat,
+                    offset,
+                    segment,
+                ));
+            }
+            if let Err(error) = ret.tag(offset, segment, mode) {
+                if mode == ParseMode::Strict {
+                    return Err(error);
+                }
+            }
         }
 
-        if parts[0].is_empty() {
-            return Err(WorldInstanceParseError::InvalidWorldId);
+        Ok(ret)
+    }
+
+    /// Apply one `~` separated tag starting at byte `offset`.
+    ///
+    /// In lenient mode whatever can be recovered is applied before the error is returned, so the
+    /// caller can ignore it.
+    fn tag(
+        &mut self,
+        offset: usize,
+        segment: &str,
+        mode: ParseMode,
+    ) -> Result<(), WorldInstanceParseError> {
+        use WorldInstanceParseErrorKind as Kind;
+        let error = |kind, at| Err(WorldInstanceParseError::new(kind, offset + at, segment));
+
+        let Some(open) = segment.find('(') else {
+            return match segment {
+                flag if FLAGS.contains(&flag) => {
+                    self.set(flag, "");
+                    Ok(())
+                }
+                "" => error(Kind::InvalidOptionalField, 0),
+                key if KEYS.contains(&key) => error(Kind::MissingValue, segment.len()),
+                _ => error(Kind::UnknownKey, 0),
+            };
+        };
+
+        let key = &segment[..open];
+        let rest = &segment[open + 1..];
+        let (value, close) = match rest.find(')') {
+            Some(close) => (&rest[..close], Some(open + 1 + close)),
+            None => (rest, None),
+        };
+
+        let result = if FLAGS.contains(&key) {
+            error(Kind::InvalidOptionalField, open)
+        } else if !KEYS.contains(&key) {
+            error(Kind::UnknownKey, 0)
+        } else if let Some(paren) = value.find('(') {
+            error(Kind::InvalidOptionalField, open + 1 + paren)
+        } else {
+            match close {
+                None => error(Kind::UnclosedParen, open),
+                Some(close) if close + 1 < segment.len() => {
+                    error(Kind::TrailingCharacters, close + 1)
+                }
+                Some(_) => Ok(()),
+            }
+        };
+        if mode == ParseMode::Lenient || result.is_ok() {
+            self.set(key, value);
         }
-        ret.world_id = parts[0].to_string();
+        result
+    }
 
-        let parts = parts[1].split('~').collect::<Vec<_>>();
-        if parts[0].is_empty() {
-            return Err(WorldInstanceParseError::InvalidInstanceId);
+    /// Set the field for a known `key` or flag. Anything else is ignored.
+    fn set(&mut self, key: &str, value: &str) {
+        let value = value.to_string();
+        match key {
+            "nonce" => self.nonce = Some(value),
+            "hidden" => self.hidden = Some(value),
+            "private" => self.private = Some(value),
+            "region" => self.region = Some(value.into()),
+            "friends" => self.friends = Some(value),
+            "group" => self.group = Some(value),
+            "groupAccessType" => self.group_access_type = Some(value),
+            "canRequestInvite" => self.can_request_invite = true,
+            "strict" => self.strict = true,
+            _ => {}
         }
-        ret.instance_id = parts[0].to_string();
+    }
+}
+
+/// Tag keys that take a `(value)`.
+const KEYS: [&str; 7] = [
+    "nonce",
+    "hidden",
+    "private",
+    "region",
+    "friends",
+    "group",
+    "groupAccessType",
+];
+
+/// Tags that stand on their own, without a value.
+const FLAGS: [&str; 2] = ["canRequestInvite", "strict"];
+
+/// The `~` separated segments of `s`, with the byte offset each one starts at.
+fn segments(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    s.split('~').map(move |segment| {
+        let start = offset;
+        offset += segment.len() + 1;
+        (start, segment)
+    })
+}
+
+impl FromStr for WorldInstance {
+    type Err = WorldInstanceParseError;
+
+    /// Parse a `WorldInstance` from a string, strictly.
+    /// See `WorldInstance::parse` for the grammar.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use surrealdb_test::zaphkiel::world_instance::WorldInstance;
+    ///
+    /// let world_instance = WorldInstance::from_str("wrld_1234:1234~private(usr_1234)").unwrap();
+    /// assert_eq!(world_instance.world_id, "wrld_1234");
+    /// assert_eq!(world_instance.instance_id, "1234");
+    /// assert_eq!(world_instance.private, Some("usr_1234".to_string()));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, ParseMode::Strict)
+    }
+}
 
-        for part in parts {
-            let parts = part.split('(').collect::<Vec<_>>();
-            let key = parts[0];
-            if parts.len() < 2 {
-                continue;
+impl fmt::Display for WorldInstance {
+    /// Write the location string, which parses back to the same `WorldInstance`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use surrealdb_test::zaphkiel::world_instance::WorldInstance;
+    ///
+    /// let location = "wrld_1234:1234~private(usr_1234)~canRequestInvite~region(eu)";
+    /// let world_instance = WorldInstance::try_from(location).unwrap();
+    /// assert_eq!(world_instance.to_string(), location);
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.world_id, self.instance_id)?;
+        let fields = [
+            ("hidden", &self.hidden),
+            ("friends", &self.friends),
+            ("private", &self.private),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                write!(f, "~{}({})", key, value)?;
             }
-            let value = parts[1].split(')').collect::<Vec<_>>()[0].to_string();
-
-            match key {
-                "nonce" => ret.nonce = Some(value),
-                "hidden" => ret.hidden = Some(value),
-                "private" => ret.private = Some(value),
-                "region" => ret.region = Some(value.into()),
-                "friends" => ret.friends = Some(value),
-                "group" => ret.group = Some(value),
-                _ => panic!("Unknown key: {}", key),
+        }
+        if self.can_request_invite {
+            write!(f, "~canRequestInvite")?;
+        }
+        let fields = [
+            ("group", &self.group),
+            ("groupAccessType", &self.group_access_type),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                write!(f, "~{}({})", key, value)?;
             }
         }
-
-        Ok(ret)
+        if let Some(region) = &self.region {
+            write!(f, "~region({})", region)?;
+        }
+        if let Some(nonce) = &self.nonce {
+            write!(f, "~nonce({})", nonce)?;
+        }
+        if self.strict {
+            write!(f, "~strict")?;
+        }
+        Ok(())
     }
 }
 
-impl From<&str> for WorldInstance {
-    /// Parse a `WorldInstance` from a string.
+impl TryFrom<&str> for WorldInstance {
+    type Error = WorldInstanceParseError;
+
+    /// Parse a `WorldInstance` from a string, strictly.
     /// See `FromStr` for more information.
+    ///
     /// # Examples
+    ///
     /// ```
-    /// use std::str::FromStr;
     /// use surrealdb_test::zaphkiel::world_instance::WorldInstance;
     ///
-    /// let world_instance = WorldInstance::from("wrld_1234:1234~private(usr_1234)");
+    /// let world_instance = WorldInstance::try_from("wrld_1234:1234~private(usr_1234)").unwrap();
     /// assert_eq!(world_instance.world_id, "wrld_1234");
     /// assert_eq!(world_instance.instance_id, "1234");
     /// assert_eq!(world_instance.private, Some("usr_1234".to_string()));
+    ///
+    /// assert!(WorldInstance::try_from("wrld_1234").is_err());
     /// ```
-    fn from(s: &str) -> Self {
-        Self::from_str(s).unwrap()
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::from_str(s)
     }
 }
 
-impl From<String> for WorldInstance {
-    /// Parse a `WorldInstance` from a string.
+impl TryFrom<String> for WorldInstance {
+    type Error = WorldInstanceParseError;
+
+    /// Parse a `WorldInstance` from a string, strictly.
     /// See `FromStr` for more information.
+    ///
     /// # Examples
+    ///
     /// ```
-    /// use std::str::FromStr;
     /// use surrealdb_test::zaphkiel::world_instance::WorldInstance;
     ///
-    /// let world_instance = WorldInstance::from("wrld_1234:1234~private(usr_1234)".to_string());
+    /// let location = "wrld_1234:1234~private(usr_1234)".to_string();
+    /// let world_instance = WorldInstance::try_from(location).unwrap();
     /// assert_eq!(world_instance.world_id, "wrld_1234");
     /// assert_eq!(world_instance.instance_id, "1234");
     /// assert_eq!(world_instance.private, Some("usr_1234".to_string()));
     /// ```
-    fn from(s: String) -> Self {
-        Self::from_str(&s).unwrap()
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::from_str(&s)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::zaphkiel::world_instance::{WorldInstance, WorldInstanceParseError};
+    use crate::zaphkiel::world_instance::{
+        ParseMode, WorldInstance, WorldInstanceParseError, WorldInstanceParseErrorKind,
+    };
     use crate::zaphkiel::world_regions::Regions;
+    use proptest::option;
+    use proptest::prelude::*;
+    use proptest::sample::select;
     use std::str::FromStr;
 
+    /// Optional field values that survive a round trip: no `~`, `(` or `)`.
+    fn value() -> impl Strategy<Value = String> {
+        "[A-Za-z0-9_\\-.]{0,40}"
+    }
+
+    fn region() -> impl Strategy<Value = Regions> {
+        select(vec![
+            Regions::Other,
+            Regions::USWest,
+            Regions::US,
+            Regions::USEast,
+            Regions::Europe,
+            Regions::Japan,
+        ])
+    }
+
+    prop_compose! {
+        fn world_instance()(
+            world_id in "wrld_[a-f0-9-]{1,36}",
+            instance_id in "[A-Za-z0-9]{1,12}",
+            nonce in option::of(value()),
+            hidden in option::of(value()),
+            private in option::of(value()),
+            region in option::of(region()),
+            friends in option::of(value()),
+            group in option::of(value()),
+            group_access_type in option::of(value()),
+            can_request_invite in any::<bool>(),
+            strict in any::<bool>(),
+        ) -> WorldInstance {
+            WorldInstance {
+                world_id,
+                instance_id,
+                nonce,
+                hidden,
+                private,
+                region,
+                friends,
+                group,
+                group_access_type,
+                can_request_invite,
+                strict,
+            }
+        }
+    }
+
+    /// Strings built from the pieces of a location, in any order: stray `:`, `~`, nested and
+    /// unbalanced parens, known and unknown keys, unicode and empty parts.
+    fn adversarial() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            select(vec![
+                ":",
+                "~",
+                "(",
+                ")",
+                "((",
+                "))",
+                "()",
+                "wrld_",
+                "usr_",
+                "private",
+                "hidden",
+                "friends",
+                "group",
+                "region",
+                "nonce",
+                "groupAccessType",
+                "canRequestInvite",
+                "strict",
+                "offline",
+                "traveling",
+                "",
+            ])
+            .prop_map(str::to_string),
+            "[a-z0-9]{0,8}",
+            "\\PC{0,4}",
+        ];
+        prop::collection::vec(piece, 0..16).prop_map(|pieces| pieces.concat())
+    }
+
+    proptest! {
+        #[test]
+        fn test_world_instance_round_trips(world_instance in world_instance()) {
+            let parsed = WorldInstance::from_str(&world_instance.to_string());
+            prop_assert_eq!(parsed, Ok(world_instance));
+        }
+
+        #[test]
+        fn test_world_instance_never_panics(s in "\\PC*") {
+            let _ = WorldInstance::from_str(&s);
+        }
+
+        #[test]
+        fn test_world_instance_reparses_adversarial_input(s in adversarial()) {
+            if let Ok(world_instance) = WorldInstance::from_str(&s) {
+                let reparsed = WorldInstance::from_str(&world_instance.to_string());
+                prop_assert_eq!(reparsed, Ok(world_instance));
+            }
+        }
+
+        #[test]
+        fn test_world_instance_lenient_reparses_adversarial_input(s in adversarial()) {
+            if let Ok(world_instance) = WorldInstance::parse(&s, ParseMode::Lenient) {
+                let reparsed = WorldInstance::parse(&world_instance.to_string(), ParseMode::Lenient);
+                prop_assert_eq!(reparsed, Ok(world_instance));
+            }
+        }
+
+        #[test]
+        fn test_world_instance_lenient_accepts_strict_input(s in adversarial()) {
+            if let Ok(world_instance) = WorldInstance::from_str(&s) {
+                prop_assert_eq!(WorldInstance::parse(&s, ParseMode::Lenient), Ok(world_instance));
+            }
+        }
+
+        #[test]
+        fn test_world_instance_error_points_into_input(s in adversarial()) {
+            if let Err(error) = WorldInstance::from_str(&s) {
+                prop_assert!(error.offset <= s.len());
+                prop_assert!(s.contains(&error.segment));
+            }
+        }
+    }
+
     #[test]
     fn test_parse_world_instance() {
         let world_instance_str = "world_id:instance_id~region(EU)";
@@ -272,6 +705,9 @@ mod tests {
             region: Some(Regions::Europe),
             friends: None,
             group: None,
+            group_access_type: None,
+            can_request_invite: false,
+            strict: false,
         };
         let actual_world_instance = WorldInstance::from_str(world_instance_str).unwrap();
         assert_eq!(actual_world_instance, expected_world_instance);
@@ -283,8 +719,8 @@ mod tests {
         let actual_result = WorldInstance::from_str(world_instance_str);
         assert!(actual_result.is_err());
         assert_eq!(
-            actual_result.unwrap_err(),
-            WorldInstanceParseError::InvalidFormat
+            actual_result.unwrap_err().kind,
+            WorldInstanceParseErrorKind::InvalidFormat
         );
     }
 
@@ -294,8 +730,8 @@ mod tests {
         let actual_result = WorldInstance::from_str(world_instance_str);
         assert!(actual_result.is_err());
         assert_eq!(
-            actual_result.unwrap_err(),
-            WorldInstanceParseError::InvalidWorldId
+            actual_result.unwrap_err().kind,
+            WorldInstanceParseErrorKind::InvalidWorldId
         );
     }
 
@@ -305,19 +741,24 @@ mod tests {
         let actual_result = WorldInstance::from_str(world_instance_str);
         assert!(actual_result.is_err());
         assert_eq!(
-            actual_result.unwrap_err(),
-            WorldInstanceParseError::InvalidInstanceId
+            actual_result.unwrap_err().kind,
+            WorldInstanceParseErrorKind::InvalidInstanceId
         );
     }
 
-    #[ignore]
     #[test]
-    #[should_panic]
     fn test_parse_world_instance_unknown_key() {
         let world_instance_str = "world_id:instance_id~unknown_key(value)";
         let actual_result = WorldInstance::from_str(world_instance_str);
         assert!(actual_result.is_err());
-        assert_eq!(actual_result.unwrap_err(), WorldInstanceParseError::Other);
+        assert_eq!(
+            actual_result.unwrap_err(),
+            WorldInstanceParseError {
+                kind: WorldInstanceParseErrorKind::UnknownKey,
+                offset: 21,
+                segment: "unknown_key(value)".to_string(),
+            }
+        );
     }
 
     #[test]
@@ -325,7 +766,10 @@ mod tests {
         let world_instance_str = "";
         let actual_result = WorldInstance::from_str(world_instance_str);
         assert!(actual_result.is_err());
-        assert_eq!(actual_result.unwrap_err(), WorldInstanceParseError::Empty);
+        assert_eq!(
+            actual_result.unwrap_err().kind,
+            WorldInstanceParseErrorKind::Empty
+        );
     }
 
     #[test]
@@ -340,8 +784,12 @@ mod tests {
             region: Some(Regions::US),
             friends: None,
             group: None,
+            group_access_type: None,
+            can_request_invite: false,
+            strict: false,
         };
-        let actual_world_instance = WorldInstance::from(world_instance_str.to_string());
+        let actual_world_instance =
+            WorldInstance::try_from(world_instance_str.to_string()).unwrap();
         assert_eq!(actual_world_instance, expected_world_instance);
     }
 
@@ -357,8 +805,94 @@ mod tests {
             region: Some(Regions::US),
             friends: None,
             group: None,
+            group_access_type: None,
+            can_request_invite: false,
+            strict: false,
         };
-        let actual_world_instance = WorldInstance::from(world_instance_str);
+        let actual_world_instance = WorldInstance::try_from(world_instance_str).unwrap();
         assert_eq!(actual_world_instance, expected_world_instance);
     }
+
+    fn parse_error(s: &str) -> (WorldInstanceParseErrorKind, usize, String) {
+        let error = WorldInstance::from_str(s).unwrap_err();
+        (error.kind, error.offset, error.segment)
+    }
+
+    #[test]
+    fn test_parse_world_instance_real_location() {
+        let world_instance_str = "wrld_1234:56789~private(usr_1234)~canRequestInvite~region(eu)\
+            ~nonce(abcd-ef)";
+        let world_instance = WorldInstance::from_str(world_instance_str).unwrap();
+        assert_eq!(world_instance.private, Some("usr_1234".to_string()));
+        assert!(world_instance.can_request_invite);
+        assert_eq!(world_instance.region, Some(Regions::Europe));
+        assert_eq!(world_instance.nonce, Some("abcd-ef".to_string()));
+        assert_eq!(world_instance.to_string(), world_instance_str);
+    }
+
+    #[test]
+    fn test_parse_world_instance_special_locations() {
+        for location in ["offline", "private", "traveling", "traveling:traveling"] {
+            for mode in [ParseMode::Strict, ParseMode::Lenient] {
+                let error = WorldInstance::parse(location, mode).unwrap_err();
+                assert_eq!(error.kind, WorldInstanceParseErrorKind::SpecialLocation);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_world_instance_error_positions() {
+        use WorldInstanceParseErrorKind as Kind;
+        assert_eq!(
+            parse_error("wrld_1:1~private(usr_1"),
+            (Kind::UnclosedParen, 16, "private(usr_1".to_string())
+        );
+        assert_eq!(
+            parse_error("wrld_1:1~private(usr_1)x"),
+            (Kind::TrailingCharacters, 23, "private(usr_1)x".to_string())
+        );
+        assert_eq!(
+            parse_error("wrld_1:1~region(eu)~private"),
+            (Kind::MissingValue, 27, "private".to_string())
+        );
+        assert_eq!(
+            parse_error("wrld_1:1~region(eu)~~nonce(1)"),
+            (Kind::InvalidOptionalField, 20, "".to_string())
+        );
+        assert_eq!(
+            parse_error("wrld_1:1~private(a(b))"),
+            (Kind::InvalidOptionalField, 18, "private(a(b))".to_string())
+        );
+        assert_eq!(
+            parse_error("wrld_1:1~strict(yes)"),
+            (Kind::InvalidOptionalField, 15, "strict(yes)".to_string())
+        );
+        assert_eq!(
+            parse_error("wrld_1:1:2"),
+            (Kind::InvalidFormat, 8, "wrld_1:1:2".to_string())
+        );
+        assert_eq!(
+            parse_error("wrld(1):1"),
+            (Kind::InvalidWorldId, 4, "wrld(1):1".to_string())
+        );
+        assert_eq!(
+            parse_error("wrld_1:1)"),
+            (Kind::InvalidInstanceId, 8, "wrld_1:1)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_world_instance_lenient() {
+        let world_instance = WorldInstance::parse(
+            "wrld_1:1~private(usr_1)junk~unknown(x)~~hidden~nonce(abc",
+            ParseMode::Lenient,
+        )
+        .unwrap();
+        assert_eq!(world_instance.private, Some("usr_1".to_string()));
+        assert_eq!(world_instance.hidden, None);
+        assert_eq!(world_instance.nonce, Some("abc".to_string()));
+
+        let error = WorldInstance::parse(":1~private(usr_1)", ParseMode::Lenient).unwrap_err();
+        assert_eq!(error.kind, WorldInstanceParseErrorKind::InvalidWorldId);
+    }
 }
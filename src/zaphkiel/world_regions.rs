@@ -1,3 +1,4 @@
+use std::fmt;
 use std::str::FromStr;
 
 /// Regions where VRChat worlds instances are hosted.
@@ -16,6 +17,7 @@ use std::str::FromStr;
 ///
 /// let region = Regions::from("uswest");
 /// assert_eq!(region, Regions::USWest);
+/// assert_eq!(Regions::from("moon"), Regions::Other);
 /// ```
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, Default,
@@ -61,7 +63,7 @@ impl From<String> for Regions {
             "uw" => Regions::USWest,
             "ue" => Regions::USEast,
 
-            _ => panic!("Unknown region: {}", value),
+            _ => Regions::Other,
         }
     }
 }
@@ -73,3 +75,58 @@ impl FromStr for Regions {
         Ok(Self::from(s))
     }
 }
+
+impl fmt::Display for Regions {
+    /// Write the short region code used in location strings, which parses back to the same
+    /// variant.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            Regions::Other => "other",
+            Regions::USWest => "usw",
+            Regions::US => "us",
+            Regions::USEast => "use",
+            Regions::Europe => "eu",
+            Regions::Japan => "jp",
+        };
+        write!(f, "{}", code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::zaphkiel::world_regions::Regions;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    fn variant() -> impl Strategy<Value = Regions> {
+        select(vec![
+            Regions::Other,
+            Regions::USWest,
+            Regions::US,
+            Regions::USEast,
+            Regions::Europe,
+            Regions::Japan,
+        ])
+    }
+
+    proptest! {
+        #[test]
+        fn test_region_never_panics(s in "\\PC*") {
+            let _ = Regions::from(s.as_str());
+        }
+
+        #[test]
+        fn test_region_round_trips(variant in variant()) {
+            prop_assert_eq!(Regions::from(variant.to_string()), variant);
+        }
+
+        #[test]
+        fn test_region_ignores_case(variant in variant(), upper in any::<bool>()) {
+            let s = match upper {
+                true => variant.to_string().to_uppercase(),
+                false => variant.to_string().to_lowercase(),
+            };
+            prop_assert_eq!(s.parse::<Regions>().unwrap(), variant);
+        }
+    }
+}
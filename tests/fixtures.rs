@@ -0,0 +1,1257 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use surrealdb_test::fixtures::vrcx::{Fixture, FixtureOptions};
+use surrealdb_test::graph::avatar_timeline::AvatarTimeline;
+use surrealdb_test::graph::social_graph::{EdgeKind, GraphOptions, SocialGraph};
+use surrealdb_test::import::importer::VrcxTables;
+use surrealdb_test::import::merge::merge;
+use surrealdb_test::import::sqlite::VrcxSqlite;
+use surrealdb_test::models::gamelog_join_leave::GamelogJoinLeave;
+use surrealdb_test::models::gamelog_location::GamelogLocation;
+use surrealdb_test::models::moderation::{BlockedEncounter, ModerationAction, ModerationEvent};
+use surrealdb_test::models::online_heatmap::OnlineHeatmap;
+use surrealdb_test::models::session::Session;
+use surrealdb_test::models::world::{EnrichedLocation, MissingWorld};
+use surrealdb_test::zaphkiel::location::Location;
+use surrealdb_test::zaphkiel::notification_kind::NotificationKind;
+use surrealdb_test::zaphkiel::release_status::ReleaseStatus;
+use surrealdb_test::zaphkiel::timestamp::{SourceTimezone, TimestampParseErrorKind};
+use surrealdb_test::zaphkiel::user_status::UserStatus;
+
+/// A file in the temp dir, unique to its name, deleted when dropped so a failed assertion
+/// doesn't leave it behind.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    /// A fresh path for `name`, e.g. `backup.surql`, with nothing written to it yet.
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("surrealdb-test-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        TempFile(path)
+    }
+
+    /// `fixture` written to a fresh `<name>.sqlite`.
+    fn sqlite(fixture: &Fixture, name: &str) -> Self {
+        let file = TempFile::new(&format!("{}.sqlite", name));
+        fixture.write_sqlite(&file).unwrap();
+        file
+    }
+
+    /// Every table of the file, with timestamps read as UTC.
+    fn tables(&self) -> VrcxTables {
+        VrcxTables::read(self, SourceTimezone::Utc).unwrap()
+    }
+}
+
+impl Deref for TempFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Write `fixture` to a temporary `vrcx.sqlite` and read every table back.
+fn fixture_tables(fixture: &Fixture, name: &str) -> VrcxTables {
+    TempFile::sqlite(fixture, name).tables()
+}
+
+fn fixture() -> Fixture {
+    Fixture::generate(&FixtureOptions::default())
+}
+
+/// `GET uri` from `router`, returning the status and the JSON body.
+#[cfg(feature = "kv-mem")]
+async fn api_get(router: axum::Router, uri: &str) -> (axum::http::StatusCode, serde_json::Value) {
+    use axum::body::HttpBody;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let request = Request::get(uri).body(axum::body::Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let mut body = response.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        bytes.extend_from_slice(&chunk.unwrap());
+    }
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[test]
+fn test_fixture_round_trips_through_sqlite() {
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "round-trip");
+
+    let sqlite = VrcxSqlite::open(&path).unwrap();
+    let tables = sqlite.friend_log_tables().unwrap();
+    assert_eq!(
+        tables,
+        vec![format!("{}_friend_log_current", fixture.account_prefix())]
+    );
+    assert_eq!(sqlite.gamelog_location().unwrap(), fixture.locations);
+    assert_eq!(sqlite.gamelog_join_leave().unwrap(), fixture.join_leave);
+    assert_eq!(
+        sqlite.friend_log_current(&tables[0]).unwrap(),
+        fixture.friends
+    );
+    let mut cache_worlds = fixture.cache_worlds.clone();
+    cache_worlds.sort_by(|a, b| a.id.cmp(&b.id));
+    assert_eq!(sqlite.cache_world().unwrap(), cache_worlds);
+    assert_eq!(
+        sqlite.cache_avatar().unwrap().len(),
+        fixture.cache_avatars.len()
+    );
+}
+
+#[test]
+fn test_timestamps_are_parsed_per_row() {
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "timestamps");
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "UPDATE gamelog_location SET created_at = 1682762400 WHERE id = 1; \
+            UPDATE gamelog_location SET created_at = '2023-04-29 19:00:00' WHERE id = 2; \
+            UPDATE gamelog_location SET created_at = 'not a date' WHERE id = 3;",
+        )
+        .unwrap();
+    }
+
+    let rows = VrcxSqlite::open(&path).unwrap().gamelog_location().unwrap();
+    assert_eq!(rows[0].created_at, "1682762400");
+    assert_eq!(rows[1].created_at, "2023-04-29 19:00:00");
+
+    let tokyo: SourceTimezone = "Asia/Tokyo".parse().unwrap();
+    let parsed = rows
+        .iter()
+        .cloned()
+        .map(|row| GamelogLocation::from_row(row, tokyo))
+        .collect::<Vec<_>>();
+    let first = parsed[0].as_ref().unwrap();
+    let second = parsed[1].as_ref().unwrap();
+    assert_eq!(first.created_at, second.created_at);
+    assert_eq!(second.created_at_raw, "2023-04-29 19:00:00");
+
+    let error = parsed[2].as_ref().unwrap_err();
+    assert_eq!(error.kind, TimestampParseErrorKind::UnknownFormat);
+    assert_eq!(error.raw, "not a date");
+    assert!(parsed[3..].iter().all(Result::is_ok));
+}
+
+/// Read and merge `paths` the way `Importer::merge` does.
+fn read_merged(files: &[&TempFile]) -> (VrcxTables, [usize; 3]) {
+    let mut tables = VrcxTables::default();
+    for file in files {
+        tables.extend(file.tables());
+    }
+    let (locations, merged_locations) = merge(tables.locations);
+    let (join_leave, merged_join_leave) = merge(tables.join_leave);
+    let (friends, merged_friends) = merge(tables.friends);
+    let duplicates = [
+        merged_locations.duplicates,
+        merged_join_leave.duplicates,
+        merged_friends.duplicates,
+    ];
+    (
+        VrcxTables {
+            locations,
+            join_leave,
+            friends,
+            ..Default::default()
+        },
+        duplicates,
+    )
+}
+
+#[test]
+fn test_merge_dedupes_copies() {
+    let fixture = fixture();
+    let (a, b) = (
+        TempFile::sqlite(&fixture, "merge-copy-a"),
+        TempFile::sqlite(&fixture, "merge-copy-b"),
+    );
+
+    let (tables, duplicates) = read_merged(&[&a, &b]);
+    assert_eq!(
+        duplicates,
+        [
+            fixture.locations.len(),
+            fixture.join_leave.len(),
+            fixture.friends.len()
+        ]
+    );
+    assert_eq!(tables.locations.len(), fixture.locations.len());
+    assert_eq!(tables.join_leave.len(), fixture.join_leave.len());
+    assert_eq!(tables.friends.len(), fixture.friends.len());
+
+    let mut files = [a.display().to_string(), b.display().to_string()];
+    files.sort();
+    for row in &tables.join_leave {
+        let sources = row
+            .sources
+            .iter()
+            .map(|source| source.file.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(sources, files);
+        assert!(row
+            .sources
+            .iter()
+            .all(|source| source.account == Some(fixture.account_prefix())));
+    }
+
+    // A file imported on its own is keyed the same way as a merge.
+    let single = a.tables();
+    let ids = |rows: &[GamelogJoinLeave]| rows.iter().map(|row| row.id).collect::<HashSet<_>>();
+    assert_eq!(ids(&single.join_leave), ids(&tables.join_leave));
+
+    assert_eq!(read_merged(&[&b, &a]).0, tables);
+}
+
+#[test]
+fn test_merge_keeps_every_account() {
+    let fixture = fixture();
+    let other = Fixture::generate(&FixtureOptions {
+        seed: 1,
+        ..Default::default()
+    });
+    let (a, b) = (
+        TempFile::sqlite(&fixture, "merge-union-a"),
+        TempFile::sqlite(&other, "merge-union-b"),
+    );
+
+    let (tables, _) = read_merged(&[&a, &b]);
+    let accounts = tables
+        .join_leave
+        .iter()
+        .flat_map(|row| &row.sources)
+        .filter_map(|source| source.account.clone())
+        .collect::<HashSet<_>>();
+    assert_eq!(
+        accounts,
+        HashSet::from([fixture.account_prefix(), other.account_prefix()])
+    );
+    assert!(tables.join_leave.len() > fixture.join_leave.len());
+    let ids = tables
+        .join_leave
+        .iter()
+        .map(|row| row.id)
+        .collect::<HashSet<_>>();
+    assert_eq!(ids.len(), tables.join_leave.len());
+
+    let split = tables.clone().split_by_account();
+    assert_eq!(split.len(), 2);
+    let mine = &split[&Some(fixture.account_prefix())];
+    assert_eq!(mine.join_leave.len(), fixture.join_leave.len());
+    assert_eq!(mine.friends.len(), fixture.friends.len());
+    assert_eq!(
+        &tables.for_account(&other.account_prefix()),
+        &split[&Some(other.account_prefix())]
+    );
+}
+
+#[test]
+fn test_shared_game_log_is_copied_to_every_account() {
+    let fixture = fixture();
+    let other = Fixture::generate(&FixtureOptions {
+        seed: 1,
+        ..Default::default()
+    });
+    let (path, other_path) = (
+        TempFile::sqlite(&fixture, "multi-account"),
+        TempFile::sqlite(&other, "multi-account-other"),
+    );
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(&format!(
+            "ATTACH '{}' AS other; \
+            CREATE TABLE {prefix}_friend_log_current AS \
+            SELECT * FROM other.{prefix}_friend_log_current;",
+            other_path.display(),
+            prefix = other.account_prefix(),
+        ))
+        .unwrap();
+    }
+
+    let tables = path.tables();
+    assert!(tables
+        .join_leave
+        .iter()
+        .flat_map(|row| &row.sources)
+        .all(|source| source.account.is_none()));
+
+    let split = tables.clone().split_by_account();
+    assert_eq!(
+        split.keys().cloned().collect::<HashSet<_>>(),
+        HashSet::from([Some(fixture.account_prefix()), Some(other.account_prefix())])
+    );
+    for account in split.values() {
+        assert_eq!(account.locations, tables.locations);
+        assert_eq!(account.join_leave, tables.join_leave);
+    }
+    assert_eq!(
+        split[&Some(fixture.account_prefix())].friends.len(),
+        fixture.friends.len()
+    );
+    assert_eq!(
+        &tables.for_account(&other.account_prefix()),
+        &split[&Some(other.account_prefix())]
+    );
+}
+
+#[test]
+fn test_world_cache_enriches_locations() {
+    let fixture = fixture();
+    let tables = fixture_tables(&fixture, "world-cache");
+    assert_eq!(tables.worlds.len(), fixture.worlds.len() - 1);
+    assert_eq!(tables.avatars.len(), fixture.friends.len());
+    assert!(tables
+        .worlds
+        .iter()
+        .all(|world| world.release_status == ReleaseStatus::Public && world.updated_at.is_some()));
+
+    let (uncached_id, uncached_name) = fixture.worlds.last().unwrap();
+    let uncached_visits = fixture
+        .locations
+        .iter()
+        .filter(|row| &row.world_id == uncached_id)
+        .count();
+    let missing = MissingWorld::from_locations(&tables.locations, &tables.worlds);
+    match uncached_visits {
+        0 => assert!(missing.is_empty()),
+        visits => assert_eq!(
+            missing,
+            vec![MissingWorld {
+                world_id: uncached_id.clone(),
+                world_name: uncached_name.clone(),
+                visits: visits as u64,
+            }]
+        ),
+    }
+
+    let enriched = EnrichedLocation::join(tables.locations, &tables.worlds);
+    assert_eq!(enriched.len(), fixture.locations.len());
+    for row in &enriched {
+        let cached = &row.location.world_instance.world_id != uncached_id;
+        assert_eq!(row.author_name().is_some(), cached);
+    }
+}
+
+#[test]
+fn test_avatar_timeline_from_feed() {
+    let fixture = fixture();
+    let tables = fixture_tables(&fixture, "avatar-timeline");
+    assert_eq!(tables.feed_avatar.len(), fixture.feed_avatar.len());
+    assert_eq!(tables.avatar_history.len(), fixture.avatar_history.len());
+
+    let owner = tables.user_id(&fixture.account_prefix()).unwrap();
+    assert_eq!(owner, fixture.account.user_id);
+    let timeline = AvatarTimeline::build(
+        &tables.feed_avatar,
+        &tables.avatar_history,
+        &owner,
+        &tables.avatars,
+    );
+    let cached = tables
+        .avatars
+        .iter()
+        .map(|avatar| avatar.id.as_str())
+        .collect::<HashSet<_>>();
+    assert!(timeline
+        .wears
+        .iter()
+        .all(|wear| cached.contains(wear.avatar.as_str())));
+    assert_eq!(
+        timeline.of_player(&owner).count(),
+        fixture.avatar_history.len()
+    );
+
+    for row in &tables.feed_avatar {
+        let wear = timeline.worn_at(&row.user_id, row.created_at).unwrap();
+        assert_eq!(wear.avatar_name, row.avatar_name);
+    }
+
+    let sessions = Session::from_join_leave(&tables.join_leave);
+    let friend = &fixture.friends[0].user_id;
+    let meetings = timeline.when_met(friend, &sessions);
+    assert_eq!(
+        meetings.len(),
+        sessions
+            .iter()
+            .filter(|session| session.user_id.as_ref() == Some(friend))
+            .count()
+    );
+    let first_switch = timeline.of_player(friend).next().unwrap().worn_from;
+    for meeting in meetings {
+        let started_at = meeting.session.started_at().unwrap();
+        assert_eq!(meeting.wear.is_some(), started_at >= first_switch);
+    }
+
+    let popular = timeline.most_worn(&tables.friends, Utc::now());
+    assert!(!popular.is_empty());
+    assert!(popular
+        .windows(2)
+        .all(|pair| pair[0].wearers >= pair[1].wearers));
+    assert_eq!(
+        popular.iter().map(|avatar| avatar.wears).sum::<u64>(),
+        timeline
+            .wears
+            .iter()
+            .filter(|wear| wear.user_id != owner)
+            .count() as u64
+    );
+}
+
+#[test]
+fn test_status_feeds_and_online_heatmap() {
+    let fixture = fixture();
+    let tables = fixture_tables(&fixture, "status-feeds");
+    assert_eq!(tables.feed_status.len(), fixture.feed_status.len());
+    assert_eq!(tables.feed_bio.len(), fixture.feed_bio.len());
+    assert_eq!(
+        tables.feed_online_offline.len(),
+        fixture.feed_online_offline.len()
+    );
+    assert!(
+        tables
+            .feed_status
+            .iter()
+            .all(|row| row.status != UserStatus::Unknown
+                && row.previous_status != UserStatus::Unknown)
+    );
+
+    for friend in &fixture.friends {
+        let heatmap = OnlineHeatmap::from_events(
+            &friend.user_id,
+            &tables.feed_online_offline,
+            SourceTimezone::Utc,
+        );
+        assert_eq!(heatmap.sessions, 2);
+        let online = tables
+            .feed_online_offline
+            .iter()
+            .filter(|row| row.user_id == friend.user_id)
+            .filter_map(|row| row.time)
+            .sum::<u64>()
+            / 60_000;
+        // Every hour cell rounds its seconds down, and both periods cover at most 4 cells.
+        assert!(heatmap.total() <= online && heatmap.total() + 8 >= online);
+        assert!(heatmap.peak().is_some());
+    }
+}
+
+#[test]
+fn test_gps_feed_adds_visited_edges() {
+    let fixture = fixture();
+    let tables = fixture_tables(&fixture, "gps-feed");
+    assert_eq!(tables.feed_gps.len(), fixture.feed_gps.len());
+    assert!(!tables.feed_gps.is_empty());
+
+    let moves = tables
+        .feed_gps
+        .iter()
+        .filter(|row| row.location.world_instance().is_some())
+        .count();
+    assert_eq!(
+        moves,
+        tables
+            .feed_gps
+            .iter()
+            .filter(|row| row.location != Location::Private)
+            .count()
+    );
+    let gps = SocialGraph::from_gps(&tables.feed_gps, &GraphOptions::default());
+    assert_eq!(gps.edges_of(EdgeKind::Visited).count(), moves);
+
+    // Every move of a friend lands on an instance they joined in our own game log.
+    let sessions = Session::from_join_leave(&tables.join_leave);
+    let joined = SocialGraph::build(&sessions, &[], "", &GraphOptions::default());
+    let joined = joined
+        .edges_of(EdgeKind::Joined)
+        .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+        .collect::<HashSet<_>>();
+    for edge in gps.edges_of(EdgeKind::Visited) {
+        assert!(joined.contains(&(edge.from.as_str(), edge.to.as_str())));
+        assert!(edge.first_seen <= edge.last_seen);
+    }
+}
+
+#[test]
+fn test_moderation_history_and_blocked_encounters() {
+    let fixture = fixture();
+    let tables = fixture_tables(&fixture, "moderation");
+    assert_eq!(tables.moderation.len(), fixture.moderation.len());
+    assert_eq!(tables.moderation.iter().filter(|row| row.block).count(), 2);
+
+    let now = Utc::now();
+    let history = ModerationEvent::diff(&[], &tables.moderation, now);
+    assert_eq!(history.len(), 3);
+    let unblocked = tables
+        .moderation
+        .iter()
+        .filter(|row| row.user_id != history[0].user_id)
+        .cloned()
+        .collect::<Vec<_>>();
+    let changes = ModerationEvent::diff(&tables.moderation, &unblocked, now);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].at, now);
+    assert!(matches!(
+        changes[0].action,
+        ModerationAction::Unblock | ModerationAction::Unmute
+    ));
+
+    let sessions = Session::from_join_leave(&tables.join_leave);
+    let blocked_at = tables.moderation[0].updated_at.unwrap();
+    let encounters = BlockedEncounter::find(&history, &sessions);
+    let blocked = tables
+        .moderation
+        .iter()
+        .filter(|row| row.block)
+        .map(|row| row.user_id.as_str())
+        .collect::<HashSet<_>>();
+    assert_eq!(
+        encounters.len(),
+        sessions
+            .iter()
+            .filter(|session| session
+                .user_id
+                .as_deref()
+                .is_some_and(|user_id| blocked.contains(user_id)))
+            .filter(|session| session.left_at.or(session.joined_at).unwrap() >= blocked_at)
+            .count()
+    );
+    assert!(encounters
+        .iter()
+        .all(|encounter| blocked.contains(encounter.user_id.as_str())));
+}
+
+#[test]
+fn test_empty_moderation_table_is_synced() {
+    let fixture = Fixture {
+        moderation: Vec::new(),
+        ..fixture()
+    };
+    let tables = fixture_tables(&fixture, "empty-moderation");
+    assert!(tables.moderation.is_empty());
+    assert_eq!(tables.moderation_sources.len(), 1);
+}
+
+#[test]
+fn test_notifications_add_invited_edges() {
+    let fixture = fixture();
+    let tables = fixture_tables(&fixture, "notifications");
+    assert_eq!(tables.notifications.len(), fixture.notifications.len());
+    assert!(tables.rejected_notifications.is_empty());
+
+    let invites = tables
+        .notifications
+        .iter()
+        .filter(|row| row.kind == NotificationKind::Invite)
+        .collect::<Vec<_>>();
+    assert_eq!(invites.len(), tables.notifications.len() - 1);
+    let visited = fixture
+        .visits
+        .iter()
+        .map(|visit| visit.location.as_str())
+        .collect::<HashSet<_>>();
+    // Every invite is to an instance the account visited.
+    for invite in &invites {
+        let location = invite.location.world_instance().unwrap();
+        let instance = format!("{}:{}", location.world_id, location.instance_id);
+        assert!(visited.iter().any(|visit| visit.starts_with(&instance)));
+        assert!(invite.world_name.is_some());
+    }
+    assert!(tables.notifications.iter().any(
+        |row| row.kind == NotificationKind::FriendRequest && row.location == Location::Unknown
+    ));
+
+    // Every friend invites the account once per visit they were part of.
+    let graph = SocialGraph::from_notifications(&tables.notifications, &GraphOptions::default());
+    let invited = graph.edges_of(EdgeKind::Invited).collect::<Vec<_>>();
+    assert!(!invited.is_empty());
+    assert_eq!(
+        invited.iter().map(|edge| edge.weight.unwrap()).sum::<u64>(),
+        invites.len() as u64
+    );
+    let receiver = format!("player:{}", fixture.account.user_id);
+    assert!(invited.iter().all(|edge| edge.to == receiver));
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_import_into_memory() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+    use surrealdb_test::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+    use surrealdb_test::repo::insert_strategy::InsertStrategy;
+
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "import");
+
+    for strategy in [
+        InsertStrategy::Insert,
+        InsertStrategy::Transaction,
+        InsertStrategy::Upsert,
+    ] {
+        let db = Surreal::new::<Mem>(()).await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+
+        let options = BatchOptions {
+            batch_size: 50,
+            strategy,
+            ..Default::default()
+        };
+        let reports = Importer::new(db.clone(), options)
+            .import(&path)
+            .await
+            .unwrap();
+        for report in &reports {
+            assert_eq!(report.batch.written, report.read, "{}", report.table);
+            assert!(report.batch.failed.is_empty());
+        }
+
+        let rows = GamelogJoinLeaveRepo::new(db).all().await.unwrap();
+        assert_eq!(rows.len(), fixture.join_leave.len());
+    }
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_reimport_updates_what_changed() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+    use surrealdb_test::repo::friend::FriendRepo;
+    use surrealdb_test::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+    use surrealdb_test::repo::world::WorldRepo;
+    use surrealdb_test::zaphkiel::trust_level::TrustLevel;
+
+    let mut fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "reimport");
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    let importer = Importer::new(db.clone(), BatchOptions::default());
+    importer.import(&path).await.unwrap();
+
+    let reports = importer.import(&path).await.unwrap();
+    let join_leave = reports
+        .iter()
+        .find(|report| report.table == "gamelog_join_leave")
+        .unwrap();
+    assert_eq!(join_leave.batch.written, 0);
+    assert!(join_leave.batch.failed.is_empty());
+    assert_eq!(
+        GamelogJoinLeaveRepo::new(db.clone())
+            .all()
+            .await
+            .unwrap()
+            .len(),
+        fixture.join_leave.len()
+    );
+
+    fixture.friends[0].display_name = "renamed".to_string();
+    fixture.friends[0].trust_level = "Nuisance".to_string();
+    fixture.cache_worlds[0].version += 1;
+    fixture.cache_worlds[0].name = "newer".to_string();
+    fixture.cache_worlds[1].version -= 1;
+    fixture.cache_worlds[1].name = "older".to_string();
+    let changed = TempFile::sqlite(&fixture, "reimport-changed");
+    let reports = importer.import(&changed).await.unwrap();
+    let written = |table: &str| {
+        reports
+            .iter()
+            .find(|report| report.table == table)
+            .unwrap()
+            .batch
+            .written
+    };
+    assert_eq!(written("friend_log_current"), fixture.friends.len());
+    assert_eq!(written("world"), fixture.cache_worlds.len() - 1);
+
+    let friend = FriendRepo::new(db.clone())
+        .get(&fixture.friends[0].user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(friend.display_name, "renamed");
+    assert_eq!(friend.trust_level, TrustLevel::Nuisance);
+    let worlds = WorldRepo::new(db.clone());
+    let newer = worlds.get(&fixture.cache_worlds[0].id).await.unwrap();
+    assert_eq!(newer.unwrap().name, "newer");
+    let older = worlds.get(&fixture.cache_worlds[1].id).await.unwrap();
+    assert_ne!(older.unwrap().name, "older");
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_moderation_sync_keeps_the_list_when_the_history_fails() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+    use surrealdb_test::repo::moderation::ModerationRepo;
+    use surrealdb_test::repo::moderation_history::ModerationHistoryRepo;
+    use surrealdb_test::repo::table::TableRepo;
+
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "moderation-sync");
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    Importer::new(db.clone(), BatchOptions::default())
+        .import(&path)
+        .await
+        .unwrap();
+    let repo = ModerationRepo::new(db.clone());
+    let history = ModerationHistoryRepo::new(db.clone());
+    let before = repo.all().await.unwrap();
+    let events = history.all().await.unwrap();
+    assert!(!before.is_empty());
+
+    let tables = TableRepo::new(db.clone());
+    tables
+        .run("DEFINE FIELD action ON moderation_history ASSERT $value = 'never'")
+        .await
+        .unwrap();
+    assert!(repo.sync(&[], Utc::now()).await.is_err());
+    assert_eq!(repo.all().await.unwrap().len(), before.len());
+    assert_eq!(history.all().await.unwrap(), events);
+
+    tables
+        .run("REMOVE FIELD action ON moderation_history")
+        .await
+        .unwrap();
+    let changes = repo.sync(&[], Utc::now()).await.unwrap();
+    assert!(!changes.is_empty());
+    assert!(repo.all().await.unwrap().is_empty());
+    assert_eq!(
+        history.all().await.unwrap().len(),
+        events.len() + changes.len()
+    );
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_purge_forgets_a_player() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+    use surrealdb_test::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+    use surrealdb_test::repo::purge::Purger;
+
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "purge");
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    Importer::new(db.clone(), BatchOptions::default())
+        .import(&path)
+        .await
+        .unwrap();
+
+    let user_id = &fixture.players[0].user_id;
+    let repo = GamelogJoinLeaveRepo::new(db.clone());
+    let before = repo.find_by_player(user_id).await.unwrap().len();
+    assert!(before > 0);
+
+    let report = Purger::new(db.clone())
+        .dry_run(true)
+        .forget(user_id)
+        .await
+        .unwrap();
+    assert!(report.total() >= before);
+    assert_eq!(repo.find_by_player(user_id).await.unwrap().len(), before);
+
+    let purged = Purger::new(db.clone()).forget(user_id).await.unwrap();
+    assert_eq!(purged.tables, report.tables);
+    assert!(repo.find_by_player(user_id).await.unwrap().is_empty());
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_forget_leaves_no_name_of_the_player() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+    use surrealdb_test::repo::friend::FriendRepo;
+    use surrealdb_test::repo::gamelog_join_leave::GamelogJoinLeaveRepo;
+    use surrealdb_test::repo::purge::Purger;
+    use surrealdb_test::repo::table::TableRepo;
+    use surrealdb_test::zaphkiel::join_leave_event::JoinLeaveEvent;
+
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "forget");
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    Importer::new(db.clone(), BatchOptions::default())
+        .import(&path)
+        .await
+        .unwrap();
+
+    // A player who isn't a friend, so only the game log knows their names.
+    let player = fixture
+        .players
+        .iter()
+        .filter(|player| {
+            !fixture
+                .friends
+                .iter()
+                .any(|friend| friend.user_id == player.user_id)
+        })
+        .max_by_key(|player| player.names.len())
+        .unwrap();
+
+    // Keep the id on the first join under every name, as older game logs only have names.
+    let repo = GamelogJoinLeaveRepo::new(db.clone());
+    let mut names = HashSet::new();
+    let unnamed = repo
+        .find_by_player(&player.user_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|row| {
+            !(row.event == JoinLeaveEvent::Join && names.insert(row.display_name.clone()))
+        })
+        .map(|row| row.id)
+        .collect::<Vec<_>>();
+    assert!(!unnamed.is_empty());
+    db.query("UPDATE gamelog_join_leave SET user_id = NONE WHERE meta::id(id) INSIDE $ids")
+        .bind(("ids", unnamed))
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+
+    let sessions = Session::from_join_leave(&repo.all().await.unwrap());
+    let friends = FriendRepo::new(db.clone()).all().await.unwrap();
+    SocialGraph::build(
+        &sessions,
+        &friends,
+        &fixture.account.user_id,
+        &GraphOptions::default(),
+    )
+    .store(&db)
+    .await
+    .unwrap();
+
+    let tables = TableRepo::new(db.clone());
+    let leaks = || async {
+        let mut leaks = Vec::new();
+        for table in tables.tables().await.unwrap() {
+            let records = format!("{:?}", tables.records(&table).await.unwrap());
+            for (_, name) in &player.names {
+                if records.contains(name.as_str()) {
+                    leaks.push(format!("{} in {}", name, table));
+                }
+            }
+        }
+        leaks
+    };
+    assert!(leaks()
+        .await
+        .iter()
+        .any(|leak| leak.ends_with(" in player")));
+
+    Purger::new(db.clone())
+        .forget(&player.user_id)
+        .await
+        .unwrap();
+    assert_eq!(leaks().await, Vec::<String>::new());
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_graph_store_round_trips() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::repo::wear::WearRepo;
+
+    let fixture = fixture();
+    let tables = fixture_tables(&fixture, "graph-store");
+    let owner = tables.user_id(&fixture.account_prefix()).unwrap();
+    let sessions = Session::from_join_leave(&tables.join_leave);
+    let graph = SocialGraph::build(&sessions, &tables.friends, &owner, &GraphOptions::default());
+    let timeline = AvatarTimeline::build(
+        &tables.feed_avatar,
+        &tables.avatar_history,
+        &owner,
+        &tables.avatars,
+    );
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    // Storing again updates the nodes and edges instead of adding to them.
+    for _ in 0..2 {
+        graph.store(&db).await.unwrap();
+    }
+
+    let stored = SocialGraph::fetch(&db, &GraphOptions::default())
+        .await
+        .unwrap();
+    let nodes = |graph: &SocialGraph| {
+        graph
+            .nodes
+            .iter()
+            .map(|node| (node.kind, node.key.clone(), node.label.clone()))
+            .collect::<HashSet<_>>()
+    };
+    assert_eq!(nodes(&stored), nodes(&graph));
+    assert_eq!(
+        stored.edges.iter().collect::<HashSet<_>>(),
+        graph.edges.iter().collect::<HashSet<_>>()
+    );
+
+    for _ in 0..2 {
+        timeline.store(&db).await.unwrap();
+    }
+    assert_eq!(
+        WearRepo::new(db.clone()).find(None).await.unwrap().len(),
+        timeline.wears.len()
+    );
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_backup_restores_into_an_empty_database() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::backup::snapshot::{restore, BackupFormat, Snapshot};
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "backup");
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("source").await.unwrap();
+    Importer::new(db.clone(), BatchOptions::default())
+        .import(&path)
+        .await
+        .unwrap();
+    let snapshot = Snapshot::read(&db).await.unwrap();
+
+    for format in [BackupFormat::Surql, BackupFormat::NdJsonGz] {
+        let backup = TempFile::new(&format!("backup.{}", format.extension()));
+        let manifest = snapshot.write(&backup, format).unwrap();
+
+        db.use_ns("test")
+            .use_db(format!("{:?}", format))
+            .await
+            .unwrap();
+        let report = restore(&db, &backup).await.unwrap();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(Snapshot::read(&db).await.unwrap().manifest(), manifest);
+        assert!(restore(&db, &backup).await.is_err());
+    }
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_anonymised_copy_leaks_no_user_id() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::export::anonymise::Anonymiser;
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+    use surrealdb_test::models::tenant::Tenant;
+    use surrealdb_test::repo::table::TableRepo;
+
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "anonymise");
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    let from = Tenant {
+        ns: "test".to_string(),
+        db: "source".to_string(),
+    };
+    let to = Tenant {
+        ns: "test".to_string(),
+        db: "anonymised".to_string(),
+    };
+    from.select(&db).await.unwrap();
+    Importer::new(db.clone(), BatchOptions::default())
+        .import(&path)
+        .await
+        .unwrap();
+
+    let anonymiser = Anonymiser::new("key");
+    let reports = anonymiser
+        .copy_database(&db, &from, &to, BatchOptions::default())
+        .await
+        .unwrap();
+    assert!(reports.iter().all(|report| report.batch.failed.is_empty()));
+
+    // The `world` and `avatar` caches are shared, and not copied.
+    let copied = |table: &str| !["world", "avatar", "script_migration"].contains(&table);
+    let repo = TableRepo::new(db.clone());
+    let mut counts = Vec::new();
+    from.select(&db).await.unwrap();
+    for table in repo.tables().await.unwrap() {
+        if copied(&table) {
+            counts.push((table.clone(), repo.count(&table).await.unwrap()));
+        }
+    }
+    to.select(&db).await.unwrap();
+    for (table, count) in &counts {
+        assert_eq!(repo.count(table).await.unwrap(), *count, "{}", table);
+    }
+    let mut written = BTreeMap::<&str, usize>::new();
+    for report in reports.iter().filter(|report| copied(&report.table)) {
+        *written.entry(&report.table).or_default() += report.batch.written;
+    }
+    for (table, written) in written {
+        assert_eq!(written, repo.count(table).await.unwrap(), "{}", table);
+    }
+
+    for table in repo.tables().await.unwrap() {
+        let records = format!("{:?}", repo.records(&table).await.unwrap());
+        for player in &fixture.players {
+            assert!(
+                !records.contains(&player.user_id),
+                "{} in {}",
+                player.user_id,
+                table
+            );
+        }
+    }
+
+    let other = |name: &str| Tenant {
+        ns: "test".to_string(),
+        db: name.to_string(),
+    };
+    from.select(&db).await.unwrap();
+    repo.run("DEFINE TABLE diary").await.unwrap();
+    assert_eq!(repo.count("diary").await.unwrap(), 0);
+    anonymiser
+        .copy_database(&db, &from, &other("empty"), BatchOptions::default())
+        .await
+        .unwrap();
+
+    from.select(&db).await.unwrap();
+    repo.run("CREATE diary:1 SET text = 'usr_1234'")
+        .await
+        .unwrap();
+    assert_eq!(repo.count("diary").await.unwrap(), 1);
+    let error = anonymiser
+        .copy_database(&db, &from, &other("other"), BatchOptions::default())
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("unknown table diary"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_export_pages_through_the_date_range() {
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::export::exporter::{ExportFilter, ExportFormat, ExportTable, Exporter};
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "export");
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    Importer::new(db.clone(), BatchOptions::default())
+        .import(&path)
+        .await
+        .unwrap();
+
+    let tables = VrcxTables::read(&path, SourceTimezone::default()).unwrap();
+    let mut times = tables
+        .join_leave
+        .iter()
+        .map(|row| row.created_at)
+        .collect::<Vec<_>>();
+    times.sort();
+    let filter = ExportFilter {
+        from: Some(times[times.len() / 4]),
+        to: Some(times[times.len() * 3 / 4]),
+        tables: Vec::new(),
+    };
+    let whole = Exporter::new(ExportFormat::NdJson).filter(filter.clone());
+    let paged = Exporter {
+        page_size: 7,
+        ..whole.clone()
+    };
+
+    for table in ExportTable::ALL {
+        let mut expected = Vec::new();
+        let count = match table {
+            ExportTable::GamelogLocation => whole.write(tables.locations.clone(), &mut expected),
+            ExportTable::GamelogJoinLeave => whole.write(tables.join_leave.clone(), &mut expected),
+            ExportTable::FriendLogCurrent => whole.write(tables.friends.clone(), &mut expected),
+            ExportTable::Sessions => {
+                whole.write(Session::from_join_leave(&tables.join_leave), &mut expected)
+            }
+        }
+        .unwrap();
+
+        let mut out = Vec::new();
+        let written = paged.export_table(&db, table, &mut out).await.unwrap();
+        assert!(written > 0, "{}", table.name());
+        assert_eq!(written, count, "{}", table.name());
+        let lines = |out: &[u8]| {
+            let mut lines = String::from_utf8(out.to_vec())
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+                .map(|mut row| {
+                    // The sources are tagged on import.
+                    row.as_object_mut().unwrap().remove("sources");
+                    row.to_string()
+                })
+                .collect::<Vec<_>>();
+            lines.sort();
+            lines
+        };
+        assert_eq!(lines(&out), lines(&expected), "{}", table.name());
+    }
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_api_routes() {
+    use axum::http::StatusCode;
+    use chrono::SecondsFormat;
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::api::server::{router, Player};
+    use surrealdb_test::import::batch::BatchOptions;
+    use surrealdb_test::import::importer::Importer;
+    use surrealdb_test::models::online_heatmap::OnlineHeatmap;
+    use surrealdb_test::models::usr_friend_log_current::UsrFriendLogCurrent;
+    use surrealdb_test::models::world::EnrichedLocation;
+    use surrealdb_test::models::world_stats::WorldStats;
+    use surrealdb_test::zaphkiel::join_leave_event::JoinLeaveEvent;
+
+    let fixture = fixture();
+    let path = TempFile::sqlite(&fixture, "api");
+    let tables = VrcxTables::read(&path, SourceTimezone::default()).unwrap();
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    Importer::new(db.clone(), BatchOptions::default())
+        .import(&path)
+        .await
+        .unwrap();
+    // The caches are imported into the same database, so it is the shared one too.
+    let api = || router(db.clone(), db.clone());
+    let get = |uri: String| async move {
+        let (status, body) = api_get(api(), &uri).await;
+        assert_eq!(status, StatusCode::OK, "{}: {}", uri, body);
+        body
+    };
+
+    let player = &fixture.players[0];
+    let body = get(format!("/players/{}", player.user_id)).await;
+    let body: Player = serde_json::from_value(body).unwrap();
+    let joins = tables
+        .join_leave
+        .iter()
+        .filter(|row| row.user_id.as_deref() == Some(player.user_id.as_str()))
+        .filter(|row| row.event == JoinLeaveEvent::Join)
+        .count();
+    assert_eq!(body.user_id, player.user_id);
+    assert_eq!(body.sessions.len(), joins);
+
+    let world = &fixture.cache_worlds[0];
+    let (world_id, world_name) = (&world.id, &world.name);
+    let body = get(format!("/worlds/{}/visits", world_id)).await;
+    let visits: Vec<EnrichedLocation> = serde_json::from_value(body).unwrap();
+    let expected = tables
+        .locations
+        .iter()
+        .filter(|row| &row.world_instance.world_id == world_id)
+        .count();
+    assert!(expected > 0);
+    assert_eq!(visits.len(), expected);
+    assert!(visits
+        .iter()
+        .all(|visit| visit.world.as_ref().map(|world| &world.name) == Some(world_name)));
+
+    let sessions = Session::from_join_leave(&tables.join_leave);
+    let mut starts = sessions
+        .iter()
+        .filter_map(|session| session.joined_at)
+        .collect::<Vec<_>>();
+    starts.sort();
+    let (from, to) = (starts[starts.len() / 4], starts[starts.len() * 3 / 4]);
+    let body = get(format!(
+        "/sessions?from={}&to={}",
+        from.to_rfc3339_opts(SecondsFormat::Secs, true),
+        to.to_rfc3339_opts(SecondsFormat::Secs, true)
+    ))
+    .await;
+    let body: Vec<Session> = serde_json::from_value(body).unwrap();
+    let expected = sessions
+        .iter()
+        .filter(|session| session.joined_at.is_some_and(|at| from <= at && at < to))
+        .count();
+    assert!(expected > 0);
+    assert_eq!(body.len(), expected);
+
+    let body: Vec<WorldStats> = serde_json::from_value(get("/stats/worlds".into()).await).unwrap();
+    assert_eq!(body, WorldStats::from_locations(&tables.locations));
+
+    let body = get("/friends".into()).await;
+    let friends: Vec<UsrFriendLogCurrent> = serde_json::from_value(body).unwrap();
+    assert_eq!(friends.len(), fixture.friends.len());
+
+    let friend = &fixture.friends[0].user_id;
+    let body = get(format!("/friends/{}/online?tz=utc", friend)).await;
+    let heatmap: OnlineHeatmap = serde_json::from_value(body).unwrap();
+    assert_eq!(&heatmap.user_id, friend);
+    assert!(heatmap.sessions > 0);
+}
+
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn test_api_reports_database_errors() {
+    use axum::http::StatusCode;
+    use surrealdb::engine::local::Mem;
+    use surrealdb::Surreal;
+    use surrealdb_test::api::server::router;
+    use surrealdb_test::repo::table::TableRepo;
+
+    let db = Surreal::new::<Mem>(()).await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    TableRepo::new(db.clone())
+        .run("CREATE friend_log_current:usr_1234 SET display_name = [], trust_level = 5")
+        .await
+        .unwrap();
+
+    let (status, body) = api_get(router(db.clone(), db), "/friends").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body["error"]
+        .as_str()
+        .is_some_and(|error| !error.is_empty()));
+}